@@ -0,0 +1,363 @@
+// Python SR25519 Bindings
+//
+// Copyright 2018-2020 Stichting Polkascan (Polkascan Foundation).
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Arithmetic in the field of integers modulo 2^255 - 19, and the Ristretto encoding
+//! and decoding formulas built on it.
+//!
+//! curve25519-dalek keeps its field arithmetic and the Edwards representative of a
+//! Ristretto point private, so converting between the Ristretto and Edwards encodings
+//! needs its own. This is only used on public data, so it is not constant time.
+
+const P: [u64; 4] = [0xffff_ffff_ffff_ffed, 0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff, 0x7fff_ffff_ffff_ffff];
+// (p - 5) / 8
+const P58: [u64; 4] = [0xffff_ffff_ffff_fffd, 0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff, 0x0fff_ffff_ffff_ffff];
+
+// The Edwards curve constant d = -121665/121666, and sqrt(-1)
+const EDWARDS_D: [u8; 32] = [
+    0xa3, 0x78, 0x59, 0x13, 0xca, 0x4d, 0xeb, 0x75, 0xab, 0xd8, 0x41, 0x41, 0x4d, 0x0a, 0x70, 0x00,
+    0x98, 0xe8, 0x79, 0x77, 0x79, 0x40, 0xc7, 0x8c, 0x73, 0xfe, 0x6f, 0x2b, 0xee, 0x6c, 0x03, 0x52,
+];
+const SQRT_M1: [u8; 32] = [
+    0xb0, 0xa0, 0x0e, 0x4a, 0x27, 0x1b, 0xee, 0xc4, 0x78, 0xe4, 0x2f, 0xad, 0x06, 0x18, 0x43, 0x2f,
+    0xa7, 0xd7, 0xfb, 0x3d, 0x99, 0x00, 0x4d, 0x2b, 0x0b, 0xdf, 0xc1, 0x4f, 0x80, 0x24, 0x83, 0x2b,
+];
+
+/// A field element, always kept fully reduced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct FieldElement([u64; 4]);
+
+impl FieldElement {
+    pub(crate) fn zero() -> FieldElement {
+        FieldElement([0; 4])
+    }
+
+    pub(crate) fn one() -> FieldElement {
+        FieldElement([1, 0, 0, 0])
+    }
+
+    /// Reads a little-endian encoding, ignoring the top bit as Ed25519 does.
+    pub(crate) fn from_bytes(bytes: &[u8; 32]) -> FieldElement {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(&bytes[i * 8..i * 8 + 8]);
+            *limb = u64::from_le_bytes(word);
+        }
+        limbs[3] &= 0x7fff_ffff_ffff_ffff;
+        FieldElement(Self::reduce(limbs))
+    }
+
+    pub(crate) fn to_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            bytes[i * 8..i * 8 + 8].copy_from_slice(&limb.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Whether the element is odd, which is what "negative" means for these encodings.
+    pub(crate) fn is_negative(&self) -> bool {
+        self.0[0] & 1 == 1
+    }
+
+    pub(crate) fn add(&self, other: &FieldElement) -> FieldElement {
+        let mut sum = [0u64; 4];
+        let mut carry = 0u128;
+        for (i, limb) in sum.iter_mut().enumerate() {
+            let cur = self.0[i] as u128 + other.0[i] as u128 + carry;
+            *limb = cur as u64;
+            carry = cur >> 64;
+        }
+        // Both inputs are below 2^255, so the sum fits in 256 bits
+        FieldElement(Self::reduce(sum))
+    }
+
+    pub(crate) fn neg(&self) -> FieldElement {
+        FieldElement::zero().sub(self)
+    }
+
+    pub(crate) fn sub(&self, other: &FieldElement) -> FieldElement {
+        let (difference, borrow) = Self::sub_limbs(&self.0, &other.0);
+        if borrow {
+            let mut sum = [0u64; 4];
+            let mut carry = 0u128;
+            for (i, limb) in sum.iter_mut().enumerate() {
+                let cur = difference[i] as u128 + P[i] as u128 + carry;
+                *limb = cur as u64;
+                carry = cur >> 64;
+            }
+            FieldElement(sum)
+        } else {
+            FieldElement(difference)
+        }
+    }
+
+    pub(crate) fn mul(&self, other: &FieldElement) -> FieldElement {
+        let mut wide = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let cur = wide[i + j] as u128 + (self.0[i] as u128) * (other.0[j] as u128) + carry;
+                wide[i + j] = cur as u64;
+                carry = cur >> 64;
+            }
+            wide[i + 4] = carry as u64;
+        }
+
+        // 2^256 = 38 (mod p), so fold the high half onto the low half
+        let mut folded = [0u64; 4];
+        let mut carry = 0u128;
+        for i in 0..4 {
+            let cur = wide[i] as u128 + 38 * wide[i + 4] as u128 + carry;
+            folded[i] = cur as u64;
+            carry = cur >> 64;
+        }
+        while carry > 0 {
+            let mut extra = carry * 38;
+            carry = 0;
+            for limb in folded.iter_mut() {
+                let cur = *limb as u128 + extra;
+                *limb = cur as u64;
+                extra = cur >> 64;
+            }
+            carry += extra;
+        }
+        FieldElement(Self::reduce(folded))
+    }
+
+    pub(crate) fn square(&self) -> FieldElement {
+        self.mul(self)
+    }
+
+    fn pow(&self, exponent: &[u64; 4]) -> FieldElement {
+        let mut result = FieldElement::one();
+        for limb in exponent.iter().rev() {
+            for bit in (0..64).rev() {
+                result = result.square();
+                if (limb >> bit) & 1 == 1 {
+                    result = result.mul(self);
+                }
+            }
+        }
+        result
+    }
+
+    /// Computes sqrt(u/v) exactly as curve25519-dalek does: returns whether u/v is square,
+    /// and the nonnegative root, or sqrt(i * u/v) if it is not.
+    pub(crate) fn sqrt_ratio_i(u: &FieldElement, v: &FieldElement) -> (bool, FieldElement) {
+        let v3 = v.square().mul(v);
+        let v7 = v3.square().mul(v);
+        let mut r = u.mul(&v3).mul(&u.mul(&v7).pow(&P58));
+        let check = v.mul(&r.square());
+
+        let i = FieldElement::from_bytes(&SQRT_M1);
+        let correct_sign_sqrt = check == *u;
+        let flipped_sign_sqrt = check == u.neg();
+        let flipped_sign_sqrt_i = check == u.neg().mul(&i);
+        if flipped_sign_sqrt || flipped_sign_sqrt_i {
+            r = r.mul(&i);
+        }
+        if r.is_negative() {
+            r = r.neg();
+        }
+        (correct_sign_sqrt || flipped_sign_sqrt, r)
+    }
+
+    fn sub_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], bool) {
+        let mut difference = [0u64; 4];
+        let mut borrow = false;
+        for i in 0..4 {
+            let (d1, b1) = a[i].overflowing_sub(b[i]);
+            let (d2, b2) = d1.overflowing_sub(borrow as u64);
+            difference[i] = d2;
+            borrow = b1 || b2;
+        }
+        (difference, borrow)
+    }
+
+    // Reduces any 256-bit value to its canonical representative
+    fn reduce(mut limbs: [u64; 4]) -> [u64; 4] {
+        loop {
+            let (difference, borrow) = Self::sub_limbs(&limbs, &P);
+            if borrow {
+                return limbs;
+            }
+            limbs = difference;
+        }
+    }
+}
+
+pub(crate) fn edwards_d() -> FieldElement {
+    FieldElement::from_bytes(&EDWARDS_D)
+}
+
+/// Decodes a compressed Edwards point, which must already be known to be valid, into its
+/// affine `(x, y)` coordinates exactly as curve25519-dalek's decompression does.
+pub(crate) fn edwards_decode(encoding: &[u8; 32]) -> (FieldElement, FieldElement) {
+    // x^2 = (y^2 - 1) / (d * y^2 + 1), with the sign of x in the top bit
+    let y = FieldElement::from_bytes(encoding);
+    let yy = y.square();
+    let (_, mut x) = FieldElement::sqrt_ratio_i(&yy.sub(&FieldElement::one()), &edwards_d().mul(&yy).add(&FieldElement::one()));
+    if x.is_negative() != (encoding[31] >> 7 == 1) {
+        x = x.neg();
+    }
+    (x, y)
+}
+
+/// Decodes a Ristretto encoding, which must already be known to be valid, into the affine
+/// `(x, y)` coordinates of the Edwards point curve25519-dalek uses to represent it.
+pub(crate) fn ristretto_decode(encoding: &[u8; 32]) -> (FieldElement, FieldElement) {
+    let one = FieldElement::one();
+    let s = FieldElement::from_bytes(encoding);
+    let ss = s.square();
+    let u1 = one.sub(&ss);
+    let u2 = one.add(&ss);
+    let u2_sqr = u2.square();
+
+    let v = edwards_d().neg().mul(&u1.square()).sub(&u2_sqr);
+    let (_, invsqrt) = FieldElement::sqrt_ratio_i(&one, &v.mul(&u2_sqr));
+
+    let dx = invsqrt.mul(&u2);
+    let dy = invsqrt.mul(&dx.mul(&v));
+    let mut x = s.add(&s).mul(&dx);
+    if x.is_negative() {
+        x = x.neg();
+    }
+    let y = u1.mul(&dy);
+    (x, y)
+}
+
+/// Computes the Ristretto encoding of the Edwards point with affine coordinates `(x, y)`.
+pub(crate) fn ristretto_encode(x: &FieldElement, y: &FieldElement) -> [u8; 32] {
+    let one = FieldElement::one();
+    let t = x.mul(y);
+    let sqrt_m1 = FieldElement::from_bytes(&SQRT_M1);
+    // 1/sqrt(a - d) where a = -1
+    let (_, invsqrt_a_minus_d) = FieldElement::sqrt_ratio_i(&one, &one.neg().sub(&edwards_d()));
+
+    let u1 = one.add(y).mul(&one.sub(y));
+    let u2 = x.mul(y);
+    let (_, invsqrt) = FieldElement::sqrt_ratio_i(&one, &u1.mul(&u2.square()));
+    let i1 = invsqrt.mul(&u1);
+    let i2 = invsqrt.mul(&u2);
+    let z_inv = i1.mul(&i2.mul(&t));
+    let mut den_inv = i2;
+
+    let (mut x, mut y) = (*x, *y);
+    if t.mul(&z_inv).is_negative() {
+        let rotated = (y.mul(&sqrt_m1), x.mul(&sqrt_m1));
+        x = rotated.0;
+        y = rotated.1;
+        den_inv = i1.mul(&invsqrt_a_minus_d);
+    }
+    if x.mul(&z_inv).is_negative() {
+        y = y.neg();
+    }
+
+    let mut s = den_inv.mul(&one.sub(&y));
+    if s.is_negative() {
+        s = s.neg();
+    }
+    s.to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::constants::{ED25519_BASEPOINT_POINT, RISTRETTO_BASEPOINT_POINT};
+    use curve25519_dalek::edwards::CompressedEdwardsY;
+    use curve25519_dalek::scalar::Scalar;
+    use hex_literal::hex;
+    use rand_core::OsRng;
+
+    // Small multiples of the generator, from the ristretto255 test vectors of RFC 9496
+    const GENERATOR_MULTIPLES: [[u8; 32]; 9] = [
+        hex!("0000000000000000000000000000000000000000000000000000000000000000"),
+        hex!("e2f2ae0a6abc4e71a884a961c500515f58e30b6aa582dd8db6a65945e08d2d76"),
+        hex!("6a493210f7499cd17fecb510ae0cea23a110e8d5b901f8acadd3095c73a3b919"),
+        hex!("94741f5d5d52755ece4f23f044ee27d5d1ea1e2bd196b462166b16152a9d0259"),
+        hex!("da80862773358b466ffadfe0b3293ab3d9fd53c5ea6c955358f568322daf6a57"),
+        hex!("e882b131016b52c1d3337080187cf768423efccbb517bb495ab812c4160ff44e"),
+        hex!("f64746d3c92b13050ed8d80236a7f0007c3b3f962f5ba793d19a601ebb1df403"),
+        hex!("44f53520926ec81fbd5a387845beb7df85a96a24ece18738bdcfa6a7822a176d"),
+        hex!("903293d8f2287ebe10e2374dc1a53e0bc887e592699f02d077d5263cdd55601c"),
+    ];
+
+    fn edwards_encode(x: &FieldElement, y: &FieldElement) -> [u8; 32] {
+        let mut bytes = y.to_bytes();
+        bytes[31] |= (x.is_negative() as u8) << 7;
+        bytes
+    }
+
+    #[test]
+    fn test_constants() {
+        let d = edwards_d();
+        let minus_121665 = FieldElement([121665, 0, 0, 0]).neg();
+        assert_eq!(d.mul(&FieldElement([121666, 0, 0, 0])), minus_121665);
+
+        let i = FieldElement::from_bytes(&SQRT_M1);
+        assert_eq!(i.square(), FieldElement::one().neg());
+    }
+
+    #[test]
+    fn test_reduction() {
+        // p itself and 2^255 - 1 reduce to 0 and 18
+        let mut p = [0xffu8; 32];
+        p[0] = 0xed;
+        p[31] = 0x7f;
+        assert_eq!(FieldElement::from_bytes(&p), FieldElement::zero());
+        assert_eq!(FieldElement::from_bytes(&[0xff; 32]), FieldElement([18, 0, 0, 0]));
+
+        let minus_one = FieldElement::one().neg();
+        assert_eq!(minus_one.mul(&minus_one), FieldElement::one());
+        assert_eq!(minus_one.add(&FieldElement::one()), FieldElement::zero());
+    }
+
+    #[test]
+    fn test_ristretto_vectors() {
+        let mut point = RISTRETTO_BASEPOINT_POINT * Scalar::zero();
+        for encoding in GENERATOR_MULTIPLES.iter() {
+            assert_eq!(&point.compress().to_bytes(), encoding);
+            if point.compress().to_bytes() != [0u8; 32] {
+                let (x, y) = ristretto_decode(encoding);
+                assert_eq!(&ristretto_encode(&x, &y), encoding);
+            }
+            point += RISTRETTO_BASEPOINT_POINT;
+        }
+    }
+
+    #[test]
+    fn test_against_dalek() {
+        for _ in 0..64 {
+            let k = Scalar::random(&mut OsRng);
+            let ristretto = (RISTRETTO_BASEPOINT_POINT * k).compress().to_bytes();
+            let edwards = (ED25519_BASEPOINT_POINT * k).compress().to_bytes();
+
+            // Decoding and re-encoding round-trips
+            let (x, y) = ristretto_decode(&ristretto);
+            assert_eq!(ristretto_encode(&x, &y), ristretto);
+
+            // The decoded point is in the same coset as k * B, which encodes the same way
+            let decoded = CompressedEdwardsY(edwards_encode(&x, &y)).decompress().unwrap();
+            let expected = CompressedEdwardsY(edwards).decompress().unwrap();
+            assert_eq!(decoded.mul_by_cofactor(), expected.mul_by_cofactor());
+
+            let (x, y) = edwards_decode(&edwards);
+            assert_eq!(edwards_encode(&x, &y), edwards);
+            assert_eq!(ristretto_encode(&x, &y), ristretto);
+        }
+    }
+}
@@ -20,23 +20,92 @@
 //! [schnorrkel](https://crates.io/crates/schnorrkel), allowing for some limited
 //! use and management of sr25519 elliptic keys.
 
+mod field;
+
 use pyo3::exceptions;
 use pyo3::prelude::*;
-use pyo3::types::{PyAny, PyBytes, PyTuple};
-use pyo3::{wrap_pyfunction, FromPyObject, IntoPy, PyObject};
+use pyo3::buffer::PyBuffer;
+use pyo3::types::{PyAny, PyByteArray, PyBytes, PyString, PyTuple};
+use pyo3::{wrap_pyfunction, FromPyObject, IntoPy, PyNativeType, PyObject};
 
+use curve25519_dalek::constants::{EIGHT_TORSION, RISTRETTO_BASEPOINT_TABLE};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{Identity, IsIdentity, VartimeMultiscalarMul};
 
-use schnorrkel::context::{signing_context, SigningTranscript};
+use schnorrkel::context::{attach_rng, signing_context, SigningContext as SchnorrkelSigningContext, SigningTranscript};
+use merlin::Transcript;
 use schnorrkel::keys::{ExpansionMode, MiniSecretKey, PublicKey, SecretKey, Keypair as SchnorrkelKeypair};
 use schnorrkel::sign::Signature;
 use schnorrkel::derive::{Derivation, ChainCode};
+use schnorrkel::musig::{Commitment, CommitStage, Cosignature, CosignStage, MuSig, Reveal, RevealStage};
+use schnorrkel::verify_batch as schnorrkel_verify_batch;
+use schnorrkel::vrf::{VRFPreOut, VRFProofBatchable, KUSAMA_VRF, VRF_PREOUT_LENGTH, VRF_PROOF_BATCHABLE_LENGTH};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use bip39::{Language, Mnemonic};
+use blake2::{Blake2b, VarBlake2b};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use blake2::digest::{Update, VariableOutput};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac, NewMac};
+use pbkdf2::pbkdf2;
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+use rand_core::{OsRng, RngCore};
+use scrypt::Params as ScryptParams;
+use serde_json::json;
+use sha2::Sha512;
+
+use subtle::ConstantTimeEq;
+use xsalsa20poly1305::{Nonce as XSalsaNonce, XSalsa20Poly1305};
+use xsalsa20poly1305::aead::{Aead as _, KeyInit as _};
+use zeroize::Zeroize;
+
+use std::time::{Duration, Instant};
 
-pub use schnorrkel::keys::{PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH, MINI_SECRET_KEY_LENGTH};
+pub use schnorrkel::keys::{PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH, MINI_SECRET_KEY_LENGTH, KEYPAIR_LENGTH};
 pub use schnorrkel::sign::SIGNATURE_LENGTH;
 pub use schnorrkel::derive::CHAIN_CODE_LENGTH;
 
-const SIGNING_CTX: &'static [u8] = b"substrate";
+// Secret keys are a 32-byte scalar followed by a 32-byte nonce throughout this module
+const _: () = assert!(SECRET_KEY_LENGTH == 64);
+
+const SIGNING_CTX: &[u8] = b"substrate";
+const DEV_PHRASE: &str = "bottom drive obey lake curtain smoke basket hold race lonely fit walk";
+const SCALAR_LENGTH: usize = 32;
+const COUNTER_KDF_INFO: &[u8] = b"sr25519-counter-keypair";
+const FINGERPRINT_LENGTH: usize = 4;
+const PASSWORD_KDF_PARAMS: (u32, u32, u32) = (65536, 3, 1);
+// Upper bounds on the caller's Argon2id memory (in KiB), iterations and parallelism
+const PASSWORD_KDF_MAX_PARAMS: (u32, u32, u32) = (4 * 1024 * 1024, 64, 16);
+const ECIES_INFO: &[u8] = b"sr25519-ecies-xchacha20poly1305";
+const PREHASH_THRESHOLD: usize = 256;
+const SS58_PREFIX: &[u8] = b"SS58PRE";
+const SS58_MAX_NETWORK_ID: u16 = 16383;
+const SS58_CHECKSUM_LENGTH: usize = 2;
+const PREHASH_FLAG_RAW: u8 = 0;
+const PREHASH_FLAG_BLAKE2B: u8 = 1;
+const TIMESTAMP_LENGTH: usize = 8;
+const MUSIG_COMMITMENT_LENGTH: usize = 16;
+const MUSIG_REVEAL_LENGTH: usize = 96;
+const HALF_AGGREGATE_LABEL: &[u8] = b"sr25519-half-aggregate";
+const VRF_OUTPUT_MAX_BYTES: usize = 65536;
+// (N, p, r), as polkadot-js uses them
+const KEYSTORE_SCRYPT_PARAMS: (u32, u32, u32) = (32768, 1, 8);
+const KEYSTORE_SALT_LENGTH: usize = 32;
+const KEYSTORE_NONCE_LENGTH: usize = 24;
+const PKCS8_HEADER: &[u8] = &[48, 83, 2, 1, 1, 48, 5, 6, 3, 43, 101, 112, 4, 34, 4, 32];
+const PKCS8_DIVIDER: &[u8] = &[161, 35, 3, 33, 0];
+
+// Raised for every invalid input, with a `code` attribute naming the kind of failure:
+// INVALID_LENGTH, INVALID_KEY, INVALID_SIGNATURE, INVALID_POINT, INVALID_VRF,
+// INVALID_ENCODING, INVALID_MNEMONIC, INVALID_PATH, MUSIG_ERROR, KEYSTORE_ERROR (JSON
+// keystores only), DECRYPTION_FAILED (`decrypt_with_secret`), CONTEXT_MISMATCH,
+// SELF_CHECK_FAILED or INVALID_ARGUMENT. It subclasses ValueError, so existing handlers still catch it.
+pyo3::create_exception!(sr25519, Sr25519Error, exceptions::PyValueError);
 
 pub struct Seed([u8; MINI_SECRET_KEY_LENGTH]);
 pub struct Keypair([u8; PUBLIC_KEY_LENGTH], [u8; SECRET_KEY_LENGTH]);
@@ -46,16 +115,78 @@ pub struct Sig([u8; SIGNATURE_LENGTH]);
 pub struct Message(Vec<u8>);
 pub struct ExtendedPubKey([u8; CHAIN_CODE_LENGTH], [u8; PUBLIC_KEY_LENGTH]);
 pub struct ExtendedKeypair([u8; CHAIN_CODE_LENGTH], [u8; PUBLIC_KEY_LENGTH], [u8; SECRET_KEY_LENGTH]);
+pub struct ScalarBytes([u8; SCALAR_LENGTH]);
+pub struct VrfOutput([u8; VRF_PREOUT_LENGTH]);
+pub struct VrfProof([u8; VRF_PROOF_BATCHABLE_LENGTH]);
+
+// Wipe secret key material when the value is dropped, so it doesn't linger in memory
+impl Drop for Seed {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for Keypair {
+    fn drop(&mut self) {
+        self.1.zeroize();
+    }
+}
+
+impl Drop for PrivKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for ExtendedKeypair {
+    fn drop(&mut self) {
+        self.0.zeroize();
+        self.2.zeroize();
+    }
+}
 
 
 // Helper functions
-fn _check_pybytes_len(bytes: &PyBytes, length: usize) -> PyResult<&PyBytes> {
-    bytes.len().and_then(
-        |actual_len| if actual_len == length {
-            Ok(bytes)
-        } else {
-            Err(exceptions::PyValueError::new_err(format!("Expected bytes of length {}, got {}", length, actual_len)))
-        })
+// Builds an `Sr25519Error` with the given code, to raise in place of a bare ValueError
+fn _error(code: &str, message: impl Into<String>) -> PyErr {
+    Python::with_gil(|py| {
+        let err: PyObject = Sr25519Error::new_err(message.into()).into_py(py);
+        err.as_ref(py).setattr("code", code).expect("exception instances accept new attributes; qed");
+        PyErr::from_instance(err.as_ref(py))
+    })
+}
+
+// Copies the contents of `bytes`, `bytearray` or any object supporting the buffer protocol
+fn _extract_bytes(obj: &PyAny) -> Option<Vec<u8>> {
+    if let Ok(bytes) = obj.downcast::<PyBytes>() {
+        return Some(bytes.as_bytes().to_vec());
+    }
+    if let Ok(bytearray) = obj.downcast::<PyByteArray>() {
+        return Some(bytearray.to_vec());
+    }
+    PyBuffer::<u8>::get(obj).ok()
+        .and_then(|buffer| buffer.to_vec(obj.py()).ok())
+}
+
+fn _check_bytes_len(bytes: Vec<u8>, length: usize) -> PyResult<Vec<u8>> {
+    if bytes.len() == length {
+        Ok(bytes)
+    } else {
+        Err(_error("INVALID_LENGTH", format!("Expected bytes of length {}, got {}", length, bytes.len())))
+    }
+}
+
+// Copies the bytes-like item at `index` of a tuple, checking its length
+fn _extract_tuple_item(tuple: &PyTuple, index: usize, length: usize, type_error: &str) -> PyResult<Vec<u8>> {
+    _extract_bytes(tuple.get_item(index))
+        .ok_or_else(|| exceptions::PyTypeError::new_err(type_error.to_string()))
+        .and_then(|b| _check_bytes_len(b, length))
+}
+
+fn _decode_hex(value: &str, name: &str) -> PyResult<Vec<u8>> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    hex::decode(digits)
+        .map_err(|err| _error("INVALID_ENCODING", format!("Invalid {} hex: {}", name, err)))
 }
 
 fn _to_pytuple(any: &PyAny) -> PyResult<&PyTuple> {
@@ -63,6 +194,162 @@ fn _to_pytuple(any: &PyAny) -> PyResult<&PyTuple> {
         .map_err(|_| exceptions::PyTypeError::new_err("Expected tuple"))
 }
 
+fn _to_schnorrkel_keypair(keypair: &Keypair) -> PyResult<SchnorrkelKeypair> {
+    let secret = SecretKey::from_bytes(&keypair.1)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+    let public = PublicKey::from_bytes(&keypair.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+    Ok(SchnorrkelKeypair{secret, public})
+}
+
+fn _sum_public_keys(pubkeys: &[PubKey]) -> PyResult<RistrettoPoint> {
+    if pubkeys.is_empty() {
+        return Err(_error("INVALID_ARGUMENT", "Expected at least one public key"));
+    }
+    let mut sum = RistrettoPoint::identity();
+    for (index, pubkey) in pubkeys.iter().enumerate() {
+        let pk = PublicKey::from_bytes(&pubkey.0)
+            .map_err(|err| _error("INVALID_KEY", format!("Invalid public key at index {}: {}", index, err)))?;
+        sum += pk.as_point();
+    }
+    Ok(sum)
+}
+
+fn _to_signatures(signatures: &[Sig]) -> PyResult<Vec<Signature>> {
+    signatures.iter().enumerate()
+        .map(|(index, signature)| Signature::from_bytes(&signature.0)
+            .map_err(|err| _error("INVALID_SIGNATURE", format!("Invalid signature at index {}: {}", index, err))))
+        .collect()
+}
+
+fn _to_public_keys(pubkeys: &[PubKey]) -> PyResult<Vec<PublicKey>> {
+    pubkeys.iter().enumerate()
+        .map(|(index, pubkey)| PublicKey::from_bytes(&pubkey.0)
+            .map_err(|err| _error("INVALID_KEY", format!("Invalid public key at index {}: {}", index, err))))
+        .collect()
+}
+
+// Substrate's BIP39 seed derivation: PBKDF2-HMAC-SHA512 over the mnemonic entropy (not the
+// phrase), salted with "mnemonic" followed by the password, keeping the first 32 bytes.
+#[allow(clippy::manual_is_multiple_of)]
+fn _mini_secret_from_entropy(entropy: &[u8], password: &str) -> PyResult<Seed> {
+    if entropy.len() < 16 || entropy.len() > 32 || entropy.len() % 4 != 0 {
+        return Err(_error("INVALID_LENGTH", format!("Invalid entropy length: {}", entropy.len())));
+    }
+    let salt = format!("mnemonic{}", password);
+    let mut seed = [0u8; 64];
+    pbkdf2::<Hmac<Sha512>>(entropy, salt.as_bytes(), 2048, &mut seed);
+
+    let mut mini_secret = [0u8; MINI_SECRET_KEY_LENGTH];
+    mini_secret.copy_from_slice(&seed[..MINI_SECRET_KEY_LENGTH]);
+    Ok(Seed(mini_secret))
+}
+
+// SCALE compact encoding of a length, as used to prefix encoded strings
+fn _scale_compact_len(len: usize) -> Vec<u8> {
+    match len {
+        0..=0x3f => vec![(len as u8) << 2],
+        0x40..=0x3fff => (((len as u16) << 2) | 0b01).to_le_bytes().to_vec(),
+        0x4000..=0x3fff_ffff => (((len as u32) << 2) | 0b10).to_le_bytes().to_vec(),
+        _ => {
+            let bytes: Vec<u8> = (len as u64).to_le_bytes().iter().cloned()
+                .rev().skip_while(|b| *b == 0).collect::<Vec<u8>>().into_iter().rev().collect();
+            [vec![(((bytes.len() - 4) as u8) << 2) | 0b11], bytes].concat()
+        }
+    }
+}
+
+// Decodes a SCALE compact integer at `offset`, advancing it past the encoding
+fn _scale_decode_compact(data: &[u8], offset: &mut usize) -> PyResult<u128> {
+    let truncated = || _error("INVALID_ENCODING", "Truncated compact integer");
+    let first = *data.get(*offset).ok_or_else(truncated)?;
+    let len = match first & 0b11 {
+        0b00 => 1,
+        0b01 => 2,
+        0b10 => 4,
+        _ => (first >> 2) as usize + 5,
+    };
+    if len > 17 {
+        return Err(_error("INVALID_ENCODING", "Compact integer too large"));
+    }
+    let bytes = data.get(*offset..*offset + len).ok_or_else(truncated)?;
+    *offset += len;
+
+    let mut value = [0u8; 16];
+    if len > 4 {
+        value[..len - 1].copy_from_slice(&bytes[1..]);
+        Ok(u128::from_le_bytes(value))
+    } else {
+        value[..len].copy_from_slice(bytes);
+        Ok(u128::from_le_bytes(value) >> 2)
+    }
+}
+
+// Parses a substrate derivation path such as `//hard/soft` into its junctions, each a flag
+// for whether it is hard and its chain code. Numeric junctions are encoded as a u64, and
+// all others as a string, as substrate does.
+fn _parse_derivation_path(path: &str) -> PyResult<Vec<(bool, ChainCode)>> {
+    let invalid = || _error("INVALID_PATH", format!("Invalid derivation path: {}", path));
+    let mut junctions = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        let (hard, segment_start) = if let Some(stripped) = rest.strip_prefix("//") {
+            (true, stripped)
+        } else if let Some(stripped) = rest.strip_prefix('/') {
+            (false, stripped)
+        } else {
+            return Err(invalid());
+        };
+        let end = segment_start.find('/').unwrap_or(segment_start.len());
+        let segment = &segment_start[..end];
+        if segment.is_empty() {
+            return Err(invalid());
+        }
+
+        let junction = match segment.parse::<u64>() {
+            Ok(index) => _index_junction(index),
+            Err(_) => ChainCode(_junction_chain_code(&[_scale_compact_len(segment.len()), segment.as_bytes().to_vec()].concat())),
+        };
+        junctions.push((hard, junction));
+        rest = &segment_start[end..];
+    }
+    Ok(junctions)
+}
+
+// Substrate's chain code for a numeric junction, such as `/5`
+fn _index_junction(index: u64) -> ChainCode {
+    ChainCode(_junction_chain_code(&index.to_le_bytes()))
+}
+
+// Substrate's chain code for a derivation junction: the SCALE-encoded junction, zero-padded
+// to 32 bytes, or its Blake2b-256 hash if it is longer than that.
+fn _junction_chain_code(encoded: &[u8]) -> [u8; CHAIN_CODE_LENGTH] {
+    if encoded.len() > CHAIN_CODE_LENGTH {
+        return _blake2b_256(encoded);
+    }
+    let mut chain_code = [0u8; CHAIN_CODE_LENGTH];
+    chain_code[..encoded.len()].copy_from_slice(encoded);
+    chain_code
+}
+
+fn _blake2b_256(data: &[u8]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    _blake2b(data, &mut hash);
+    hash
+}
+
+fn _blake2b(data: &[u8], hash: &mut [u8]) {
+    let mut hasher = VarBlake2b::new(hash.len()).expect("1 to 64 bytes is a valid Blake2b output length; qed");
+    hasher.update(data);
+    hasher.finalize_variable(|res| hash.copy_from_slice(res));
+}
+
+fn _ss58_checksum(payload: &[u8]) -> [u8; SS58_CHECKSUM_LENGTH] {
+    let mut hash = [0u8; 64];
+    _blake2b(&[SS58_PREFIX, payload].concat(), &mut hash);
+    [hash[0], hash[1]]
+}
+
 // Main interface
 
 /// Signs a message with the given keypair, returning the resulting signature.
@@ -71,16 +358,34 @@ fn _to_pytuple(any: &PyAny) -> PyResult<&PyTuple> {
 ///
 /// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
 /// * `message` - The binary message to sign.
+/// * `encoding` - How to return the signature: `"bytes"` (the default), `"hex"` for a `0x`
+///   prefixed hex string, or `"base58"` for a base58 string.
 ///
 /// # Returns
 ///
-/// A 64-byte signature.
+/// A 64-byte signature, or its encoding as a str.
 ///
 /// # Raises
 ///
-/// * `ValueError` - If either the public or private key is invalid.
-#[pyfunction]
-#[pyo3(text_signature = "(keypair, message)")]
+/// * `ValueError` - If either the public or private key is invalid, or the encoding is not
+///   one of the above.
+#[pyfunction(encoding = "\"bytes\"")]
+#[pyo3(name = "sign")]
+#[pyo3(text_signature = "(keypair, message, encoding='bytes')")]
+pub fn sign_encoded(py: Python, keypair: Keypair, message: Message, encoding: &str) -> PyResult<PyObject> {
+    if !["bytes", "hex", "base58"].contains(&encoding) {
+        return Err(_error("INVALID_ARGUMENT", format!("Invalid encoding: {}", encoding)));
+    }
+    let sig = sign(keypair, message)?;
+    Ok(match encoding {
+        "hex" => format!("0x{}", hex::encode(sig.0)).into_py(py),
+        "base58" => bs58::encode(sig.0).into_string().into_py(py),
+        _ => sig.into_py(py),
+    })
+}
+
+/// Signs a message with the given keypair under the `substrate` context. This is what the
+/// Python `sign` calls, before encoding the signature as asked.
 pub fn sign(keypair: Keypair, message: Message) -> PyResult<Sig> {
     let mut public = [0u8; PUBLIC_KEY_LENGTH];
     let mut private = [0u8; SECRET_KEY_LENGTH];
@@ -88,12 +393,12 @@ pub fn sign(keypair: Keypair, message: Message) -> PyResult<Sig> {
     private.clone_from_slice(&keypair.1[0..SECRET_KEY_LENGTH]);
     let secret = match SecretKey::from_bytes(&private) {
         Ok(some_secret) => some_secret,
-        Err(err) => return Err(exceptions::PyValueError::new_err(format!("Invalid secret key: {}", err.to_string()))),
+        Err(err) => return Err(_error("INVALID_KEY", format!("Invalid secret key: {}", err))),
     };
 
     let public = match PublicKey::from_bytes(&public) {
         Ok(some_public) => some_public,
-        Err(err) => return Err(exceptions::PyValueError::new_err(format!("Invalid public key: {}", err.to_string()))),
+        Err(err) => return Err(_error("INVALID_KEY", format!("Invalid public key: {}", err))),
     };
 
     let context = signing_context(SIGNING_CTX);
@@ -101,202 +406,277 @@ pub fn sign(keypair: Keypair, message: Message) -> PyResult<Sig> {
     Ok(Sig(sig))
 }
 
-/// Verifies that a signature on a given message was generated by private key
-/// corresponding to the specified public key.
+/// Signs a message as `sign` does, then verifies the signature before returning it.
+///
+/// This guards security-critical signers against releasing a bad signature, as a bit flip
+/// in memory or a faulty CPU could produce, which might leak information about the secret
+/// key. It also catches a keypair whose public key doesn't belong to its secret key. Under
+/// correct operation with a consistent keypair the check never fails.
 ///
 /// # Arguments
 ///
-/// * `signature` - The 64-byte sr25519 signature.
-/// * `message` - The binary message on which to verify the signature.
-/// * `pubkey` - The sr25519 public key, as an array of 32 bytes, to use.
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
+/// * `message` - The binary message to sign.
 ///
 /// # Returns
 ///
-/// True if the signature is valid, false otherwise.
+/// A 64-byte signature.
 ///
 /// # Raises
 ///
-/// * `ValueError` - If either the signature or public key are structurally invalid.
+/// * `ValueError` - If either the public or private key is invalid, or, with the code
+///   `SELF_CHECK_FAILED`, if the signature does not verify.
 #[pyfunction]
-#[pyo3(text_signature = "(signature, message, pubkey)")]
-pub fn verify(signature: Sig, message: Message, pubkey: PubKey) -> PyResult<bool> {
-    let sig = match Signature::from_bytes(&signature.0) {
-        Ok(some_sig) => some_sig,
-        Err(err) => return Err(exceptions::PyValueError::new_err(format!("Invalid signature: {}", err.to_string()))),
-    };
-    let pk = match PublicKey::from_bytes(&pubkey.0) {
-        Ok(some_pk) => some_pk,
-        Err(err) => return Err(exceptions::PyValueError::new_err(format!("Invalid public key: {}", err.to_string()))),
-    };
-    let result = pk.verify_simple(SIGNING_CTX, &message.0, &sig);
-    Ok(result.is_ok())
+#[pyo3(text_signature = "(keypair, message)")]
+pub fn sign_checked(keypair: Keypair, message: Message) -> PyResult<Sig> {
+    let keypair = _to_schnorrkel_keypair(&keypair)?;
+    let context = signing_context(SIGNING_CTX);
+    let sig = keypair.sign(context.bytes(&message.0));
+
+    if keypair.public.verify(context.bytes(&message.0), &sig).is_err() {
+        return Err(_error("SELF_CHECK_FAILED", "Signature failed its self-check against the public key"));
+    }
+    Ok(Sig(sig.to_bytes()))
 }
 
-/// Returns a public and private key pair from the given 32-byte seed.
+// Wraps a message as polkadot-js `u8aWrapBytes` does, leaving an already wrapped message alone
+fn _wrap_bytes(message: &[u8]) -> Vec<u8> {
+    const PREFIX: &[u8] = b"<Bytes>";
+    const SUFFIX: &[u8] = b"</Bytes>";
+    if message.len() >= PREFIX.len() + SUFFIX.len() && message.starts_with(PREFIX) && message.ends_with(SUFFIX) {
+        message.to_vec()
+    } else {
+        [PREFIX, message, SUFFIX].concat()
+    }
+}
+
+/// Signs a message wrapped in `<Bytes>` and `</Bytes>`, as wallets do for "sign message".
+///
+/// This matches polkadot-js `signRaw`: the wrapper keeps a signed message from ever being
+/// a valid extrinsic payload. A message that is already wrapped is signed as it is.
 ///
 /// # Arguments
 ///
-/// * `seed` - A 32 byte seed.
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
+/// * `message` - The binary message to sign.
 ///
 /// # Returns
 ///
-/// A tuple containing the 32-byte public key and 64-byte secret key, in that order.
+/// A 64-byte signature over the wrapped message.
+///
+/// # Raises
+///
+/// * `ValueError` - If either the public or private key is invalid.
 #[pyfunction]
-#[pyo3(text_signature = "(seed)")]
-pub fn pair_from_seed(seed: Seed) -> PyResult<Keypair> {
-    let k = MiniSecretKey::from_bytes(&seed.0).expect("32 bytes can always build a key; qed");
-    let kp = k.expand_to_keypair(ExpansionMode::Ed25519);
-
-    Ok(Keypair(kp.public.to_bytes(), kp.secret.to_bytes()))
+#[pyo3(text_signature = "(keypair, message)")]
+pub fn sign_wrapped(keypair: Keypair, message: Message) -> PyResult<Sig> {
+    sign(keypair, Message(_wrap_bytes(&message.0)))
 }
 
-/// Returns the corresponding public key for the given secret key.
+/// Signs a message with a nonce derived only from the secret key and message.
+///
+/// `sign` mixes output of the OS random number generator into the nonce, so signing the
+/// same message twice gives different signatures. Here the random number generator is
+/// replaced by a ChaCha20 generator with a fixed all-zero seed, so the nonce depends only
+/// on the secret key's nonce seed and the signing transcript, and the same keypair and
+/// message always give the same signature.
+///
+/// The signature therefore differs from any `sign` produces, but is an ordinary
+/// signature under the `substrate` context and verifies with `verify`.
 ///
 /// # Arguments
 ///
-/// * `secret_key` - The sr25519 secret key, comprised of the 32 byte scalar and 32 byte nonce.
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
+/// * `message` - The binary message to sign.
 ///
 /// # Returns
 ///
-/// The 32-byte public key corresponding to the provided secret key.
+/// A 64-byte signature.
 ///
 /// # Raises
 ///
-/// * `ValueError` - If the provided secret key is invalid.
+/// * `ValueError` - If either the public or private key is invalid.
 #[pyfunction]
-#[pyo3(text_signature = "(secret_key)")]
-pub fn public_from_secret_key(secret_key: PrivKey) -> PyResult<PubKey> {
-    let sec_key = match SecretKey::from_bytes(&secret_key.0) {
-        Ok(some_key) => some_key,
-        Err(err) => return Err(exceptions::PyValueError::new_err(format!("Invalid secret key: {}", err.to_string()))),
-    };
-    let pub_key = sec_key.to_public();
-
-    Ok(PubKey(pub_key.to_bytes()))
+#[pyo3(text_signature = "(keypair, message)")]
+pub fn sign_deterministic(keypair: Keypair, message: Message) -> PyResult<Sig> {
+    let keypair = _to_schnorrkel_keypair(&keypair)?;
+    let t = attach_rng(signing_context(SIGNING_CTX).bytes(&message.0), ChaCha20Rng::from_seed([0u8; 32]));
+    Ok(Sig(keypair.sign(t).to_bytes()))
 }
 
-
-/// Returns the soft derivation of the public key of the specified child.
+/// Signs a message with the nonce randomness drawn from a caller-supplied seed.
+///
+/// As `sign_deterministic`, but the ChaCha20 generator attached to the transcript is seeded
+/// with `rng_seed` instead of zeros. The same keypair, message and seed always give the
+/// same signature, and different seeds give different ones, which suits test harnesses
+/// and simulations that need reproducible signatures. The nonce still depends on the
+/// secret key, so a reused seed is not a reused nonce across messages.
 ///
 /// # Arguments
 ///
-/// * `extended_pubkey` - The extended public key, comprised of the chain code and public key.
-/// * `id` - The identifier for the child key to derive.
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
+/// * `message` - The binary message to sign.
+/// * `rng_seed` - The 32-byte seed for the nonce generator.
 ///
 /// # Returns
 ///
-/// A new extended public key for the child.
+/// A 64-byte signature.
+///
+/// # Raises
+///
+/// * `ValueError` - If the seed is not 32 bytes, or either the public or private key is invalid.
 #[pyfunction]
-#[pyo3(text_signature = "(extended_pubkey, id)")]
-pub fn derive_pubkey(extended_pubkey: ExtendedPubKey, id: Message) -> PyResult<ExtendedPubKey> {
-    let chain_code = ChainCode(extended_pubkey.0);
-    let pubkey = PublicKey::from_bytes(&extended_pubkey.1)
-        .map_err(|err| exceptions::PyValueError::new_err(format!("Invalid public key: {}", err.to_string())))?;
-    let (new_pubkey, new_chaincode) = pubkey.derived_key_simple(chain_code, &id.0);
-
-    Ok(ExtendedPubKey(new_chaincode.0, new_pubkey.to_bytes()))
+#[pyo3(text_signature = "(keypair, message, rng_seed)")]
+pub fn sign_with_rng_seed(keypair: Keypair, message: Message, rng_seed: Message) -> PyResult<Sig> {
+    let mut seed = [0u8; 32];
+    seed.copy_from_slice(&_check_bytes_len(rng_seed.0, 32)?);
+    let keypair = _to_schnorrkel_keypair(&keypair)?;
+    let t = attach_rng(signing_context(SIGNING_CTX).bytes(&message.0), ChaCha20Rng::from_seed(seed));
+    Ok(Sig(keypair.sign(t).to_bytes()))
 }
 
-/// Returns the soft deriviation of the private and public key of the specified child.
+/// Signs a message under the given context with a deterministically derived nonce.
+///
+/// The nonce scalar is `r = HMAC-Blake2b-512(key = secret_key, data = len(context) || context || message)`
+/// reduced mod the group order, where `secret_key` is the full 64-byte secret key and
+/// `len(context)` is the context length as 8 little-endian bytes. The signature is then
+/// computed exactly as schnorrkel does, with `R = r * B` and `s = c * x + r`, where `c` is
+/// the challenge from the standard transcript for `context` and `message`.
+///
+/// The same keypair, message and context therefore always give the same signature, and
+/// when `context` is `b"substrate"` it verifies with `verify`.
 ///
 /// # Arguments
 ///
-/// * `extended_keypair` - The extended keypair, comprised of the chain code, public key, and private key.
-/// * `id` - The identifier for the child key to derive.
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
+/// * `message` - The binary message to sign.
+/// * `context` - The signing context to use.
 ///
 /// # Returns
 ///
-/// A new extended keypair for the child.
+/// A 64-byte signature.
 ///
-/// *NOTE:* The chain code, public key, and secret key scalar are generated
-/// deterministically, but the secret key nonce is *RANDOM*, even with
-/// identical input.
+/// # Raises
+///
+/// * `ValueError` - If either the public or private key is invalid.
 #[pyfunction]
-#[pyo3(text_signature = "(extended_keypair, id)")]
-pub fn derive_keypair(extended_keypair: ExtendedKeypair, id: Message) -> PyResult<ExtendedKeypair> {
-    let chain_code = ChainCode(extended_keypair.0);
-    let pubkey = PublicKey::from_bytes(&extended_keypair.1)
-        .map_err(|err| exceptions::PyValueError::new_err(format!("Invalid public key: {}", err.to_string())))?;
-    let privkey = SecretKey::from_bytes(&extended_keypair.2)
-        .map_err(|err| exceptions::PyValueError::new_err(format!("Invalid secret key: {}", err.to_string())))?;
-    let keypair = SchnorrkelKeypair{secret: privkey, public: pubkey};
-    let (new_keypair, new_chaincode) = keypair.derived_key_simple(chain_code, &id.0);
+#[pyo3(text_signature = "(keypair, message, context)")]
+pub fn sign_rfc6979(keypair: Keypair, message: Message, context: Message) -> PyResult<Sig> {
+    let kp = _to_schnorrkel_keypair(&keypair)?;
 
-    Ok(ExtendedKeypair(new_chaincode.0, new_keypair.public.to_bytes(), new_keypair.secret.to_bytes()))
+    let mut mac = Hmac::<Blake2b>::new_from_slice(&keypair.1)
+        .expect("HMAC accepts keys of any length; qed");
+    mac.update(&(context.0.len() as u64).to_le_bytes());
+    mac.update(&context.0);
+    mac.update(&message.0);
+    let mut wide = [0u8; 64];
+    wide.copy_from_slice(&mac.finalize().into_bytes());
+    let nonce = Scalar::from_bytes_mod_order_wide(&wide);
+
+    let t = signing_context(&context.0).bytes(&message.0);
+    Ok(Sig(_sign_with_nonce(&kp, t, &nonce)))
 }
 
-/// Returns the hard derivation of the private and public key of the specified child.
+// Computes a standard schnorrkel signature using the given nonce scalar instead of a
+// transcript-derived one.
+fn _sign_with_nonce<T: SigningTranscript>(keypair: &SchnorrkelKeypair, mut t: T, nonce: &Scalar) -> [u8; SIGNATURE_LENGTH] {
+    let key = _secret_scalar(&keypair.secret);
+    let r = (nonce * &RISTRETTO_BASEPOINT_TABLE).compress();
+
+    t.proto_name(b"Schnorr-sig");
+    t.commit_point(b"sign:pk", keypair.public.as_compressed());
+    t.commit_point(b"sign:R", &r);
+    let c = t.challenge_scalar(b"sign:c");
+    let s = (c * key) + nonce;
+
+    let mut bytes = [0u8; SIGNATURE_LENGTH];
+    bytes[..32].copy_from_slice(r.as_bytes());
+    bytes[32..].copy_from_slice(s.as_bytes());
+    bytes[63] |= 128;
+    bytes
+}
+
+/// Signs a message under each of several signing contexts.
 ///
-/// This derivation is performed using the secret material for the key, so even knowing
-/// the extended public key of this or a child key is not enough to go any further up the
-/// hierarchy.
+/// The keypair is parsed once and reused for every context, so this is cheaper than
+/// calling `sign` repeatedly when the same message is authenticated for several verifiers.
+/// Each signature is an ordinary schnorrkel signature over `message` under its context.
 ///
 /// # Arguments
 ///
-/// * `extended_keypair` - The extended keypair, comprised of the chain code, public key, and private key.
-/// * `id` - The identifier for the child key to derive.
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
+/// * `message` - The binary message to sign.
+/// * `contexts` - The list of signing contexts to sign under.
 ///
 /// # Returns
 ///
-/// A new extended keypair for the child.
+/// A list of 64-byte signatures, one per context and in the same order.
 ///
-/// *NOTE:* The chain code, public key, and secret key scalar are generated
-/// deterministically, but the secret key nonce is *RANDOM*, even with
-/// identical input.
+/// # Raises
+///
+/// * `ValueError` - If the list of contexts is empty, or either the public or private key is invalid.
 #[pyfunction]
-#[pyo3(text_signature = "(extended_keypair, id)")]
-pub fn hard_derive_keypair(extended_keypair: ExtendedKeypair, id: Message) -> PyResult<ExtendedKeypair> {
-    let chain_code = ChainCode(extended_keypair.0);
-    let privkey = SecretKey::from_bytes(&extended_keypair.2)
-        .map_err(|err| exceptions::PyValueError::new_err(format!("Invalid secret key: {}", err.to_string())))?;
+#[pyo3(text_signature = "(keypair, message, contexts)")]
+pub fn sign_multi_context(keypair: Keypair, message: Message, contexts: Vec<Message>) -> PyResult<Vec<Sig>> {
+    if contexts.is_empty() {
+        return Err(_error("INVALID_ARGUMENT", "Expected at least one context"));
+    }
+    let keypair = _to_schnorrkel_keypair(&keypair)?;
 
-    let (new_mini, new_chaincode) = privkey.hard_derive_mini_secret_key(Some(chain_code), &id.0);
-    let new_keypair = new_mini.expand_to_keypair(ExpansionMode::Ed25519);
-    Ok(ExtendedKeypair(new_chaincode.0, new_keypair.public.to_bytes(), new_keypair.secret.to_bytes()))
+    Ok(contexts.iter()
+        .map(|context| Sig(keypair.sign(signing_context(&context.0).bytes(&message.0)).to_bytes()))
+        .collect())
 }
 
-/// aggregate two public points (public_keys or R values.)
+/// Re-signs a message under a new signing context, for migrating away from an old one.
+///
+/// The message is first signed under `old_context` and that signature is checked against
+/// the keypair's public key, proving the keypair is able to produce signatures under the
+/// old context. Only then is a fresh signature under `new_context` produced. Both
+/// signatures are returned so the migration can be recorded.
+///
+/// This requires the secret key: an existing signature cannot be converted to a new
+/// context without it.
 ///
 /// # Arguments
 ///
-/// * `pubkey1` - The sr25519 public point, as an array of 32 bytes, to use.
-/// * `pubkey2` - The sr25519 public point, as an array of 32 bytes, to use.
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
+/// * `message` - The binary message to sign.
+/// * `old_context` - The signing context being migrated from.
+/// * `new_context` - The signing context being migrated to.
 ///
 /// # Returns
 ///
-/// * `pubkey` - The sr25519 public point, as an array of 32 bytes, to use.
-///
+/// A tuple of the 64-byte signatures under the old and the new context, in that order.
 ///
+/// # Raises
 ///
+/// * `ValueError` - If either key is invalid, or the keypair's secret and public key don't match.
 #[pyfunction]
-#[text_signature = "(public1, public2)"]
-pub fn sum_public_points(pubkey1: PubKey, pubkey2: PubKey) -> PyResult<PubKey> {
-
-    let pk1 = match PublicKey::from_bytes(&pubkey1.0) {
-        Ok(some_pk) => some_pk,
-        Err(err) => return Err(exceptions::ValueError::py_err(format!("Invalid public key: {}", err.to_string()))),
-    };
+#[pyo3(text_signature = "(keypair, message, old_context, new_context)")]
+pub fn resign_under_context(keypair: Keypair, message: Message, old_context: Message, new_context: Message) -> PyResult<(Sig, Sig)> {
+    let keypair = _to_schnorrkel_keypair(&keypair)?;
 
-    let pk2 = match PublicKey::from_bytes(&pubkey2.0) {
-        Ok(some_pk) => some_pk,
-        Err(err) => return Err(exceptions::ValueError::py_err(format!("Invalid public key: {}", err.to_string()))),
-    };
-
-    let res_point = pk1.as_point() + pk2.as_point();
-    let result = res_point.compress();
+    let old_t = signing_context(&old_context.0).bytes(&message.0);
+    let old_sig = keypair.sign(old_t.clone());
+    keypair.public.verify(old_t, &old_sig)
+        .map_err(|_| _error("INVALID_KEY", "Keypair cannot produce a valid signature under the old context"))?;
 
-    Ok(PubKey(result.to_bytes()))
+    let new_sig = keypair.sign(signing_context(&new_context.0).bytes(&message.0));
+    Ok((Sig(old_sig.to_bytes()), Sig(new_sig.to_bytes())))
 }
 
-/// Multi-Signature: each party must call this function locally. resulting signatures can be
-/// aggregated to construct final signature.
+/// Signs a message bound to a session identifier.
+///
+/// The session identifier is committed to the signing transcript after the message,
+/// under the label `session-id`, so the signature only verifies for that session.
 ///
 /// # Arguments
 ///
-/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (shared_public_bytes, private_bytes)
-/// * `message` - The binary message to signScala.
-/// * 'R_compressed' - The aggregated public point R = R1 + R2
-/// * 'k' - The random scalar related to local R1/R2
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
+/// * `message` - The binary message to sign.
+/// * `session_id` - The server-issued session identifier.
+/// * `context` - The signing context to use.
 ///
 /// # Returns
 ///
@@ -306,378 +686,6051 @@ pub fn sum_public_points(pubkey1: PubKey, pubkey2: PubKey) -> PyResult<PubKey> {
 ///
 /// * `ValueError` - If either the public or private key is invalid.
 #[pyfunction]
-#[text_signature = "(keypair, message, R, k)"]
-pub fn multi_sign(keypair: Keypair, message: Message, R_compressed: PubKey, k: PrivKey) -> PyResult<Sig> {
-    let mut public = [0u8; PUBLIC_KEY_LENGTH];
-    let mut private = [0u8; SECRET_KEY_LENGTH];
-    public.clone_from_slice(&keypair.0[0..PUBLIC_KEY_LENGTH]);
-    private.clone_from_slice(&keypair.1[0..SECRET_KEY_LENGTH]);
-    let secret = match SecretKey::from_bytes(&private) {
-        Ok(some_secret) => some_secret,
-        Err(err) => return Err(exceptions::ValueError::py_err(format!("Invalid secret key: {}", err.to_string()))),
-    };
-
-    let k_scalar = match SecretKey::from_bytes(&k.0) {
-        Ok(some_key) => some_key,
-        Err(err) => return Err(exceptions::ValueError::py_err(format!("Invalid secret key: {}", err.to_string()))),
-    };
+#[pyo3(text_signature = "(keypair, message, session_id, context)")]
+pub fn sign_session(keypair: Keypair, message: Message, session_id: Message, context: Message) -> PyResult<Sig> {
+    let keypair = _to_schnorrkel_keypair(&keypair)?;
+    let t = _session_transcript(&context.0, &message.0, &session_id.0);
+    Ok(Sig(keypair.sign(t).to_bytes()))
+}
 
-    let public = match PublicKey::from_bytes(&public) {
-        Ok(some_public) => some_public,
-        Err(err) => return Err(exceptions::ValueError::py_err(format!("Invalid public key: {}", err.to_string()))),
-    };
+/// Verifies a signature made with `sign_session` for the given session identifier.
+///
+/// # Arguments
+///
+/// * `signature` - The 64-byte sr25519 signature.
+/// * `message` - The binary message on which to verify the signature.
+/// * `session_id` - The session identifier the signature must be bound to.
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes, to use.
+/// * `context` - The signing context the message was signed under.
+///
+/// # Returns
+///
+/// True if the signature is valid for this session, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If either the signature or public key are structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(signature, message, session_id, pubkey, context)")]
+pub fn verify_session(signature: Sig, message: Message, session_id: Message, pubkey: PubKey, context: Message) -> PyResult<bool> {
+    let sig = Signature::from_bytes(&signature.0)
+        .map_err(|err| _error("INVALID_SIGNATURE", format!("Invalid signature: {}", err)))?;
+    let pk = PublicKey::from_bytes(&pubkey.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+    let t = _session_transcript(&context.0, &message.0, &session_id.0);
+    Ok(pk.verify(t, &sig).is_ok())
+}
+
+fn _session_transcript(context: &[u8], message: &[u8], session_id: &[u8]) -> Transcript {
+    let mut t = signing_context(context).bytes(message);
+    t.append_message(b"session-id", session_id);
+    t
+}
+
+/// Signs a message that expires at the given block number.
+///
+/// The expiry block is committed to the signing transcript after the message, under the
+/// label `expiry-block`, as its 8-byte big-endian encoding. A signature therefore only
+/// verifies for the expiry block it was made with, so the expiry can't be extended.
+///
+/// # Arguments
+///
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
+/// * `message` - The binary message to sign.
+/// * `expiry_block` - The last block number at which the signature is still valid.
+/// * `context` - The signing context to use.
+///
+/// # Returns
+///
+/// A 64-byte signature.
+///
+/// # Raises
+///
+/// * `ValueError` - If either the public or private key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(keypair, message, expiry_block, context)")]
+pub fn sign_with_expiry_block(keypair: Keypair, message: Message, expiry_block: u64, context: Message) -> PyResult<Sig> {
+    let keypair = _to_schnorrkel_keypair(&keypair)?;
+    let t = _expiry_block_transcript(&context.0, &message.0, expiry_block);
+    Ok(Sig(keypair.sign(t).to_bytes()))
+}
+
+/// Verifies a signature made with `sign_with_expiry_block` and checks whether it has expired.
+///
+/// # Arguments
+///
+/// * `signature` - The 64-byte sr25519 signature.
+/// * `message` - The binary message on which to verify the signature.
+/// * `expiry_block` - The expiry block number the signature claims.
+/// * `current_block` - The current block number.
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes, to use.
+/// * `context` - The signing context the message was signed under.
+///
+/// # Returns
+///
+/// A tuple of whether the signature is valid for this expiry block, and whether
+/// `current_block` is past the expiry block.
+///
+/// # Raises
+///
+/// * `ValueError` - If either the signature or public key are structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(signature, message, expiry_block, current_block, pubkey, context)")]
+pub fn verify_with_expiry_block(signature: Sig, message: Message, expiry_block: u64, current_block: u64, pubkey: PubKey, context: Message) -> PyResult<(bool, bool)> {
+    let sig = Signature::from_bytes(&signature.0)
+        .map_err(|err| _error("INVALID_SIGNATURE", format!("Invalid signature: {}", err)))?;
+    let pk = PublicKey::from_bytes(&pubkey.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+    let t = _expiry_block_transcript(&context.0, &message.0, expiry_block);
+    Ok((pk.verify(t, &sig).is_ok(), current_block > expiry_block))
+}
+
+fn _expiry_block_transcript(context: &[u8], message: &[u8], expiry_block: u64) -> Transcript {
+    let mut t = signing_context(context).bytes(message);
+    t.append_message(b"expiry-block", &expiry_block.to_be_bytes());
+    t
+}
+
+/// Evaluates the verifiable random function on a message with the given keypair.
+///
+/// The VRF input is the message under the same `substrate` signing context as `sign`,
+/// matching how substrate derives on-chain randomness.
+///
+/// # Arguments
+///
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
+/// * `message` - The binary message to evaluate the VRF on.
+///
+/// # Returns
+///
+/// A tuple containing the 32-byte VRF output and the 96-byte batchable VRF proof, in that order.
+///
+/// # Raises
+///
+/// * `ValueError` - If either the public or private key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(keypair, message)")]
+pub fn vrf_sign(keypair: Keypair, message: Message) -> PyResult<(VrfOutput, VrfProof)> {
+    let keypair = _to_schnorrkel_keypair(&keypair)?;
+    let context = signing_context(SIGNING_CTX);
+    let (in_out, _, proof_batchable) = keypair.vrf_sign(context.bytes(&message.0));
+
+    Ok((VrfOutput(in_out.to_preout().to_bytes()), VrfProof(proof_batchable.to_bytes())))
+}
+
+/// Verifies a VRF output and proof produced by `vrf_sign` against a public key.
+///
+/// # Arguments
+///
+/// * `public_key` - The sr25519 public key, as an array of 32 bytes, to use.
+/// * `message` - The binary message the VRF was evaluated on.
+/// * `vrf_output` - The 32-byte VRF output.
+/// * `vrf_proof` - The 96-byte batchable VRF proof.
+///
+/// # Returns
+///
+/// True if the proof is valid for this output, key and message, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If the public key, output or proof are structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(public_key, message, vrf_output, vrf_proof)")]
+pub fn vrf_verify(public_key: PubKey, message: Message, vrf_output: VrfOutput, vrf_proof: VrfProof) -> PyResult<bool> {
+    let pk = PublicKey::from_bytes(&public_key.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+    let (output, proof) = _to_vrf_output_and_proof(&vrf_output, &vrf_proof)?;
+
+    let context = signing_context(SIGNING_CTX);
+    let proof = match proof.shorten_vrf(&pk, context.bytes(&message.0), &output) {
+        Ok(some_proof) => some_proof,
+        Err(_) => return Ok(false),
+    };
+    Ok(pk.vrf_verify(context.bytes(&message.0), &output, &proof).is_ok())
+}
+
+// The transcript the DLEQ proof is made on; schnorrkel's `vrf_sign` uses it with nothing committed
+fn _vrf_extra_transcript(extra: &[u8]) -> Transcript {
+    let mut t = Transcript::new(b"VRF");
+    t.append_message(b"extra", extra);
+    t
+}
+
+/// Evaluates the VRF on a message like `vrf_sign`, additionally binding `extra` data into the proof.
+///
+/// The extra data goes into the transcript of the proof only, so the output is the same as
+/// `vrf_sign` gives for this keypair and message, but the proof only verifies with the same
+/// extra data. BABE uses this to bind slot claim metadata to the VRF.
+///
+/// # Arguments
+///
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
+/// * `message` - The binary message to evaluate the VRF on.
+/// * `extra` - The binary data to bind into the proof.
+///
+/// # Returns
+///
+/// A tuple containing the 32-byte VRF output and the 96-byte batchable VRF proof, in that order.
+///
+/// # Raises
+///
+/// * `ValueError` - If either the public or private key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(keypair, message, extra)")]
+pub fn vrf_sign_extra(keypair: Keypair, message: Message, extra: Message) -> PyResult<(VrfOutput, VrfProof)> {
+    let keypair = _to_schnorrkel_keypair(&keypair)?;
+    let context = signing_context(SIGNING_CTX);
+    let (in_out, _, proof_batchable) = keypair.vrf_sign_extra(context.bytes(&message.0), _vrf_extra_transcript(&extra.0));
+
+    Ok((VrfOutput(in_out.to_preout().to_bytes()), VrfProof(proof_batchable.to_bytes())))
+}
+
+/// Verifies a VRF output and proof produced by `vrf_sign_extra` against a public key.
+///
+/// # Arguments
+///
+/// * `public_key` - The sr25519 public key, as an array of 32 bytes, to use.
+/// * `message` - The binary message the VRF was evaluated on.
+/// * `extra` - The binary data bound into the proof.
+/// * `vrf_output` - The 32-byte VRF output.
+/// * `vrf_proof` - The 96-byte batchable VRF proof.
+///
+/// # Returns
+///
+/// True if the proof is valid for this output, key, message and extra data, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If the public key, output or proof are structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(public_key, message, extra, vrf_output, vrf_proof)")]
+pub fn vrf_verify_extra(public_key: PubKey, message: Message, extra: Message, vrf_output: VrfOutput, vrf_proof: VrfProof) -> PyResult<bool> {
+    let pk = PublicKey::from_bytes(&public_key.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+    let (output, proof) = _to_vrf_output_and_proof(&vrf_output, &vrf_proof)?;
+
+    let context = signing_context(SIGNING_CTX);
+    let in_out = match output.attach_input_hash(&pk, context.bytes(&message.0)) {
+        Ok(in_out) => in_out,
+        Err(_) => return Ok(false),
+    };
+    let proof = proof.shorten_dleq(_vrf_extra_transcript(&extra.0), &pk, &in_out, KUSAMA_VRF);
+    Ok(pk.vrf_verify_extra(context.bytes(&message.0), &output, &proof, _vrf_extra_transcript(&extra.0)).is_ok())
+}
+
+/// Expands a VRF output into `length` bytes of randomness for the given context label.
+///
+/// This is schnorrkel's `VRFInOut::make_bytes`: a merlin transcript labelled `VRFResult`
+/// absorbs the context label and then both the VRF input point (rebuilt from the public
+/// key and message, as in `vrf_sign`) and the output point, and `length` bytes are squeezed
+/// out of it. Identical inputs always yield identical bytes, and distinct context labels
+/// yield independent bytes.
+///
+/// Unless you produced the output yourself, check it with `vrf_verify` before use.
+///
+/// # Arguments
+///
+/// * `vrf_output` - The 32-byte VRF output.
+/// * `context` - The context label for this use of the randomness.
+/// * `length` - The number of bytes to produce, at most 65536.
+/// * `public_key` - The sr25519 public key of the VRF signer.
+/// * `message` - The binary message the VRF was evaluated on.
+///
+/// # Returns
+///
+/// `length` bytes of randomness.
+///
+/// # Raises
+///
+/// * `ValueError` - If the length is above 65536, or the public key or VRF output are
+///   structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(vrf_output, context, length, public_key, message)")]
+pub fn vrf_output_bytes<'p>(py: Python<'p>, vrf_output: VrfOutput, context: Message, length: usize, public_key: PubKey, message: Message) -> PyResult<&'p PyBytes> {
+    if length > VRF_OUTPUT_MAX_BYTES {
+        return Err(_error("INVALID_ARGUMENT", format!("Invalid length: {} is above the maximum of {}", length, VRF_OUTPUT_MAX_BYTES)));
+    }
+    let pk = PublicKey::from_bytes(&public_key.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+    let in_out = VRFPreOut::from_bytes(&vrf_output.0)
+        .and_then(|output| output.attach_input_hash(&pk, signing_context(SIGNING_CTX).bytes(&message.0)))
+        .map_err(|err| _error("INVALID_VRF", format!("Invalid VRF output: {}", err)))?;
+
+    let mut t = Transcript::new(b"VRFResult");
+    t.append_message(b"", &context.0);
+    in_out.commit(&mut t);
+    let mut bytes = vec![0u8; length];
+    t.challenge_bytes(b"", &mut bytes);
+    Ok(PyBytes::new(py, &bytes))
+}
+
+fn _to_vrf_output_and_proof(vrf_output: &VrfOutput, vrf_proof: &VrfProof) -> PyResult<(VRFPreOut, VRFProofBatchable)> {
+    match CompressedRistretto(vrf_output.0).decompress() {
+        Some(point) if !point.is_identity() => (),
+        _ => return Err(_error("INVALID_VRF", "Invalid VRF output: not a valid point")),
+    }
+    let output = VRFPreOut::from_bytes(&vrf_output.0)
+        .map_err(|err| _error("INVALID_VRF", format!("Invalid VRF output: {}", err)))?;
+    let proof = VRFProofBatchable::from_bytes(&vrf_proof.0)
+        .map_err(|err| _error("INVALID_VRF", format!("Invalid VRF proof: {}", err)))?;
+    Ok((output, proof))
+}
+
+/// Verifies that a signature on a given message was generated by private key
+/// corresponding to the specified public key.
+///
+/// Verification is already strict about encodings, so there is no separate canonical mode.
+/// schnorrkel requires the marker bit on the last byte, and requires s, with the marker
+/// cleared, to be fully reduced, so `s + l` and other malleable variants of a signature
+/// raise. A non-canonical encoding of R fails to decompress, so returns false.
+///
+/// # Arguments
+///
+/// * `signature` - The 64-byte sr25519 signature.
+/// * `message` - The binary message on which to verify the signature.
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes, to use.
+///
+/// # Returns
+///
+/// True if the signature is valid, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If either the signature or public key are structurally invalid,
+///   including a missing marker bit or a non-canonical s.
+#[pyfunction]
+#[pyo3(text_signature = "(signature, message, pubkey)")]
+pub fn verify(signature: Sig, message: Message, pubkey: PubKey) -> PyResult<bool> {
+    let sig = match Signature::from_bytes(&signature.0) {
+        Ok(some_sig) => some_sig,
+        Err(err) => return Err(_error("INVALID_SIGNATURE", format!("Invalid signature: {}", err))),
+    };
+    let pk = match PublicKey::from_bytes(&pubkey.0) {
+        Ok(some_pk) => some_pk,
+        Err(err) => return Err(_error("INVALID_KEY", format!("Invalid public key: {}", err))),
+    };
+    let result = pk.verify_simple(SIGNING_CTX, &message.0, &sig);
+    Ok(result.is_ok())
+}
+
+/// Verifies a signature as `verify` does, against the public half of a keypair.
+///
+/// Only the public key is used; the secret key is not checked to match it.
+///
+/// # Arguments
+///
+/// * `signature` - The 64-byte sr25519 signature.
+/// * `message` - The binary message on which to verify the signature.
+/// * `keypair` - The sr25519 keypair, as a tuple of (public_bytes, private_bytes)
+///
+/// # Returns
+///
+/// True if the signature is valid, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If either the signature or public key are structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(signature, message, keypair)")]
+pub fn verify_with_keypair(signature: Sig, message: Message, keypair: Keypair) -> PyResult<bool> {
+    verify(signature, message, PubKey(keypair.0))
+}
+
+/// Verifies a signature made by `sign_wrapped`, wrapping the message the same way first.
+///
+/// # Arguments
+///
+/// * `signature` - The signature in bytes
+/// * `message` - The binary message that was signed, with or without the wrapper.
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes
+///
+/// # Returns
+///
+/// True if the signature is valid for the wrapped message, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If the signature or public key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(signature, message, pubkey)")]
+pub fn verify_wrapped(signature: Sig, message: Message, pubkey: PubKey) -> PyResult<bool> {
+    verify(signature, Message(_wrap_bytes(&message.0)), pubkey)
+}
+
+/// Signs a 32-byte Blake2b digest of a message instead of the message itself.
+///
+/// The digest is appended to the `substrate` signing context transcript as a single
+/// `sign-256` item, as schnorrkel's `SigningContext::hash256` does, so large messages
+/// never need to be passed in. The signature is not the same as one from `sign` over the
+/// 32 digest bytes, and only verifies with `verify_prehashed`.
+///
+/// The digest is taken as given: the caller is responsible for hashing the message with
+/// Blake2b-256, and for any domain separation of what was hashed.
+///
+/// # Arguments
+///
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
+/// * `hash32` - The 32-byte Blake2b-256 digest of the message.
+///
+/// # Returns
+///
+/// A 64-byte signature.
+///
+/// # Raises
+///
+/// * `ValueError` - If the digest is not 32 bytes, or either the public or private key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(keypair, hash32)")]
+pub fn sign_prehashed(keypair: Keypair, hash32: Message) -> PyResult<Sig> {
+    let t = _prehashed_transcript(hash32)?;
+    let keypair = _to_schnorrkel_keypair(&keypair)?;
+    Ok(Sig(keypair.sign(t).to_bytes()))
+}
+
+/// Verifies a signature from `sign_prehashed` on a 32-byte message digest.
+///
+/// # Arguments
+///
+/// * `signature` - The 64-byte sr25519 signature.
+/// * `hash32` - The 32-byte Blake2b-256 digest of the message.
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes, to use.
+///
+/// # Returns
+///
+/// True if the signature is valid, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If the digest is not 32 bytes, or either the signature or public key is structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(signature, hash32, pubkey)")]
+pub fn verify_prehashed(signature: Sig, hash32: Message, pubkey: PubKey) -> PyResult<bool> {
+    let t = _prehashed_transcript(hash32)?;
+    let sig = Signature::from_bytes(&signature.0)
+        .map_err(|err| _error("INVALID_SIGNATURE", format!("Invalid signature: {}", err)))?;
+    let pk = PublicKey::from_bytes(&pubkey.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+    Ok(pk.verify(t, &sig).is_ok())
+}
+
+// The transcript `signing_context(SIGNING_CTX).hash256(...)` builds for a Blake2b-256 digest
+fn _prehashed_transcript(hash32: Message) -> PyResult<Transcript> {
+    let digest = _check_bytes_len(hash32.0, 32)?;
+    let mut t = Transcript::new(b"SigningContext");
+    t.append_message(b"", SIGNING_CTX);
+    t.append_message(b"sign-256", &digest);
+    Ok(t)
+}
+
+/// Returns whether a signature and public key are malformed, without verifying anything.
+///
+/// Only the cheap structural checks are done: that the public key and the signature's R
+/// decompress to Ristretto points, and that the signature has the schnorrkel marker bit
+/// and a canonical s. This is a filter to skip verifying obvious garbage, not a substitute
+/// for `verify`: false only means the inputs are well-formed, not that the signature is
+/// valid. The message is not used, and is taken so the arguments match `verify`.
+///
+/// # Arguments
+///
+/// * `signature` - The 64-byte sr25519 signature.
+/// * `message` - The binary message the signature is for.
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes.
+///
+/// # Returns
+///
+/// True if the inputs can't possibly verify, false if they need a full `verify`.
+#[pyfunction]
+#[pyo3(text_signature = "(signature, message, pubkey)")]
+pub fn quick_reject(signature: Sig, message: Message, pubkey: PubKey) -> bool {
+    let _ = message;
+    let mut r = [0u8; 32];
+    r.copy_from_slice(&signature.0[..32]);
+    PublicKey::from_bytes(&pubkey.0).is_err()
+        || Signature::from_bytes(&signature.0).is_err()
+        || CompressedRistretto(r).decompress().is_none()
+}
+
+/// Verifies a signature as `verify` does, but raises on degenerate public keys.
+///
+/// `verify` only reports whether a signature checks out, so a public key that is a
+/// small-order point, such as the identity, looks no different from a wrong signature.
+/// Signatures under such a key prove nothing about who made them, so consensus code should
+/// reject the key outright. Here the identity, the only small-order sr25519 key (see
+/// `reject_small_order_public_key`), raises a `ValueError`, as does an encoding that is not
+/// a valid Ristretto point, such as the other Edwards `small_order_points`.
+///
+/// # Arguments
+///
+/// * `signature` - The 64-byte sr25519 signature.
+/// * `message` - The binary message on which to verify the signature.
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes, to use.
+///
+/// # Returns
+///
+/// True if the signature is valid, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If the public key is the identity, or either the signature or public
+///   key is structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(signature, message, pubkey)")]
+pub fn verify_strict(signature: Sig, message: Message, pubkey: PubKey) -> PyResult<bool> {
+    reject_small_order_public_key(PubKey(pubkey.0))?;
+    verify(signature, message, pubkey)
+}
+
+/// Verifies a signature under a given context, explaining any failure, to debug signatures
+/// made by other sr25519 libraries.
+///
+/// Instead of returning false, a failure raises an `Sr25519Error` saying which step
+/// failed: the public key not decompressing (code `INVALID_KEY`), the signature bytes
+/// being malformed, such as lacking the schnorrkel marker bit (`INVALID_SIGNATURE`), the
+/// signature verifying under the `substrate` context rather than the given one
+/// (`CONTEXT_MISMATCH`), or the signature not verifying with a well-formed key
+/// (`INVALID_SIGNATURE`).
+///
+/// # Arguments
+///
+/// * `signature` - The 64-byte sr25519 signature.
+/// * `message` - The binary message on which to verify the signature.
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes, to use.
+/// * `context` - The signing context the signature is expected to be under.
+///
+/// # Returns
+///
+/// True if the signature is valid.
+///
+/// # Raises
+///
+/// * `ValueError` - If the signature is not valid, with the reason as above.
+#[pyfunction]
+#[pyo3(text_signature = "(signature, message, pubkey, context)")]
+pub fn verify_compat(signature: Sig, message: Message, pubkey: PubKey, context: Message) -> PyResult<bool> {
+    let pk = PublicKey::from_bytes(&pubkey.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: it does not decompress to a Ristretto point: {}", err)))?;
+    let sig = Signature::from_bytes(&signature.0)
+        .map_err(|err| _error("INVALID_SIGNATURE", format!("Invalid signature: the public key decompressed, but the signature is malformed: {}", err)))?;
+
+    if pk.verify_simple(&context.0, &message.0, &sig).is_ok() {
+        return Ok(true);
+    }
+    if context.0 != SIGNING_CTX && pk.verify_simple(SIGNING_CTX, &message.0, &sig).is_ok() {
+        return Err(_error("CONTEXT_MISMATCH", format!(
+            "Signature verifies under the substrate context, not {:?}", String::from_utf8_lossy(&context.0))));
+    }
+    Err(_error("INVALID_SIGNATURE", format!(
+        "Signature does not verify: the public key decompressed and the signature is well-formed, \
+         so the message, key or context {:?} differ from the signer's", String::from_utf8_lossy(&context.0))))
+}
+
+/// Signs a message as `sign` does, taking and returning hex strings instead of bytes.
+///
+/// # Arguments
+///
+/// * `keypair_hex` - A tuple of the hex-encoded public and secret key, each optionally `0x` prefixed.
+/// * `message_hex` - The hex-encoded message, optionally `0x` prefixed.
+///
+/// # Returns
+///
+/// The `0x` prefixed hex encoding of the 64-byte signature.
+///
+/// # Raises
+///
+/// * `ValueError` - If any input is not valid hex, or the keys are invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(keypair_hex, message_hex)")]
+pub fn sign_hex(keypair_hex: (&str, &str), message_hex: &str) -> PyResult<String> {
+    let mut public = [0u8; PUBLIC_KEY_LENGTH];
+    let mut secret = [0u8; SECRET_KEY_LENGTH];
+    public.copy_from_slice(&_check_bytes_len(_decode_hex(keypair_hex.0, "public key")?, PUBLIC_KEY_LENGTH)?);
+    secret.copy_from_slice(&_check_bytes_len(_decode_hex(keypair_hex.1, "secret key")?, SECRET_KEY_LENGTH)?);
+    let message = Message(_decode_hex(message_hex, "message")?);
+
+    let keypair = Keypair(public, secret);
+    secret.zeroize();
+    let sig = sign(keypair, message)?;
+    Ok(format!("0x{}", hex::encode(sig.0)))
+}
+
+/// Verifies a signature as `verify` does, taking hex strings instead of bytes.
+///
+/// # Arguments
+///
+/// * `signature_hex` - The hex-encoded 64-byte signature, optionally `0x` prefixed.
+/// * `message_hex` - The hex-encoded message, optionally `0x` prefixed.
+/// * `pubkey_hex` - The hex-encoded 32-byte public key, optionally `0x` prefixed.
+///
+/// # Returns
+///
+/// True if the signature is valid, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If any input is not valid hex, or the signature or public key are structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(signature_hex, message_hex, pubkey_hex)")]
+pub fn verify_hex(signature_hex: &str, message_hex: &str, pubkey_hex: &str) -> PyResult<bool> {
+    let mut signature = [0u8; SIGNATURE_LENGTH];
+    let mut pubkey = [0u8; PUBLIC_KEY_LENGTH];
+    signature.copy_from_slice(&_check_bytes_len(_decode_hex(signature_hex, "signature")?, SIGNATURE_LENGTH)?);
+    pubkey.copy_from_slice(&_check_bytes_len(_decode_hex(pubkey_hex, "public key")?, PUBLIC_KEY_LENGTH)?);
+    let message = Message(_decode_hex(message_hex, "message")?);
+
+    verify(Sig(signature), message, PubKey(pubkey))
+}
+
+/// Decodes a hex-encoded public key, checking its length.
+///
+/// # Arguments
+///
+/// * `s` - The hex-encoded 32-byte public key, optionally `0x` prefixed.
+///
+/// # Returns
+///
+/// The 32-byte public key.
+///
+/// # Raises
+///
+/// * `ValueError` - If the input is not valid hex or does not decode to 32 bytes.
+#[pyfunction]
+#[pyo3(text_signature = "(s)")]
+pub fn public_key_from_hex<'p>(py: Python<'p>, s: &str) -> PyResult<&'p PyBytes> {
+    Ok(PyBytes::new(py, &_check_bytes_len(_decode_hex(s, "public key")?, PUBLIC_KEY_LENGTH)?))
+}
+
+/// Decodes a hex-encoded secret key, checking its length.
+///
+/// # Arguments
+///
+/// * `s` - The hex-encoded 64-byte secret key, optionally `0x` prefixed.
+///
+/// # Returns
+///
+/// The 64-byte secret key.
+///
+/// # Raises
+///
+/// * `ValueError` - If the input is not valid hex or does not decode to 64 bytes.
+#[pyfunction]
+#[pyo3(text_signature = "(s)")]
+pub fn secret_key_from_hex<'p>(py: Python<'p>, s: &str) -> PyResult<&'p PyBytes> {
+    let mut secret = _check_bytes_len(_decode_hex(s, "secret key")?, SECRET_KEY_LENGTH)?;
+    let bytes = PyBytes::new(py, &secret);
+    secret.zeroize();
+    Ok(bytes)
+}
+
+/// Signs a merlin transcript built from a list of labelled items.
+///
+/// The transcript starts with the protocol label `sr25519-transcript`. Merlin labels are
+/// fixed at compile time, so each item is appended as two messages, its label under
+/// `item-label` and its value under `item-value`. Merlin frames every message with its
+/// length, so no two different lists of items give the same transcript.
+///
+/// # Arguments
+///
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
+/// * `items` - A list of `(label, value)` byte string tuples, appended in order.
+///
+/// # Returns
+///
+/// A 64-byte signature.
+///
+/// # Raises
+///
+/// * `ValueError` - If either the public or private key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(keypair, items)")]
+pub fn sign_transcript(keypair: Keypair, items: Vec<(Message, Message)>) -> PyResult<Sig> {
+    let keypair = _to_schnorrkel_keypair(&keypair)?;
+    Ok(Sig(keypair.sign(_items_transcript(&items)).to_bytes()))
+}
+
+/// Verifies a signature from `sign_transcript` over the same list of labelled items.
+///
+/// # Arguments
+///
+/// * `signature` - The 64-byte sr25519 signature.
+/// * `items` - The list of `(label, value)` byte string tuples that was signed.
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes, to use.
+///
+/// # Returns
+///
+/// True if the signature is valid, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If either the signature or public key are structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(signature, items, pubkey)")]
+pub fn verify_transcript(signature: Sig, items: Vec<(Message, Message)>, pubkey: PubKey) -> PyResult<bool> {
+    let sig = Signature::from_bytes(&signature.0)
+        .map_err(|err| _error("INVALID_SIGNATURE", format!("Invalid signature: {}", err)))?;
+    let pk = PublicKey::from_bytes(&pubkey.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+    Ok(pk.verify(_items_transcript(&items), &sig).is_ok())
+}
+
+fn _items_transcript(items: &[(Message, Message)]) -> Transcript {
+    let mut t = Transcript::new(b"sr25519-transcript");
+    for (label, value) in items {
+        t.append_message(b"item-label", &label.0);
+        t.append_message(b"item-value", &value.0);
+    }
+    t
+}
+
+/// Signs a message under the given context, prehashing it first if it is large.
+///
+/// Following Substrate's convention for signed payloads, messages longer than 256 bytes
+/// are replaced by their Blake2b-256 hash before signing, while shorter messages are
+/// signed as-is. The result is a flag byte followed by the 64-byte signature, where the
+/// flag is `0x00` if the message was signed raw and `0x01` if it was prehashed.
+///
+/// # Arguments
+///
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
+/// * `message` - The binary message to sign.
+/// * `context` - The signing context to use.
+///
+/// # Returns
+///
+/// A 65-byte flagged signature.
+///
+/// # Raises
+///
+/// * `ValueError` - If either the public or private key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(keypair, message, context)")]
+pub fn sign_auto_prehash<'p>(py: Python<'p>, keypair: Keypair, message: Message, context: Message) -> PyResult<&'p PyBytes> {
+    let secret = SecretKey::from_bytes(&keypair.1)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+    let public = PublicKey::from_bytes(&keypair.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+
+    let (flag, payload) = _auto_prehash_payload(&message.0);
+    let sig = secret.sign(signing_context(&context.0).bytes(&payload), &public).to_bytes();
+    Ok(PyBytes::new(py, &[&[flag][..], &sig[..]].concat()))
+}
+
+/// Verifies a flagged signature produced by `sign_auto_prehash`.
+///
+/// The flag byte selects whether the message is checked raw (`0x00`) or as its Blake2b-256
+/// hash (`0x01`). A flag that disagrees with the 256-byte threshold for this message is
+/// treated as an invalid signature.
+///
+/// # Arguments
+///
+/// * `signature` - The 65-byte flagged signature.
+/// * `message` - The binary message on which to verify the signature.
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes, to use.
+/// * `context` - The signing context the message was signed under.
+///
+/// # Returns
+///
+/// True if the signature is valid, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If the flagged signature, its flag byte, or the public key is structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(signature, message, pubkey, context)")]
+pub fn verify_auto_prehash(signature: Message, message: Message, pubkey: PubKey, context: Message) -> PyResult<bool> {
+    if signature.0.len() != SIGNATURE_LENGTH + 1 {
+        return Err(_error("INVALID_LENGTH", format!("Expected bytes of length {}, got {}", SIGNATURE_LENGTH + 1, signature.0.len())));
+    }
+    let flag = signature.0[0];
+    if flag != PREHASH_FLAG_RAW && flag != PREHASH_FLAG_BLAKE2B {
+        return Err(_error("INVALID_ARGUMENT", format!("Invalid prehash flag: {}", flag)));
+    }
+    let sig = Signature::from_bytes(&signature.0[1..])
+        .map_err(|err| _error("INVALID_SIGNATURE", format!("Invalid signature: {}", err)))?;
+    let pk = PublicKey::from_bytes(&pubkey.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+
+    let (expected_flag, payload) = _auto_prehash_payload(&message.0);
+    if flag != expected_flag {
+        return Ok(false);
+    }
+    Ok(pk.verify(signing_context(&context.0).bytes(&payload), &sig).is_ok())
+}
+
+/// Signs a message together with the time it was signed at.
+///
+/// The timestamp is committed to the signing transcript after the message, under the label
+/// `timestamp`, as its 8-byte big-endian encoding. The result is that same encoding followed
+/// by the 64-byte signature, so the verifier can read the timestamp back out of it.
+///
+/// # Arguments
+///
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (public_bytes, private_bytes)
+/// * `message` - The binary message to sign.
+/// * `context` - The signing context to use.
+/// * `timestamp` - The signing time, as Unix seconds.
+///
+/// # Returns
+///
+/// A 72-byte timestamped signature.
+///
+/// # Raises
+///
+/// * `ValueError` - If either the public or private key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(keypair, message, context, timestamp)")]
+pub fn sign_fresh<'p>(py: Python<'p>, keypair: Keypair, message: Message, context: Message, timestamp: u64) -> PyResult<&'p PyBytes> {
+    let keypair = _to_schnorrkel_keypair(&keypair)?;
+    let t = _timestamp_transcript(&context.0, &message.0, timestamp);
+    let sig = keypair.sign(t).to_bytes();
+    Ok(PyBytes::new(py, &[&timestamp.to_be_bytes()[..], &sig[..]].concat()))
+}
+
+/// Verifies a timestamped signature from `sign_fresh` and checks it is recent enough.
+///
+/// The timestamp is the first 8 bytes of the signature, read as a big-endian integer, and
+/// the age is `now - timestamp`. A signature is only accepted if it verifies for that
+/// timestamp and its age is between 0 and `max_age_seconds`, so signatures claiming to be
+/// from the future are rejected.
+///
+/// # Arguments
+///
+/// * `signature` - The 72-byte timestamped signature.
+/// * `message` - The binary message on which to verify the signature.
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes, to use.
+/// * `context` - The signing context the message was signed under.
+/// * `max_age_seconds` - The maximum accepted age of the signature.
+/// * `now` - The current time, as Unix seconds.
+///
+/// # Returns
+///
+/// A tuple of whether the signature is valid and fresh, and its age in seconds.
+///
+/// # Raises
+///
+/// * `ValueError` - If the timestamped signature or the public key is structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(signature, message, pubkey, context, max_age_seconds, now)")]
+pub fn verify_fresh(signature: Message, message: Message, pubkey: PubKey, context: Message, max_age_seconds: u64, now: u64) -> PyResult<(bool, i128)> {
+    if signature.0.len() != TIMESTAMP_LENGTH + SIGNATURE_LENGTH {
+        return Err(_error("INVALID_LENGTH", format!("Expected {} byte timestamped signature", TIMESTAMP_LENGTH + SIGNATURE_LENGTH)));
+    }
+    let mut timestamp = [0u8; TIMESTAMP_LENGTH];
+    timestamp.copy_from_slice(&signature.0[..TIMESTAMP_LENGTH]);
+    let timestamp = u64::from_be_bytes(timestamp);
+
+    let sig = Signature::from_bytes(&signature.0[TIMESTAMP_LENGTH..])
+        .map_err(|err| _error("INVALID_SIGNATURE", format!("Invalid signature: {}", err)))?;
+    let pk = PublicKey::from_bytes(&pubkey.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+
+    let age = now as i128 - timestamp as i128;
+    let fresh = age >= 0 && age <= max_age_seconds as i128;
+    let valid = pk.verify(_timestamp_transcript(&context.0, &message.0, timestamp), &sig).is_ok();
+    Ok((valid && fresh, age))
+}
+
+fn _timestamp_transcript(context: &[u8], message: &[u8], timestamp: u64) -> Transcript {
+    let mut t = signing_context(context).bytes(message);
+    t.append_message(b"timestamp", &timestamp.to_be_bytes());
+    t
+}
+
+fn _auto_prehash_payload(message: &[u8]) -> (u8, Vec<u8>) {
+    if message.len() > PREHASH_THRESHOLD {
+        (PREHASH_FLAG_BLAKE2B, _blake2b_256(message).to_vec())
+    } else {
+        (PREHASH_FLAG_RAW, message.to_vec())
+    }
+}
+
+/// Verifies the sr25519 signature of a signed substrate extrinsic without decoding its call.
+///
+/// The extrinsic must be SCALE-encoded in the version 4 format used by Polkadot and most
+/// substrate chains:
+///
+/// * a compact length prefix covering the rest of the extrinsic,
+/// * the version byte `0x84` (signed, version 4),
+/// * the signer as a `MultiAddress::Id`: `0x00` followed by the 32-byte public key,
+/// * the signature as a `MultiSignature::Sr25519`: `0x01` followed by 64 bytes,
+/// * the signed extra: a mortal (2 bytes) or immortal (`0x00`) era, a compact nonce and a
+///   compact tip, as with Polkadot's default signed extensions,
+/// * the call, which is all remaining bytes.
+///
+/// The signed payload is the call, then the signed extra, then the additional signed data,
+/// replaced by its Blake2b-256 hash if longer than 256 bytes. The additional signed data
+/// (spec version, transaction version, genesis hash and era checkpoint block hash, each
+/// SCALE-encoded) is not part of the extrinsic, so it has to be supplied by the caller.
+/// This is why the function takes a third `additional_signed` argument on top of the
+/// extrinsic and context: the signature covers data the extrinsic bytes alone don't carry,
+/// so no extrinsic can be verified from them. Indexers have it at hand from the runtime
+/// version and block they are processing.
+/// Chains with other signed extensions need a different layout and are not supported.
+///
+/// # Arguments
+///
+/// * `extrinsic_bytes` - The SCALE-encoded signed extrinsic, including its length prefix.
+/// * `context` - The signing context, `b"substrate"` for substrate chains.
+/// * `additional_signed` - The SCALE-encoded additional signed data.
+///
+/// # Returns
+///
+/// True if the signature is valid, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If the extrinsic doesn't follow the layout above, is not signed with
+///   sr25519, or contains an invalid public key or signature.
+#[pyfunction]
+#[pyo3(text_signature = "(extrinsic_bytes, context, additional_signed)")]
+pub fn verify_extrinsic_signature(extrinsic_bytes: Message, context: Message, additional_signed: Message) -> PyResult<bool> {
+    let data = &extrinsic_bytes.0;
+    let invalid = |reason: &str| _error("INVALID_ENCODING", format!("Invalid extrinsic: {}", reason));
+
+    let mut offset = 0;
+    let len = _scale_decode_compact(data, &mut offset)?;
+    if len != (data.len() - offset) as u128 {
+        return Err(invalid("length prefix does not match"));
+    }
+    if data.get(offset) != Some(&0x84) {
+        return Err(invalid("not a signed version 4 extrinsic"));
+    }
+    offset += 1;
+
+    if data.get(offset) != Some(&0x00) || data.len() < offset + 1 + PUBLIC_KEY_LENGTH {
+        return Err(invalid("signer is not a 32-byte account ID"));
+    }
+    let pubkey = PublicKey::from_bytes(&data[offset + 1..offset + 1 + PUBLIC_KEY_LENGTH])
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+    offset += 1 + PUBLIC_KEY_LENGTH;
+
+    if data.get(offset) != Some(&0x01) || data.len() < offset + 1 + SIGNATURE_LENGTH {
+        return Err(invalid("signature is not sr25519"));
+    }
+    let sig = Signature::from_bytes(&data[offset + 1..offset + 1 + SIGNATURE_LENGTH])
+        .map_err(|err| _error("INVALID_SIGNATURE", format!("Invalid signature: {}", err)))?;
+    offset += 1 + SIGNATURE_LENGTH;
+
+    let extra_start = offset;
+    offset += match data.get(offset) {
+        Some(0x00) => 1,
+        Some(_) => 2,
+        None => return Err(invalid("missing era")),
+    };
+    _scale_decode_compact(data, &mut offset)?;
+    _scale_decode_compact(data, &mut offset)?;
+    if offset >= data.len() {
+        return Err(invalid("missing call"));
+    }
+
+    let payload = [&data[offset..], &data[extra_start..offset], &additional_signed.0[..]].concat();
+    let (_, payload) = _auto_prehash_payload(&payload);
+    Ok(pubkey.verify_simple(&context.0, &payload, &sig).is_ok())
+}
+
+/// Verifies a batch of signatures at once, which is much faster than calling `verify` on each.
+///
+/// All signatures are checked under the same `substrate` signing context as `verify`. The
+/// GIL is released while the batch is verified, so other Python threads keep running.
+///
+/// # Arguments
+///
+/// * `signatures` - A list of 64-byte sr25519 signatures.
+/// * `messages` - A list of binary messages, one per signature.
+/// * `pubkeys` - A list of sr25519 public keys, one per signature.
+///
+/// # Returns
+///
+/// True if every signature is valid, false if at least one is not.
+///
+/// # Raises
+///
+/// * `ValueError` - If the lists differ in length, or any signature or public key is structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(signatures, messages, pubkeys)")]
+pub fn verify_batch(py: Python, signatures: Vec<Sig>, messages: Vec<Message>, pubkeys: Vec<PubKey>) -> PyResult<bool> {
+    if signatures.len() != messages.len() || signatures.len() != pubkeys.len() {
+        return Err(_error("INVALID_LENGTH", format!(
+            "Expected lists of equal length, got {} signatures, {} messages and {} public keys",
+            signatures.len(), messages.len(), pubkeys.len())));
+    }
+
+    // Errors need the GIL, so every input is parsed before it is released
+    let sigs = _to_signatures(&signatures)?;
+    let pks = _to_public_keys(&pubkeys)?;
+
+    Ok(py.allow_threads(|| {
+        let context = signing_context(SIGNING_CTX);
+        let transcripts = messages.iter().map(|message| context.bytes(&message.0));
+        schnorrkel_verify_batch(transcripts, &sigs, &pks, false).is_ok()
+    }))
+}
+
+/// Verifies a batch of signatures at once, each under its own signing context.
+///
+/// The contexts only change the transcript each signature is checked against, so the
+/// batch is still verified in a single multiscalar multiplication as with `verify_batch`,
+/// with the GIL released.
+///
+/// # Arguments
+///
+/// * `signatures` - A list of 64-byte sr25519 signatures.
+/// * `messages` - A list of binary messages, one per signature.
+/// * `pubkeys` - A list of sr25519 public keys, one per signature.
+/// * `contexts` - A list of signing contexts, one per signature.
+///
+/// # Returns
+///
+/// True if every signature is valid, false if at least one is not.
+///
+/// # Raises
+///
+/// * `ValueError` - If the lists differ in length, or any signature or public key is structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(signatures, messages, pubkeys, contexts)")]
+pub fn verify_batch_contexts(py: Python, signatures: Vec<Sig>, messages: Vec<Message>, pubkeys: Vec<PubKey>, contexts: Vec<Message>) -> PyResult<bool> {
+    if signatures.len() != messages.len() || signatures.len() != pubkeys.len() || signatures.len() != contexts.len() {
+        return Err(_error("INVALID_LENGTH", format!(
+            "Expected lists of equal length, got {} signatures, {} messages, {} public keys and {} contexts",
+            signatures.len(), messages.len(), pubkeys.len(), contexts.len())));
+    }
+
+    let sigs = _to_signatures(&signatures)?;
+    let pks = _to_public_keys(&pubkeys)?;
+
+    Ok(py.allow_threads(|| {
+        let transcripts = messages.iter().zip(contexts.iter())
+            .map(|(message, context)| signing_context(&context.0).bytes(&message.0));
+        schnorrkel_verify_batch(transcripts, &sigs, &pks, false).is_ok()
+    }))
+}
+
+/// Verifies a batch of signatures all made by one public key, over different messages.
+///
+/// The public key is parsed once rather than per signature, and the batch is otherwise
+/// verified as `verify_batch` does, with the GIL released.
+///
+/// # Arguments
+///
+/// * `signatures` - A list of 64-byte sr25519 signatures.
+/// * `messages` - A list of binary messages, one per signature.
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes, that made every signature.
+///
+/// # Returns
+///
+/// True if every signature is valid, false if at least one is not.
+///
+/// # Raises
+///
+/// * `ValueError` - If the lists differ in length, or any signature or the public key is structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(signatures, messages, pubkey)")]
+pub fn verify_batch_single_key(py: Python, signatures: Vec<Sig>, messages: Vec<Message>, pubkey: PubKey) -> PyResult<bool> {
+    if signatures.len() != messages.len() {
+        return Err(_error("INVALID_LENGTH", format!(
+            "Expected lists of equal length, got {} signatures and {} messages",
+            signatures.len(), messages.len())));
+    }
+
+    let sigs = _to_signatures(&signatures)?;
+    let pk = PublicKey::from_bytes(&pubkey.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+
+    Ok(py.allow_threads(|| {
+        let pks = vec![pk; sigs.len()];
+        let context = signing_context(SIGNING_CTX);
+        let transcripts = messages.iter().map(|message| context.bytes(&message.0));
+        schnorrkel_verify_batch(transcripts, &sigs, &pks, false).is_ok()
+    }))
+}
+
+/// Verifies each signature on its own, reporting which ones are valid.
+///
+/// `verify_batch` can only say whether the whole batch is valid. Here every signature is
+/// checked individually under the shared `substrate` context, so the invalid ones can be
+/// dropped. A signature or public key that is structurally invalid is reported as false
+/// rather than raising, as it can't be valid either. The GIL is released meanwhile.
+///
+/// # Arguments
+///
+/// * `signatures` - A list of 64-byte sr25519 signatures.
+/// * `messages` - A list of binary messages, one per signature.
+/// * `pubkeys` - A list of sr25519 public keys, one per signature.
+///
+/// # Returns
+///
+/// A list of bools, true where the signature at the same index is valid.
+///
+/// # Raises
+///
+/// * `ValueError` - If the lists differ in length.
+#[pyfunction]
+#[pyo3(text_signature = "(signatures, messages, pubkeys)")]
+pub fn verify_each(py: Python, signatures: Vec<Sig>, messages: Vec<Message>, pubkeys: Vec<PubKey>) -> PyResult<Vec<bool>> {
+    if signatures.len() != messages.len() || signatures.len() != pubkeys.len() {
+        return Err(_error("INVALID_LENGTH", format!(
+            "Expected lists of equal length, got {} signatures, {} messages and {} public keys",
+            signatures.len(), messages.len(), pubkeys.len())));
+    }
+
+    Ok(py.allow_threads(|| {
+        let context = signing_context(SIGNING_CTX);
+        signatures.iter().zip(messages.iter()).zip(pubkeys.iter())
+            .map(|((signature, message), pubkey)| {
+                match (Signature::from_bytes(&signature.0), PublicKey::from_bytes(&pubkey.0)) {
+                    (Ok(sig), Ok(pk)) => pk.verify(context.bytes(&message.0), &sig).is_ok(),
+                    _ => false,
+                }
+            })
+            .collect()
+    }))
+}
+
+// Computes the challenge scalar schnorrkel binds a signature's R to under `substrate`
+fn _signature_challenge(message: &[u8], public: &PublicKey, r: &CompressedRistretto) -> Scalar {
+    let mut t = signing_context(SIGNING_CTX).bytes(message);
+    t.proto_name(b"Schnorr-sig");
+    t.commit_point(b"sign:pk", public.as_compressed());
+    t.commit_point(b"sign:R", r);
+    t.challenge_scalar(b"sign:c")
+}
+
+// Derives one coefficient per signature for half-aggregation. Each commits to every R,
+// public key and challenge, so no signature in the set can be chosen after the others.
+fn _half_aggregate_coefficients(rs: &[CompressedRistretto], pks: &[PublicKey], challenges: &[Scalar]) -> Vec<Scalar> {
+    let mut t = Transcript::new(HALF_AGGREGATE_LABEL);
+    for ((r, pk), c) in rs.iter().zip(pks.iter()).zip(challenges.iter()) {
+        t.append_message(b"R", r.as_bytes());
+        t.append_message(b"pk", &pk.to_bytes());
+        t.append_message(b"c", c.as_bytes());
+    }
+    (0..rs.len())
+        .map(|index| {
+            let mut t = t.clone();
+            t.append_u64(b"index", index as u64);
+            let mut wide = [0u8; 64];
+            t.challenge_bytes(b"z", &mut wide);
+            Scalar::from_bytes_mod_order_wide(&wide)
+        })
+        .collect()
+}
+
+/// Half-aggregates signatures, so they take about half the space.
+///
+/// This is the half-aggregation of Chalkias et al. (https://eprint.iacr.org/2021/350).
+/// The `s` scalars are combined into a single scalar, weighted by coefficients bound to
+/// every signature, message and public key, while every `R` point is kept. The result is
+/// `32 * (n + 1)` bytes for `n` signatures: the aggregate `s` followed by each `R` in
+/// order. It is not a 64-byte signature, and the format is specific to this module: only
+/// `verify_aggregate_compact` checks it, given the same messages and public keys in the
+/// same order.
+///
+/// Each `R` has to be kept, and the messages and public keys passed in, because each
+/// signature's challenge hashes its own `R`, message and public key. A sum of the `R`
+/// points could not be checked against those challenges, so aggregating the signatures
+/// alone, into a single `R` and `s`, would give something no one could verify.
+///
+/// The signatures are not checked: an invalid one gives an aggregate that fails to verify.
+///
+/// # Arguments
+///
+/// * `signatures` - A list of 64-byte sr25519 signatures under the `substrate` context.
+/// * `messages` - A list of binary messages, one per signature.
+/// * `pubkeys` - A list of sr25519 public keys, one per signature.
+///
+/// # Returns
+///
+/// The aggregate, as `32 * (len(signatures) + 1)` bytes.
+///
+/// # Raises
+///
+/// * `ValueError` - If the lists are empty or differ in length, or any signature or public
+///   key is structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(signatures, messages, pubkeys)")]
+pub fn aggregate_signatures_compact<'p>(py: Python<'p>, signatures: Vec<Sig>, messages: Vec<Message>, pubkeys: Vec<PubKey>) -> PyResult<&'p PyBytes> {
+    if signatures.len() != messages.len() || signatures.len() != pubkeys.len() {
+        return Err(_error("INVALID_LENGTH", format!(
+            "Expected lists of equal length, got {} signatures, {} messages and {} public keys",
+            signatures.len(), messages.len(), pubkeys.len())));
+    }
+    if signatures.is_empty() {
+        return Err(_error("INVALID_ARGUMENT", "Expected at least one signature"));
+    }
+
+    let sigs = _to_signatures(&signatures)?;
+    let pks = _to_public_keys(&pubkeys)?;
+
+    let aggregate = py.allow_threads(|| {
+        let mut rs = Vec::with_capacity(sigs.len());
+        let mut ss = Vec::with_capacity(sigs.len());
+        for sig in sigs.iter() {
+            let bytes = sig.to_bytes();
+            let mut r = [0u8; 32];
+            let mut s = [0u8; SCALAR_LENGTH];
+            r.copy_from_slice(&bytes[..32]);
+            s.copy_from_slice(&bytes[32..]);
+            s[31] &= 127;
+            rs.push(CompressedRistretto(r));
+            ss.push(Scalar::from_bits(s));
+        }
+        let challenges: Vec<Scalar> = messages.iter().zip(pks.iter()).zip(rs.iter())
+            .map(|((message, pk), r)| _signature_challenge(&message.0, pk, r))
+            .collect();
+        let zs = _half_aggregate_coefficients(&rs, &pks, &challenges);
+        let s: Scalar = zs.iter().zip(ss.iter()).map(|(z, s)| z * s).sum();
+
+        let mut aggregate = s.to_bytes().to_vec();
+        for r in rs.iter() {
+            aggregate.extend_from_slice(r.as_bytes());
+        }
+        aggregate
+    });
+    Ok(PyBytes::new(py, &aggregate))
+}
+
+/// Verifies a half-aggregate made by `aggregate_signatures_compact`.
+///
+/// # Arguments
+///
+/// * `aggregate` - The `32 * (n + 1)` byte aggregate of `n` signatures.
+/// * `messages` - The `n` binary messages, in the order they were aggregated.
+/// * `pubkeys` - The `n` sr25519 public keys, in the order they were aggregated.
+///
+/// # Returns
+///
+/// True if every aggregated signature was valid, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If the lists are empty or differ in length, the aggregate's length
+///   doesn't match them, or any public key is structurally invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(aggregate, messages, pubkeys)")]
+pub fn verify_aggregate_compact(py: Python, aggregate: Message, messages: Vec<Message>, pubkeys: Vec<PubKey>) -> PyResult<bool> {
+    if messages.len() != pubkeys.len() {
+        return Err(_error("INVALID_LENGTH", format!(
+            "Expected lists of equal length, got {} messages and {} public keys",
+            messages.len(), pubkeys.len())));
+    }
+    // An aggregate of nothing would check `0 * B` against an empty sum, and always pass
+    if messages.is_empty() {
+        return Err(_error("INVALID_ARGUMENT", "Expected at least one signature"));
+    }
+    let expected_len = 32 * (messages.len() + 1);
+    if aggregate.0.len() != expected_len {
+        return Err(_error("INVALID_LENGTH", format!("Expected an aggregate of length {}, got {}", expected_len, aggregate.0.len())));
+    }
+
+    let pks = _to_public_keys(&pubkeys)?;
+
+    Ok(py.allow_threads(|| {
+        let mut s = [0u8; SCALAR_LENGTH];
+        s.copy_from_slice(&aggregate.0[..32]);
+        let s = match Scalar::from_canonical_bytes(s) {
+            Some(s) => s,
+            None => return false,
+        };
+        let rs: Vec<CompressedRistretto> = aggregate.0[32..].chunks(32)
+            .map(CompressedRistretto::from_slice)
+            .collect();
+        let challenges: Vec<Scalar> = messages.iter().zip(pks.iter()).zip(rs.iter())
+            .map(|((message, pk), r)| _signature_challenge(&message.0, pk, r))
+            .collect();
+        let zs = _half_aggregate_coefficients(&rs, &pks, &challenges);
+
+        // s * B == sum(z * R) + sum(z * c * A)
+        let scalars = zs.iter().cloned().chain(zs.iter().zip(challenges.iter()).map(|(z, c)| z * c));
+        let points = rs.iter().map(|r| r.decompress()).chain(pks.iter().map(|pk| Some(*pk.as_point())));
+        match RistrettoPoint::optional_multiscalar_mul(scalars, points) {
+            Some(sum) => sum == &s * &RISTRETTO_BASEPOINT_TABLE,
+            None => false,
+        }
+    }))
+}
+
+/// Verifies a chain of delegations starting from a root public key.
+///
+/// Each link is a tuple of `(delegate_public, signature)`, where `signature` is made by the
+/// previous key in the chain (the root for the first link) over the 32 bytes of
+/// `delegate_public`, under the given signing context. A valid link means the signer
+/// authorized the delegate, which in turn signs the next link.
+///
+/// # Arguments
+///
+/// * `root_public` - The sr25519 public key, as an array of 32 bytes, the chain starts from.
+/// * `links` - The list of `(delegate_public, signature)` tuples, in delegation order.
+/// * `context` - The signing context the delegation signatures were made under.
+///
+/// # Returns
+///
+/// The public key at the end of the chain, or `root_public` if there are no links.
+///
+/// # Raises
+///
+/// * `ValueError` - If any key or signature is invalid, or any link's signature does not verify.
+#[pyfunction]
+#[pyo3(text_signature = "(root_public, links, context)")]
+pub fn verify_delegation_chain(root_public: PubKey, links: Vec<(PubKey, Sig)>, context: Message) -> PyResult<PubKey> {
+    let mut signer = PublicKey::from_bytes(&root_public.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+
+    for (index, (delegate, signature)) in links.iter().enumerate() {
+        let sig = Signature::from_bytes(&signature.0)
+            .map_err(|err| _error("INVALID_SIGNATURE", format!("Invalid signature at link {}: {}", index, err)))?;
+        signer.verify_simple(&context.0, &delegate.0, &sig)
+            .map_err(|_| _error("INVALID_SIGNATURE", format!("Broken delegation at link {}", index)))?;
+        signer = PublicKey::from_bytes(&delegate.0)
+            .map_err(|err| _error("INVALID_KEY", format!("Invalid public key at link {}: {}", index, err)))?;
+    }
+    Ok(PubKey(signer.to_bytes()))
+}
+
+/// Measures whether `verify` takes comparable time on valid and invalid signatures.
+///
+/// A fixed keypair signs a fixed message, and verification of that signature is timed
+/// against verification of a well-formed signature over a different message, alternating
+/// between the two for `iterations` rounds each.
+///
+/// This is a diagnostic, not a guarantee. Wall-clock timing from inside the process is
+/// noisy (scheduling, frequency scaling, caches), only one key and message are exercised,
+/// and schnorrkel verification is not designed to be constant time, since everything it
+/// touches is public. A `True` result only means no gross difference was observed on this
+/// machine during this run.
+///
+/// # Arguments
+///
+/// * `iterations` - The number of verifications to time for each case.
+/// * `tolerance` - The maximum accepted relative difference between the two totals, defaults to 0.1.
+///
+/// # Returns
+///
+/// True if the two timings differ by at most `tolerance`, relative to the slower one.
+///
+/// # Raises
+///
+/// * `ValueError` - If `iterations` is zero or `tolerance` is negative.
+#[pyfunction(tolerance = "0.1")]
+#[pyo3(text_signature = "(iterations, tolerance=0.1)")]
+pub fn timing_self_test(iterations: u32, tolerance: f64) -> PyResult<bool> {
+    if iterations == 0 {
+        return Err(_error("INVALID_ARGUMENT", "Expected at least one iteration"));
+    }
+    if tolerance.is_nan() || tolerance < 0.0 {
+        return Err(_error("INVALID_ARGUMENT", format!("Invalid tolerance: {}", tolerance)));
+    }
+
+    let keypair = MiniSecretKey::from_bytes(&[7u8; MINI_SECRET_KEY_LENGTH])
+        .expect("32 bytes can always build a key; qed")
+        .expand_to_keypair(ExpansionMode::Ed25519);
+    let context = signing_context(SIGNING_CTX);
+    let message: &[u8] = b"timing self test";
+    let valid = keypair.sign(context.bytes(message));
+    let invalid = keypair.sign(context.bytes(b"a different message"));
+
+    let mut valid_time = Duration::new(0, 0);
+    let mut invalid_time = Duration::new(0, 0);
+    let mut accepted = 0u32;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        accepted += keypair.public.verify(context.bytes(message), &valid).is_ok() as u32;
+        valid_time += start.elapsed();
+
+        let start = Instant::now();
+        accepted += keypair.public.verify(context.bytes(message), &invalid).is_ok() as u32;
+        invalid_time += start.elapsed();
+    }
+    debug_assert_eq!(accepted, iterations);
+
+    let slower = valid_time.max(invalid_time).as_secs_f64();
+    let faster = valid_time.min(invalid_time).as_secs_f64();
+    if slower == 0.0 {
+        return Ok(true);
+    }
+    Ok((slower - faster) / slower <= tolerance)
+}
+
+/// Assembles a signature from an externally computed R point and s scalar, and checks it.
+///
+/// The signature is the 32-byte compressed R followed by the 32-byte s, with the schnorrkel
+/// marker bit set on the last byte. Before it is returned, the signature is verified
+/// against `pubkey` over `message` under `context`, so components that don't belong
+/// together are rejected rather than handed back.
+///
+/// # Arguments
+///
+/// * `r_compressed` - The compressed Ristretto point R, as 32 bytes.
+/// * `s_scalar` - The canonical scalar s, as 32 little-endian bytes.
+/// * `pubkey` - The sr25519 public key the signature should verify under.
+/// * `message` - The binary message that was signed.
+/// * `context` - The signing context used by the external signer.
+///
+/// # Returns
+///
+/// A 64-byte signature.
+///
+/// # Raises
+///
+/// * `ValueError` - If R is not a valid point, s is not canonical, the public key is
+///   invalid, or the assembled signature does not verify.
+#[pyfunction]
+#[pyo3(text_signature = "(r_compressed, s_scalar, pubkey, message, context)")]
+pub fn assemble_signature(r_compressed: PubKey, s_scalar: ScalarBytes, pubkey: PubKey, message: Message, context: Message) -> PyResult<Sig> {
+    PublicKey::from_bytes(&r_compressed.0)
+        .map_err(|err| _error("INVALID_SIGNATURE", format!("Invalid R point: {}", err)))?;
+    if Scalar::from_canonical_bytes(s_scalar.0).is_none() {
+        return Err(_error("INVALID_SIGNATURE", "Invalid s scalar: not canonical"));
+    }
+    let pk = PublicKey::from_bytes(&pubkey.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+
+    let mut bytes = [0u8; SIGNATURE_LENGTH];
+    bytes[..32].copy_from_slice(&r_compressed.0);
+    bytes[32..].copy_from_slice(&s_scalar.0);
+    bytes[63] |= 128;
+    let sig = Signature::from_bytes(&bytes)
+        .map_err(|err| _error("INVALID_SIGNATURE", format!("Invalid signature: {}", err)))?;
+
+    pk.verify(signing_context(&context.0).bytes(&message.0), &sig)
+        .map_err(|_| _error("INVALID_SIGNATURE", "Assembled signature does not verify"))?;
+    Ok(Sig(bytes))
+}
+
+/// Splits a signature into its R point and s scalar.
+///
+/// sr25519 signatures don't allow the public key to be recovered, but the components are
+/// useful for debugging and for interop with other Schnorr implementations. The schnorrkel
+/// marker bit is cleared from s, so it is returned as a plain canonical scalar.
+///
+/// # Arguments
+///
+/// * `signature` - The 64-byte signature.
+///
+/// # Returns
+///
+/// A tuple of the 32-byte compressed R and the 32-byte little-endian s.
+///
+/// # Raises
+///
+/// * `ValueError` - If the signature is not a valid schnorrkel signature.
+#[pyfunction]
+#[pyo3(text_signature = "(signature)")]
+pub fn signature_components(signature: Sig) -> PyResult<(PubKey, ScalarBytes)> {
+    Signature::from_bytes(&signature.0)
+        .map_err(|err| _error("INVALID_SIGNATURE", format!("Invalid signature: {}", err)))?;
+
+    let mut r = [0u8; PUBLIC_KEY_LENGTH];
+    let mut s = [0u8; SCALAR_LENGTH];
+    r.copy_from_slice(&signature.0[..32]);
+    s.copy_from_slice(&signature.0[32..]);
+    s[31] &= 127;
+    Ok((PubKey(r), ScalarBytes(s)))
+}
+
+/// Reassembles a signature from the components returned by `signature_components`.
+///
+/// Unlike `assemble_signature`, the result is not verified, and R is not checked to be a
+/// valid point.
+///
+/// # Arguments
+///
+/// * `r` - The compressed R point, as 32 bytes.
+/// * `s` - The canonical scalar s, as 32 little-endian bytes.
+///
+/// # Returns
+///
+/// A 64-byte signature.
+///
+/// # Raises
+///
+/// * `ValueError` - If s is not a canonical scalar.
+#[pyfunction]
+#[pyo3(text_signature = "(r, s)")]
+pub fn signature_from_components(r: PubKey, s: ScalarBytes) -> PyResult<Sig> {
+    if Scalar::from_canonical_bytes(s.0).is_none() {
+        return Err(_error("INVALID_SIGNATURE", "Invalid s scalar: not canonical"));
+    }
+
+    let mut bytes = [0u8; SIGNATURE_LENGTH];
+    bytes[..32].copy_from_slice(&r.0);
+    bytes[32..].copy_from_slice(&s.0);
+    bytes[63] |= 128;
+    Ok(Sig(bytes))
+}
+
+/// Returns a public and private key pair from the given 32-byte seed.
+///
+/// The seed is expanded into a secret key with schnorrkel's `"ed25519"` expansion by
+/// default, which is what substrate uses. `"uniform"` selects schnorrkel's uniform
+/// expansion instead, giving a different keypair for the same seed.
+///
+/// # Arguments
+///
+/// * `seed` - A 32 byte seed.
+/// * `expansion_mode` - Either `"ed25519"` (the default) or `"uniform"`.
+///
+/// # Returns
+///
+/// A tuple containing the 32-byte public key and 64-byte secret key, in that order.
+///
+/// # Raises
+///
+/// * `ValueError` - If the expansion mode is not one of the above.
+#[pyfunction(expansion_mode = "\"ed25519\"")]
+#[pyo3(text_signature = "(seed, expansion_mode='ed25519')")]
+pub fn pair_from_seed(seed: Seed, expansion_mode: &str) -> PyResult<Keypair> {
+    let mode = _expansion_mode(expansion_mode)?;
+    let k = match MiniSecretKey::from_bytes(&seed.0) {
+        Ok(some_key) => some_key,
+        Err(err) => return Err(_error("INVALID_KEY", format!("Invalid seed: {}", err))),
+    };
+    let kp = k.expand_to_keypair(mode);
+
+    Ok(Keypair(kp.public.to_bytes(), kp.secret.to_bytes()))
+}
+
+fn _expansion_mode(expansion_mode: &str) -> PyResult<ExpansionMode> {
+    match expansion_mode {
+        "ed25519" => Ok(ExpansionMode::Ed25519),
+        "uniform" => Ok(ExpansionMode::Uniform),
+        _ => Err(_error("INVALID_ARGUMENT", format!("Invalid expansion mode: {}", expansion_mode))),
+    }
+}
+
+/// Expands a 32-byte seed into a secret key, without computing its public key.
+///
+/// The expansion is the same as in `pair_from_seed`, so the result is the secret half of
+/// the keypair it returns for the same seed and mode.
+///
+/// # Arguments
+///
+/// * `seed` - A 32 byte seed.
+/// * `expansion_mode` - Either `"ed25519"` (the default) or `"uniform"`.
+///
+/// # Returns
+///
+/// The 64-byte secret key.
+///
+/// # Raises
+///
+/// * `ValueError` - If the expansion mode is not one of the above.
+#[pyfunction(expansion_mode = "\"ed25519\"")]
+#[pyo3(text_signature = "(seed, expansion_mode='ed25519')")]
+pub fn expand_mini_secret(seed: Seed, expansion_mode: &str) -> PyResult<PrivKey> {
+    let mode = _expansion_mode(expansion_mode)?;
+    let mini = MiniSecretKey::from_bytes(&seed.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid seed: {}", err)))?;
+    Ok(PrivKey(mini.expand(mode).to_bytes()))
+}
+
+/// Signs a message with the keypair of a 32-byte seed, for callers that only store seeds.
+///
+/// The seed is expanded as `pair_from_seed` does by default, so this is the same as signing
+/// with that keypair, without handing the expanded secret key back to Python.
+///
+/// # Arguments
+///
+/// * `seed` - A 32 byte seed.
+/// * `message` - The binary message to sign.
+///
+/// # Returns
+///
+/// A 64-byte signature.
+#[pyfunction]
+#[pyo3(text_signature = "(seed, message)")]
+pub fn sign_from_seed(seed: Seed, message: Message) -> PyResult<Sig> {
+    sign(pair_from_seed(seed, "ed25519")?, message)
+}
+
+/// Returns the keypairs of a list of 32-byte seeds, as `pair_from_seed` would one by one.
+///
+/// # Arguments
+///
+/// * `seeds` - A list of 32-byte seeds, expanded with the `"ed25519"` mode.
+///
+/// # Returns
+///
+/// A list of `(public_key, secret_key)` keypair tuples, one per seed.
+///
+/// # Raises
+///
+/// * `ValueError` - If any seed is not 32 bytes long, naming its index.
+#[pyfunction]
+#[pyo3(text_signature = "(seeds)")]
+pub fn pairs_from_seeds(seeds: Vec<Message>) -> PyResult<Vec<Keypair>> {
+    seeds.into_iter().enumerate()
+        .map(|(index, seed)| {
+            if seed.0.len() != MINI_SECRET_KEY_LENGTH {
+                return Err(_error("INVALID_LENGTH", format!(
+                    "Invalid seed at index {}: expected {} bytes, got {}", index, MINI_SECRET_KEY_LENGTH, seed.0.len())));
+            }
+            let mut bytes = [0u8; MINI_SECRET_KEY_LENGTH];
+            bytes.copy_from_slice(&seed.0);
+            pair_from_seed(Seed(bytes), "ed25519")
+        })
+        .collect()
+}
+
+/// Generates a new random keypair, along with the seed it was generated from.
+///
+/// The seed is sampled from the operating system's random number generator and expanded
+/// as in `pair_from_seed`, so passing it to `pair_from_seed` gives the same keypair again.
+///
+/// # Returns
+///
+/// A tuple containing the 32-byte seed and a `(public_key, secret_key)` keypair tuple.
+#[pyfunction]
+#[pyo3(text_signature = "()")]
+pub fn generate_random_keypair() -> PyResult<(Seed, Keypair)> {
+    let seed = MiniSecretKey::generate().to_bytes();
+    let keypair = pair_from_seed(Seed(seed), "ed25519")?;
+    Ok((Seed(seed), keypair))
+}
+
+/// Returns a public and private key pair from a BIP39 mnemonic phrase.
+///
+/// This follows substrate rather than the BIP39 seed derivation: the 32-byte seed is the
+/// first half of PBKDF2-HMAC-SHA512 (2048 rounds) over the mnemonic's *entropy*, salted with
+/// `"mnemonic"` followed by the password. The seed is then expanded as in `pair_from_seed`,
+/// so the keys match those from `subkey` and polkadot-js for the same phrase.
+///
+/// # Arguments
+///
+/// * `phrase` - The English BIP39 mnemonic phrase.
+/// * `password` - The optional password, defaults to an empty string.
+///
+/// # Returns
+///
+/// A tuple containing the 32-byte public key and 64-byte secret key, in that order.
+///
+/// # Raises
+///
+/// * `ValueError` - If the phrase is not a valid BIP39 mnemonic.
+#[pyfunction(password = "\"\"")]
+#[pyo3(text_signature = "(phrase, password='')")]
+pub fn pair_from_mnemonic(phrase: &str, password: &str) -> PyResult<Keypair> {
+    let mnemonic = Mnemonic::from_phrase(phrase, Language::English)
+        .map_err(|err| _error("INVALID_MNEMONIC", format!("Invalid mnemonic: {}", err)))?;
+    pair_from_seed(_mini_secret_from_entropy(mnemonic.entropy(), password)?, "ed25519")
+}
+
+/// Returns a public and private key pair from raw BIP39 entropy.
+///
+/// This is `pair_from_mnemonic` without the words: the entropy is what a mnemonic phrase
+/// encodes, and the keys are derived from it in the same substrate-specific way.
+///
+/// # Arguments
+///
+/// * `entropy` - The 16, 20, 24, 28 or 32 bytes of mnemonic entropy.
+/// * `password` - The optional password, defaults to an empty string.
+///
+/// # Returns
+///
+/// A tuple containing the 32-byte public key and 64-byte secret key, in that order.
+///
+/// # Raises
+///
+/// * `ValueError` - If the entropy is not a valid BIP39 entropy length.
+#[pyfunction(password = "\"\"")]
+#[pyo3(text_signature = "(entropy, password='')")]
+pub fn pair_from_entropy(entropy: Message, password: &str) -> PyResult<Keypair> {
+    pair_from_seed(_mini_secret_from_entropy(&entropy.0, password)?, "ed25519")
+}
+
+/// Returns a public and private key pair derived from a password and salt.
+///
+/// The password is stretched with Argon2id (version 0x13) into a 32-byte seed, which is
+/// then expanded exactly as in `pair_from_seed`.
+///
+/// **Security caveat:** keys derived from something a human can remember are only as
+/// strong as that password. Argon2id makes each guess expensive, but a weak or reused
+/// password can still be brute-forced offline by anyone who learns the public key.
+/// Prefer `pair_from_seed` with 32 bytes from a secure random source. The salt is
+/// mandatory and should be unique per key (e.g. an account identifier) so the same
+/// password does not map to the same key everywhere.
+///
+/// The GIL is released while the password is stretched, so other Python threads keep
+/// running.
+///
+/// # Arguments
+///
+/// * `password` - The password to derive from.
+/// * `salt` - The salt, at least 8 bytes long.
+/// * `kdf_params` - Optional tuple of (memory_kib, iterations, parallelism) for Argon2id,
+///   defaults to (65536, 3, 1). They may be at most (4194304, 64, 16), that is 4 GiB of
+///   memory, 64 iterations and 16 lanes.
+///
+/// # Returns
+///
+/// A tuple containing the 32-byte public key and 64-byte secret key, in that order.
+///
+/// # Raises
+///
+/// * `ValueError` - If the salt is too short or the KDF parameters are out of range.
+#[pyfunction(kdf_params = "None")]
+#[pyo3(text_signature = "(password, salt, kdf_params=None)")]
+pub fn keypair_from_password(py: Python, password: &str, salt: Message, kdf_params: Option<(u32, u32, u32)>) -> PyResult<Keypair> {
+    let (m_cost, t_cost, p_cost) = kdf_params.unwrap_or(PASSWORD_KDF_PARAMS);
+    let (max_m_cost, max_t_cost, max_p_cost) = PASSWORD_KDF_MAX_PARAMS;
+    if m_cost > max_m_cost || t_cost > max_t_cost || p_cost > max_p_cost {
+        return Err(_error("INVALID_ARGUMENT", format!(
+            "Invalid KDF parameters: ({}, {}, {}) exceed the maximum ({}, {}, {})",
+            m_cost, t_cost, p_cost, max_m_cost, max_t_cost, max_p_cost)));
+    }
+    let params = Params::new(m_cost, t_cost, p_cost, Some(MINI_SECRET_KEY_LENGTH))
+        .map_err(|err| _error("INVALID_ARGUMENT", format!("Invalid KDF parameters: {}", err)))?;
+
+    let mut seed = [0u8; MINI_SECRET_KEY_LENGTH];
+    let derived = py.allow_threads(|| Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), &salt.0, &mut seed));
+    let keypair = derived
+        .map_err(|err| _error("INVALID_ARGUMENT", format!("Password derivation failed: {}", err)))
+        .and_then(|_| pair_from_seed(Seed(seed), "ed25519"));
+    seed.zeroize();
+    keypair
+}
+
+/// Returns one of substrate's well-known development keypairs, such as `//Alice`.
+///
+/// The keypair is derived from the public development phrase
+/// `"bottom drive obey lake curtain smoke basket hold race lonely fit walk"` (as in
+/// `pair_from_mnemonic`, with no password) using the single hard junction `//<name>`,
+/// exactly as substrate does. These keys are public knowledge and must only be used for
+/// testing.
+///
+/// # Arguments
+///
+/// * `name` - The junction name, e.g. `"Alice"`, `"Bob"` or `"Charlie"`.
+///
+/// # Returns
+///
+/// A tuple containing the 32-byte public key and 64-byte secret key, in that order.
+#[pyfunction]
+#[pyo3(text_signature = "(name)")]
+pub fn dev_keypair(name: &str) -> PyResult<Keypair> {
+    let root = pair_from_mnemonic(DEV_PHRASE, "")?;
+    let derived = derive_path(ExtendedKeypair([0u8; CHAIN_CODE_LENGTH], root.0, root.1), &format!("//{}", name))?;
+    Ok(Keypair(derived.1, derived.2))
+}
+
+/// Returns a context manager holding a secret key, which is wiped when the block exits.
+///
+/// Used as `with sr25519.load_secret(secret_key) as sk: sk.sign(message, context)`. Only
+/// the copy held by the guard is wiped: the `bytes` object passed in is immutable and
+/// remains in Python's memory until it is garbage collected.
+///
+/// # Arguments
+///
+/// * `secret_key` - The 64-byte sr25519 secret key.
+///
+/// # Returns
+///
+/// A `SecretGuard` holding the secret key.
+///
+/// # Raises
+///
+/// * `ValueError` - If the secret key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(secret_key)")]
+pub fn load_secret(secret_key: PrivKey) -> PyResult<SecretGuard> {
+    SecretKey::from_bytes(&secret_key.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+    Ok(SecretGuard { secret_key: secret_key.0, wiped: false })
+}
+
+/// Returns the corresponding public key for the given secret key.
+///
+/// # Arguments
+///
+/// * `secret_key` - The sr25519 secret key, comprised of the 32 byte scalar and 32 byte nonce.
+///
+/// # Returns
+///
+/// The 32-byte public key corresponding to the provided secret key.
+///
+/// # Raises
+///
+/// * `ValueError` - If the provided secret key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(secret_key)")]
+pub fn public_from_secret_key(secret_key: PrivKey) -> PyResult<PubKey> {
+    let sec_key = match SecretKey::from_bytes(&secret_key.0) {
+        Ok(some_key) => some_key,
+        Err(err) => return Err(_error("INVALID_KEY", format!("Invalid secret key: {}", err))),
+    };
+    let pub_key = sec_key.to_public();
+
+    Ok(PubKey(pub_key.to_bytes()))
+}
+
+/// Returns the keypair of a secret key.
+///
+/// # Arguments
+///
+/// * `secret_key` - The 64-byte sr25519 secret key.
+///
+/// # Returns
+///
+/// A tuple containing the 32-byte public key and 64-byte secret key, in that order.
+///
+/// # Raises
+///
+/// * `ValueError` - If the secret key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(secret_key)")]
+pub fn pair_from_secret_key(secret_key: PrivKey) -> PyResult<Keypair> {
+    let secret = SecretKey::from_bytes(&secret_key.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+    Ok(Keypair(secret.to_public().to_bytes(), secret_key.0))
+}
+
+/// Serializes a keypair into a single 96-byte blob, as schnorrkel's `Keypair::to_bytes` does.
+///
+/// The blob is the 64-byte secret key followed by the 32-byte public key. Note that this
+/// is the reverse of the order of the keypair tuple.
+///
+/// # Arguments
+///
+/// * `keypair` - The sr25519 keypair, as a tuple of (public_bytes, private_bytes)
+///
+/// # Returns
+///
+/// The 96-byte `secret_key || public_key`.
+///
+/// # Raises
+///
+/// * `ValueError` - If either the public or private key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(keypair)")]
+pub fn keypair_to_bytes<'p>(py: Python<'p>, keypair: Keypair) -> PyResult<&'p PyBytes> {
+    let keypair = _to_schnorrkel_keypair(&keypair)?;
+    Ok(PyBytes::new(py, &keypair.to_bytes()))
+}
+
+/// Parses a 96-byte blob from `keypair_to_bytes` back into a keypair.
+///
+/// # Arguments
+///
+/// * `blob` - The 96-byte `secret_key || public_key`.
+///
+/// # Returns
+///
+/// A tuple containing the 32-byte public key and 64-byte secret key, in that order.
+///
+/// # Raises
+///
+/// * `ValueError` - If the blob is not 96 bytes, or either key in it is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(blob)")]
+pub fn keypair_from_bytes(blob: Message) -> PyResult<Keypair> {
+    let bytes = _check_bytes_len(blob.0, KEYPAIR_LENGTH)?;
+    let keypair = SchnorrkelKeypair::from_bytes(&bytes)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid keypair: {}", err)))?;
+    Ok(Keypair(keypair.public.to_bytes(), keypair.secret.to_bytes()))
+}
+
+/// Returns whether the given bytes are a valid sr25519 public key.
+///
+/// # Arguments
+///
+/// * `data` - The bytes to check.
+///
+/// # Returns
+///
+/// True if `data` is 32 bytes long and a valid Ristretto point encoding, false otherwise.
+#[pyfunction]
+#[pyo3(text_signature = "(data)")]
+pub fn is_valid_public_key(data: Message) -> bool {
+    PublicKey::from_bytes(&data.0).is_ok()
+}
+
+/// Returns whether the given bytes are a valid sr25519 secret key.
+///
+/// # Arguments
+///
+/// * `data` - The bytes to check.
+///
+/// # Returns
+///
+/// True if `data` is 64 bytes long and starts with a canonical scalar, false otherwise.
+#[pyfunction]
+#[pyo3(text_signature = "(data)")]
+pub fn is_valid_secret_key(data: Message) -> bool {
+    SecretKey::from_bytes(&data.0).is_ok()
+}
+
+/// Returns the public key half of a keypair.
+///
+/// By default the public key is also recomputed from the secret key, so a tuple pairing a
+/// public key with the wrong secret key is caught here rather than by signatures failing
+/// to verify later on.
+///
+/// # Arguments
+///
+/// * `keypair` - The sr25519 keypair, as a tuple of (public_bytes, private_bytes)
+/// * `check` - Whether to check the public key against the secret key.
+///
+/// # Returns
+///
+/// The 32-byte public key.
+///
+/// # Raises
+///
+/// * `ValueError` - If `check` is true and the secret key is invalid or doesn't match the public key.
+#[pyfunction(check = "true")]
+#[pyo3(text_signature = "(keypair, check=True)")]
+pub fn public_from_keypair(keypair: Keypair, check: bool) -> PyResult<PubKey> {
+    if check && public_from_secret_key(PrivKey(keypair.1))?.0 != keypair.0 {
+        return Err(_error("INVALID_KEY", "Invalid keypair: public key does not match the secret key"));
+    }
+    Ok(PubKey(keypair.0))
+}
+
+/// Returns the 32-byte key scalar held in the first half of a secret key.
+///
+/// This is *not* the seed the key was generated from, and passing it to `pair_from_seed`
+/// does not give the same keypair back. Expanding a seed hashes it with SHA-512, which can't
+/// be reversed, and, for the `"ed25519"` expansion, the scalar is the clamped hash divided by
+/// the cofactor 8. The scalar is what the key signs with, so together with the nonce half
+/// of the secret key it is enough to back the key up, but not to recover a BIP39 phrase.
+///
+/// # Arguments
+///
+/// * `secret_key` - The 64-byte sr25519 secret key.
+///
+/// # Returns
+///
+/// The 32-byte canonical encoding of the key scalar.
+///
+/// # Raises
+///
+/// * `ValueError` - If the key scalar is not canonically encoded.
+#[pyfunction]
+#[pyo3(text_signature = "(secret_key)")]
+pub fn secret_key_to_mini_secret(secret_key: PrivKey) -> PyResult<ScalarBytes> {
+    let secret = SecretKey::from_bytes(&secret_key.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+    Ok(ScalarBytes(_secret_scalar(&secret).to_bytes()))
+}
+
+/// Returns a secret key in the expanded Ed25519 layout other libraries expect.
+///
+/// The first 32 bytes are the key scalar multiplied by the cofactor 8, as a little-endian
+/// integer, which is the form an expanded Ed25519 secret key holds its scalar in. The last
+/// 32 bytes are the nonce, unchanged. This is the layout of schnorrkel's `to_ed25519_bytes`
+/// and of the secret keys in polkadot-js; the secret keys used elsewhere in this module
+/// store the scalar without the multiplication.
+///
+/// # Arguments
+///
+/// * `secret_key` - The 64-byte sr25519 secret key.
+///
+/// # Returns
+///
+/// The 64-byte secret key in the Ed25519 layout.
+///
+/// # Raises
+///
+/// * `ValueError` - If the secret key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(secret_key)")]
+pub fn secret_key_to_ed25519_bytes(secret_key: PrivKey) -> PyResult<PrivKey> {
+    let secret = SecretKey::from_bytes(&secret_key.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+    Ok(PrivKey(secret.to_ed25519_bytes()))
+}
+
+/// Reads a secret key in the expanded Ed25519 layout, the inverse of `secret_key_to_ed25519_bytes`.
+///
+/// This is schnorrkel's `from_ed25519_bytes`: the first 32 bytes are a little-endian scalar,
+/// which is divided by the cofactor 8 to give the key scalar, and the last 32 bytes become the
+/// nonce unchanged. Ed25519 clamping clears the low three bits of the scalar, so dividing
+/// loses nothing; a scalar with any of those bits set, or that is still not below the group
+/// order once divided, is rejected rather than silently changed.
+///
+/// The key does not sign compatibly with the Ed25519 library it came from. Its public key is
+/// a Ristretto encoding, not an Edwards one, and while Ed25519 hashes the nonce half with the
+/// message to get each signing nonce, sr25519 only feeds it into a merlin transcript as a
+/// witness, so the two schemes never share nonces or signatures.
+///
+/// # Arguments
+///
+/// * `data` - The 64-byte secret key in the Ed25519 layout.
+///
+/// # Returns
+///
+/// The 64-byte sr25519 secret key.
+///
+/// # Raises
+///
+/// * `ValueError` - If the scalar is not a multiple of 8, or is too large once divided.
+#[pyfunction]
+#[pyo3(text_signature = "(data)")]
+pub fn secret_from_ed25519_bytes(data: PrivKey) -> PyResult<PrivKey> {
+    if data.0[0] & 7 != 0 {
+        return Err(_error("INVALID_KEY", "Invalid Ed25519 secret key: the scalar is not a multiple of 8"));
+    }
+    let secret = SecretKey::from_ed25519_bytes(&data.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid Ed25519 secret key: {}", err)))?;
+    let bytes = secret.to_bytes();
+    SecretKey::from_bytes(&bytes)
+        .map_err(|_| _error("INVALID_KEY", "Invalid Ed25519 secret key: the scalar is not below 8 times the group order"))?;
+    Ok(PrivKey(bytes))
+}
+
+/// Compares two secret keys in constant time.
+///
+/// Comparing secret keys with `==` on bytes returns as soon as a byte differs, so the time
+/// it takes reveals how many leading bytes match. An attacker able to time repeated
+/// comparisons can use that to recover a key byte by byte. This comparison always reads
+/// every byte.
+///
+/// # Arguments
+///
+/// * `a` - The first 64-byte secret key.
+/// * `b` - The second 64-byte secret key.
+///
+/// # Returns
+///
+/// True if the keys are equal, false otherwise.
+#[pyfunction]
+#[pyo3(text_signature = "(a, b)")]
+pub fn secret_keys_equal(a: PrivKey, b: PrivKey) -> bool {
+    bool::from(a.0.ct_eq(&b.0))
+}
+
+/// Compares two public keys in constant time, as `secret_keys_equal` does.
+///
+/// Public keys are not secret, so `==` is fine in most cases. This is for protocols where
+/// the key being compared against is itself meant to stay private, such as an expected
+/// key in an authentication check.
+///
+/// # Arguments
+///
+/// * `a` - The first 32-byte public key.
+/// * `b` - The second 32-byte public key.
+///
+/// # Returns
+///
+/// True if the keys are equal, false otherwise.
+#[pyfunction]
+#[pyo3(text_signature = "(a, b)")]
+pub fn public_keys_equal(a: PubKey, b: PubKey) -> bool {
+    bool::from(a.0.ct_eq(&b.0))
+}
+
+
+/// Hashes arbitrary bytes, such as a string label, into a 32-byte chain code.
+///
+/// The result is the Blake2b-256 hash of `data`, and can be passed as the `id` of
+/// `derive_keypair`, `derive_pubkey` or `hard_derive_keypair`, so the same label always
+/// selects the same child. This is not how substrate encodes path junctions; for those,
+/// use `derive_path`.
+///
+/// # Arguments
+///
+/// * `data` - The bytes to hash.
+///
+/// # Returns
+///
+/// The 32-byte chain code.
+#[pyfunction]
+#[pyo3(text_signature = "(data)")]
+pub fn chain_code_from_bytes<'p>(py: Python<'p>, data: Message) -> &'p PyBytes {
+    PyBytes::new(py, &_blake2b_256(&data.0))
+}
+
+/// Returns the soft derivation of the public key of the specified child.
+///
+/// # Arguments
+///
+/// * `extended_pubkey` - The extended public key, comprised of the chain code and public key.
+/// * `id` - The identifier for the child key to derive.
+///
+/// # Returns
+///
+/// A new extended public key for the child.
+#[pyfunction]
+#[pyo3(text_signature = "(extended_pubkey, id)")]
+pub fn derive_pubkey(extended_pubkey: ExtendedPubKey, id: Message) -> PyResult<ExtendedPubKey> {
+    let chain_code = ChainCode(extended_pubkey.0);
+    let pubkey = PublicKey::from_bytes(&extended_pubkey.1)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+    let (new_pubkey, new_chaincode) = pubkey.derived_key_simple(chain_code, &id.0);
+
+    Ok(ExtendedPubKey(new_chaincode.0, new_pubkey.to_bytes()))
+}
+
+/// Returns the soft derivation of the public key of the child with a numeric index.
+///
+/// The child is substrate's soft junction `/index`, as `derive_path_pubkey` derives it:
+/// the chain code is the index as a little-endian u64 zero-padded to 32 bytes, and the
+/// message is empty, so the chain code of `extended_pubkey` is not used. The children are
+/// the public halves of those `derive_keypairs_range` gives.
+///
+/// # Arguments
+///
+/// * `extended_pubkey` - The extended public key, comprised of the chain code and public key.
+/// * `index` - The index of the child key to derive, from 0 to 2**64 - 1.
+///
+/// # Returns
+///
+/// A new extended public key for the child.
+///
+/// # Raises
+///
+/// * `ValueError` - If the index is negative or too large, or the public key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(extended_pubkey, index)")]
+pub fn derive_pubkey_index(extended_pubkey: ExtendedPubKey, index: i128) -> PyResult<ExtendedPubKey> {
+    if index < 0 || index > u64::MAX as i128 {
+        return Err(_error("INVALID_ARGUMENT", format!("Invalid index: {} is not a u64", index)));
+    }
+    derive_path_pubkey(extended_pubkey, &format!("/{}", index))
+}
+
+/// Always raises: there is no hard derivation of a public key.
+///
+/// A hard child is derived from the parent's secret key, so it cannot be computed from the
+/// extended public key alone. This exists so that callers reaching for it get an error that
+/// says so; use `hard_derive_keypair` with the extended keypair instead.
+///
+/// # Arguments
+///
+/// * `extended_pubkey` - The extended public key, comprised of the chain code and public key.
+/// * `id` - The identifier for the child key to derive.
+///
+/// # Raises
+///
+/// * `ValueError` - Always, with the code `INVALID_ARGUMENT`.
+#[pyfunction]
+#[pyo3(text_signature = "(extended_pubkey, id)")]
+pub fn hard_derive_pubkey(extended_pubkey: ExtendedPubKey, id: Message) -> PyResult<ExtendedPubKey> {
+    let _ = (extended_pubkey, id);
+    Err(_error("INVALID_ARGUMENT",
+        "Hard derivation requires the secret key: use hard_derive_keypair with the extended keypair, or derive_pubkey for a soft child"))
+}
+
+/// Returns the soft deriviation of the private and public key of the specified child.
+///
+/// # Arguments
+///
+/// * `extended_keypair` - The extended keypair, comprised of the chain code, public key, and private key.
+/// * `id` - The identifier for the child key to derive.
+///
+/// # Returns
+///
+/// A new extended keypair for the child.
+///
+/// *NOTE:* The chain code, public key, and secret key scalar are generated
+/// deterministically, but the secret key nonce is *RANDOM*, even with
+/// identical input. `derive_keypair_deterministic` fixes the nonce as well.
+#[pyfunction]
+#[pyo3(text_signature = "(extended_keypair, id)")]
+pub fn derive_keypair(extended_keypair: ExtendedKeypair, id: Message) -> PyResult<ExtendedKeypair> {
+    let chain_code = ChainCode(extended_keypair.0);
+    let pubkey = PublicKey::from_bytes(&extended_keypair.1)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+    let privkey = SecretKey::from_bytes(&extended_keypair.2)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+    let keypair = SchnorrkelKeypair{secret: privkey, public: pubkey};
+    let (new_keypair, new_chaincode) = keypair.derived_key_simple(chain_code, &id.0);
+
+    Ok(ExtendedKeypair(new_chaincode.0, new_keypair.public.to_bytes(), new_keypair.secret.to_bytes()))
+}
+
+/// Returns the soft derivation of a keypair's child, as `derive_keypair` does, with a
+/// deterministic secret key nonce.
+///
+/// The chain code, public key and secret key scalar are the same as `derive_keypair`
+/// gives. Only the nonce half of the secret key changes: it is the 32-byte challenge
+/// `nonce` of a merlin transcript labelled `sr25519-derive-nonce`, into which the child's
+/// chain code (`chain-code`) and secret key scalar (`key`) are appended. Repeated calls
+/// with the same inputs give byte-identical results.
+///
+/// # Arguments
+///
+/// * `extended_keypair` - The extended keypair, comprised of the chain code, public key, and private key.
+/// * `id` - The identifier for the child key to derive.
+///
+/// # Returns
+///
+/// A new extended keypair for the child.
+#[pyfunction]
+#[pyo3(text_signature = "(extended_keypair, id)")]
+pub fn derive_keypair_deterministic(extended_keypair: ExtendedKeypair, id: Message) -> PyResult<ExtendedKeypair> {
+    let mut child = derive_keypair(extended_keypair, id)?;
+
+    let mut t = Transcript::new(b"sr25519-derive-nonce");
+    t.append_message(b"chain-code", &child.0);
+    t.append_message(b"key", &child.2[..32]);
+    t.challenge_bytes(b"nonce", &mut child.2[32..]);
+    Ok(child)
+}
+
+/// Soft derives the children of a keypair for a range of consecutive indices.
+///
+/// Each child is the soft junction `/index` of substrate, as `derive_path` derives it: the
+/// chain code is the index as a little-endian u64 zero-padded to 32 bytes, and the message
+/// is empty. That is, child `i` is `derive_keypair` called with that chain code in place
+/// of the parent's and an empty id.
+///
+/// **The chain code of `extended_keypair` is ignored**: only its keys are used, so two
+/// parents with the same keys and different chain codes give the same children. `derive_pubkey_index` derives the same
+/// children's public keys. The parent keys are only parsed once, which makes this faster
+/// than calling `derive_path` in a loop when scanning a wallet for used accounts.
+///
+/// # Arguments
+///
+/// * `extended_keypair` - The extended keypair, comprised of the chain code, public key, and private key.
+/// * `start_index` - The index of the first child.
+/// * `count` - The number of children to derive.
+///
+/// # Returns
+///
+/// A list of the children's extended keypairs, in index order.
+///
+/// # Raises
+///
+/// * `ValueError` - If either key is invalid, or the range goes past the largest u64 index.
+///
+/// *NOTE:* As with `derive_keypair`, the secret key nonces are random.
+#[pyfunction]
+#[pyo3(text_signature = "(extended_keypair, start_index, count)")]
+pub fn derive_keypairs_range(extended_keypair: ExtendedKeypair, start_index: u64, count: u32) -> PyResult<Vec<ExtendedKeypair>> {
+    if start_index as u128 + count as u128 > u64::MAX as u128 + 1 {
+        return Err(_error("INVALID_ARGUMENT", format!(
+            "Invalid index range: {} children from index {} go past the largest index", count, start_index)));
+    }
+    let pubkey = PublicKey::from_bytes(&extended_keypair.1)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+    let privkey = SecretKey::from_bytes(&extended_keypair.2)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+    let keypair = SchnorrkelKeypair{secret: privkey, public: pubkey};
+
+    Ok((0..count).map(|offset| {
+        let index = start_index + offset as u64;
+        let (child, child_chain_code) = keypair.derived_key_simple(_index_junction(index), b"");
+        ExtendedKeypair(child_chain_code.0, child.public.to_bytes(), child.secret.to_bytes())
+    }).collect())
+}
+
+/// Returns the hard derivation of the private and public key of the specified child.
+///
+/// This derivation is performed using the secret material for the key, so even knowing
+/// the extended public key of this or a child key is not enough to go any further up the
+/// hierarchy.
+///
+/// # Arguments
+///
+/// * `extended_keypair` - The extended keypair, comprised of the chain code, public key, and private key.
+/// * `id` - The identifier for the child key to derive.
+///
+/// # Returns
+///
+/// A new extended keypair for the child.
+///
+/// *NOTE:* The chain code, public key, and secret key scalar are generated
+/// deterministically, but the secret key nonce is *RANDOM*, even with
+/// identical input.
+#[pyfunction]
+#[pyo3(text_signature = "(extended_keypair, id)")]
+pub fn hard_derive_keypair(extended_keypair: ExtendedKeypair, id: Message) -> PyResult<ExtendedKeypair> {
+    let chain_code = ChainCode(extended_keypair.0);
+    let privkey = SecretKey::from_bytes(&extended_keypair.2)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+
+    let (new_mini, new_chaincode) = privkey.hard_derive_mini_secret_key(Some(chain_code), &id.0);
+    let new_keypair = new_mini.expand_to_keypair(ExpansionMode::Ed25519);
+    Ok(ExtendedKeypair(new_chaincode.0, new_keypair.public.to_bytes(), new_keypair.secret.to_bytes()))
+}
+
+/// Returns the mini secret key and chain code of a hard derived child, before expansion.
+///
+/// `hard_derive_keypair` expands the derived mini secret key into a keypair and discards
+/// it. Here it is returned instead, so that it can be stored and later expanded with
+/// `pair_from_seed`, which gives the same keypair as `hard_derive_keypair`.
+///
+/// The mini secret key is as sensitive as the child's secret key: anyone holding it can
+/// sign as the child and derive every key below it.
+///
+/// # Arguments
+///
+/// * `extended_keypair` - The extended keypair, comprised of the chain code, public key, and private key.
+/// * `id` - The identifier for the child key to derive.
+///
+/// # Returns
+///
+/// A tuple of the child's 32-byte chain code and its 32-byte mini secret key.
+///
+/// # Raises
+///
+/// * `ValueError` - If the secret key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(extended_keypair, id)")]
+pub fn hard_derive_mini_secret<'p>(py: Python<'p>, extended_keypair: ExtendedKeypair, id: Message) -> PyResult<(&'p PyBytes, Seed)> {
+    let chain_code = ChainCode(extended_keypair.0);
+    let privkey = SecretKey::from_bytes(&extended_keypair.2)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+
+    let (new_mini, new_chaincode) = privkey.hard_derive_mini_secret_key(Some(chain_code), &id.0);
+    Ok((PyBytes::new(py, &new_chaincode.0), Seed(new_mini.to_bytes())))
+}
+
+/// Derives a keypair along a substrate derivation path such as `//hard/soft`.
+///
+/// Each junction is applied in turn, `//` for a hard and `/` for a soft derivation. As in
+/// substrate, a junction's chain code is its SCALE encoding (a little-endian u64 for a
+/// numeric junction, or a length-prefixed string otherwise), zero-padded to 32 bytes or
+/// Blake2b-256 hashed if longer. Hard junctions derive a new mini secret key, which is
+/// expanded as in `pair_from_seed`, and soft junctions use schnorrkel's soft derivation,
+/// both with an empty message.
+///
+/// Because the chain codes come from the path, the chain code of `extended_keypair` is
+/// not used. The returned chain code is the one produced by the last derivation. Substrate's
+/// `///password` syntax is not supported.
+///
+/// # Arguments
+///
+/// * `extended_keypair` - The extended keypair, comprised of the chain code, public key, and private key.
+/// * `path` - The derivation path, e.g. `"//polkadot//0/wallet"`.
+///
+/// # Returns
+///
+/// The extended keypair at the end of the path, or `extended_keypair` if the path is empty.
+///
+/// # Raises
+///
+/// * `ValueError` - If the path is malformed or the secret key is invalid.
+///
+/// *NOTE:* As with `derive_keypair`, the secret key nonce of a soft derived key is random.
+#[pyfunction]
+#[pyo3(text_signature = "(extended_keypair, path)")]
+pub fn derive_path(extended_keypair: ExtendedKeypair, path: &str) -> PyResult<ExtendedKeypair> {
+    let junctions = _parse_derivation_path(path)?;
+    let secret = SecretKey::from_bytes(&extended_keypair.2)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+
+    let mut keypair = secret.to_keypair();
+    let mut chain_code = ChainCode(extended_keypair.0);
+    for (hard, junction) in junctions {
+        let (derived, derived_chain_code) = if hard {
+            let (mini, cc) = keypair.secret.hard_derive_mini_secret_key(Some(junction), b"");
+            (mini.expand_to_keypair(ExpansionMode::Ed25519), cc)
+        } else {
+            keypair.derived_key_simple(junction, b"")
+        };
+        keypair = derived;
+        chain_code = derived_chain_code;
+    }
+    Ok(ExtendedKeypair(chain_code.0, keypair.public.to_bytes(), keypair.secret.to_bytes()))
+}
+
+/// Derives a public key along a substrate-style derivation path of soft junctions.
+///
+/// This is the public counterpart of `derive_path`: junction chain codes are computed the
+/// same way, and each `/` junction is applied with schnorrkel's soft derivation, so the
+/// result matches the public key `derive_path` gives for the same path.
+///
+/// # Arguments
+///
+/// * `extended_pubkey` - The extended public key, comprised of the chain code and public key.
+/// * `path` - The derivation path, e.g. `"/stash/0"`.
+///
+/// # Returns
+///
+/// The extended public key at the end of the path, or `extended_pubkey` if the path is empty.
+///
+/// # Raises
+///
+/// * `ValueError` - If the path is malformed, contains a hard junction, or the public key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(extended_pubkey, path)")]
+pub fn derive_path_pubkey(extended_pubkey: ExtendedPubKey, path: &str) -> PyResult<ExtendedPubKey> {
+    let junctions = _parse_derivation_path(path)?;
+    if junctions.iter().any(|(hard, _)| *hard) {
+        return Err(_error("INVALID_PATH", format!(
+            "Invalid derivation path: {}: hard derivation requires the secret key", path
+        )));
+    }
+    let mut pubkey = PublicKey::from_bytes(&extended_pubkey.1)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+
+    let mut chain_code = ChainCode(extended_pubkey.0);
+    for (_, junction) in junctions {
+        let (derived, derived_chain_code) = pubkey.derived_key_simple(junction, b"");
+        pubkey = derived;
+        chain_code = derived_chain_code;
+    }
+    Ok(ExtendedPubKey(chain_code.0, pubkey.to_bytes()))
+}
+
+/// Encodes a public key as an SS58 address for the given network.
+///
+/// Network IDs below 64 are encoded as a single prefix byte, and IDs from 64 to 16383 as
+/// two bytes, as specified by substrate. The checksum is the first two bytes of the
+/// Blake2b-512 hash of `b"SS58PRE"` followed by the prefix and public key.
+///
+/// # Arguments
+///
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes, to encode.
+/// * `network_id` - The SS58 network identifier, e.g. 0 for Polkadot or 42 for generic substrate.
+///
+/// # Returns
+///
+/// The base58-encoded address.
+///
+/// # Raises
+///
+/// * `ValueError` - If the network ID is outside the range 0 to 16383.
+#[pyfunction]
+#[pyo3(text_signature = "(pubkey, network_id)")]
+pub fn ss58_encode(pubkey: PubKey, network_id: i64) -> PyResult<String> {
+    if network_id < 0 || network_id > SS58_MAX_NETWORK_ID as i64 {
+        return Err(_error("INVALID_ARGUMENT", format!("Invalid network ID: {}", network_id)));
+    }
+    let network_id = network_id as u16;
+    let mut payload = match network_id {
+        0..=63 => vec![network_id as u8],
+        _ => {
+            let first = ((network_id & 0b0000_0000_1111_1100) as u8) >> 2;
+            let second = ((network_id >> 8) as u8) | ((network_id & 0b0000_0000_0000_0011) as u8) << 6;
+            vec![first | 0b0100_0000, second]
+        }
+    };
+    payload.extend_from_slice(&pubkey.0);
+    let checksum = _ss58_checksum(&payload);
+    payload.extend_from_slice(&checksum);
+    Ok(bs58::encode(payload).into_string())
+}
+
+/// Decodes an SS58 address into its public key and network identifier.
+///
+/// This is the inverse of `ss58_encode`: the checksum is verified and the one or two byte
+/// network prefix is stripped from the payload.
+///
+/// # Arguments
+///
+/// * `address` - The base58-encoded SS58 address.
+///
+/// # Returns
+///
+/// A tuple containing the 32-byte public key and the network ID, in that order.
+///
+/// # Raises
+///
+/// * `ValueError` - If the address isn't valid base58, has an unknown prefix, does not
+///   contain a 32-byte public key, or its checksum doesn't match.
+#[pyfunction]
+#[pyo3(text_signature = "(address)")]
+pub fn ss58_decode(address: &str) -> PyResult<(PubKey, u16)> {
+    let data = bs58::decode(address).into_vec()
+        .map_err(|err| _error("INVALID_ENCODING", format!("Invalid base58: {}", err)))?;
+
+    let (prefix_len, network_id) = match data.first() {
+        Some(first @ 0..=63) => (1, *first as u16),
+        Some(first @ 64..=127) if data.len() > 1 => {
+            let lower = ((first & 0b0011_1111) << 2) | (data[1] >> 6);
+            let upper = data[1] & 0b0011_1111;
+            (2, (lower as u16) | ((upper as u16) << 8))
+        },
+        _ => return Err(_error("INVALID_ENCODING", "Invalid SS58 prefix")),
+    };
+
+    if data.len() != prefix_len + PUBLIC_KEY_LENGTH + SS58_CHECKSUM_LENGTH {
+        return Err(_error("INVALID_LENGTH", format!("Invalid SS58 address length: {}", data.len())));
+    }
+    let (payload, checksum) = data.split_at(data.len() - SS58_CHECKSUM_LENGTH);
+    if _ss58_checksum(payload) != checksum {
+        return Err(_error("INVALID_ENCODING", "Invalid SS58 checksum"));
+    }
+
+    let mut pubkey = [0u8; PUBLIC_KEY_LENGTH];
+    pubkey.copy_from_slice(&payload[prefix_len..]);
+    Ok((PubKey(pubkey), network_id))
+}
+
+/// Returns the account ID that is the Blake2b-256 hash of a public key.
+///
+/// sr25519 accounts in substrate usually use the public key itself as the account ID, but
+/// some chains and pallets hash it instead. The key bytes are hashed as given, without
+/// checking that they are a valid public key.
+///
+/// # Arguments
+///
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes.
+///
+/// # Returns
+///
+/// The 32-byte Blake2b-256 hash of the public key.
+#[pyfunction]
+#[pyo3(text_signature = "(pubkey)")]
+pub fn public_key_to_account_id<'p>(py: Python<'p>, pubkey: PubKey) -> &'p PyBytes {
+    PyBytes::new(py, &_blake2b_256(&pubkey.0))
+}
+
+/// Exports a keypair as the JSON document printed by substrate's `subkey` tool.
+///
+/// The document has the fields:
+///
+/// * `secretPhrase` - The mnemonic phrase, only present if `mnemonic` is given.
+/// * `publicKey` - The 0x-prefixed hex encoding of the public key.
+/// * `accountId` - The 0x-prefixed hex account ID, which for sr25519 is the public key.
+/// * `ss58Address` - The SS58 address of the public key for `ss58_format`.
+///
+/// The mnemonic is included as given and not checked against the keypair.
+///
+/// # Arguments
+///
+/// * `keypair` - The sr25519 keypair, as a tuple of (public_bytes, private_bytes)
+/// * `ss58_format` - The SS58 network identifier to encode the address for.
+/// * `mnemonic` - The optional mnemonic phrase the keypair was generated from.
+///
+/// # Returns
+///
+/// The JSON document as a string.
+///
+/// # Raises
+///
+/// * `ValueError` - If the network ID is outside the range 0 to 16383.
+#[pyfunction(mnemonic = "None")]
+#[pyo3(text_signature = "(keypair, ss58_format, mnemonic=None)")]
+pub fn keypair_to_subkey_json(keypair: Keypair, ss58_format: i64, mnemonic: Option<String>) -> PyResult<String> {
+    let public_hex = format!("0x{}", hex::encode(keypair.0));
+    let mut document = json!({
+        "publicKey": public_hex,
+        "accountId": public_hex,
+        "ss58Address": ss58_encode(PubKey(keypair.0), ss58_format)?,
+    });
+    if let Some(phrase) = mnemonic {
+        document["secretPhrase"] = json!(phrase);
+    }
+    Ok(document.to_string())
+}
+
+/// Returns a short fingerprint identifying an extended public key.
+///
+/// The fingerprint is the first 4 bytes of the Blake2b-256 hash of the chain code
+/// followed by the public key, adapting the BIP32 fingerprint idea to sr25519 HD keys.
+/// It is meant for telling keys apart in a user interface only: 4 bytes collide easily,
+/// so it must never be used to authenticate a key.
+///
+/// # Arguments
+///
+/// * `extended_pubkey` - The extended public key, comprised of the chain code and public key.
+///
+/// # Returns
+///
+/// A 4-byte fingerprint.
+#[pyfunction]
+#[pyo3(text_signature = "(extended_pubkey)")]
+pub fn extended_key_fingerprint<'p>(py: Python<'p>, extended_pubkey: ExtendedPubKey) -> &'p PyBytes {
+    let hash = _blake2b_256(&[&extended_pubkey.0[..], &extended_pubkey.1[..]].concat());
+    PyBytes::new(py, &hash[..FINGERPRINT_LENGTH])
+}
+
+/// Returns a keypair deterministically derived from a master secret key and a counter.
+///
+/// The 32-byte seed is HKDF-SHA512 over the 64-byte master secret key (no salt),
+/// with `b"sr25519-counter-keypair"` followed by the counter as 8 little-endian
+/// bytes as the info string. The seed is then expanded exactly as in `pair_from_seed`,
+/// so the same master secret and counter always give the same keypair.
+///
+/// This is *not* HD derivation: there is no chain code, and the resulting public
+/// keys cannot be derived from the master public key.
+///
+/// # Arguments
+///
+/// * `master_secret` - The sr25519 secret key to derive from.
+/// * `counter` - The position of the key in the sequence, as an unsigned 64-bit integer.
+///
+/// # Returns
+///
+/// A tuple containing the 32-byte public key and 64-byte secret key, in that order.
+///
+/// # Raises
+///
+/// * `ValueError` - If the master secret key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(master_secret, counter)")]
+pub fn derive_counter_keypair(master_secret: PrivKey, counter: u64) -> PyResult<Keypair> {
+    SecretKey::from_bytes(&master_secret.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+
+    let info = [COUNTER_KDF_INFO, &counter.to_le_bytes()[..]].concat();
+    let mut seed = [0u8; MINI_SECRET_KEY_LENGTH];
+    Hkdf::<Sha512>::new(None, &master_secret.0)
+        .expand(&info, &mut seed)
+        .expect("32 bytes is a valid HKDF-SHA512 output length; qed");
+
+    pair_from_seed(Seed(seed), "ed25519")
+}
+
+/// aggregate two public points (public_keys or R values.)
+///
+/// # Arguments
+///
+/// * `pubkey1` - The sr25519 public point, as an array of 32 bytes, to use.
+/// * `pubkey2` - The sr25519 public point, as an array of 32 bytes, to use.
+///
+/// # Returns
+///
+/// * `pubkey` - The sr25519 public point, as an array of 32 bytes, to use.
+///
+///
+///
+#[pyfunction]
+#[pyo3(text_signature = "(public1, public2)")]
+pub fn sum_public_points(pubkey1: PubKey, pubkey2: PubKey) -> PyResult<PubKey> {
+
+    let pk1 = match PublicKey::from_bytes(&pubkey1.0) {
+        Ok(some_pk) => some_pk,
+        Err(err) => return Err(_error("INVALID_KEY", format!("Invalid public key: {}", err))),
+    };
+
+    let pk2 = match PublicKey::from_bytes(&pubkey2.0) {
+        Ok(some_pk) => some_pk,
+        Err(err) => return Err(_error("INVALID_KEY", format!("Invalid public key: {}", err))),
+    };
+
+    let res_point = pk1.as_point() + pk2.as_point();
+    let result = res_point.compress();
+
+    Ok(PubKey(result.to_bytes()))
+}
+
+/// Subtracts one public point from another, undoing `sum_public_points`.
+///
+/// # Arguments
+///
+/// * `pubkey1` - The sr25519 public point, as an array of 32 bytes, to subtract from.
+/// * `pubkey2` - The sr25519 public point, as an array of 32 bytes, to subtract.
+///
+/// # Returns
+///
+/// The 32-byte point `pubkey1 - pubkey2`.
+///
+/// # Raises
+///
+/// * `ValueError` - If either point is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(pubkey1, pubkey2)")]
+pub fn subtract_public_points(pubkey1: PubKey, pubkey2: PubKey) -> PyResult<PubKey> {
+    let pk1 = PublicKey::from_bytes(&pubkey1.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+    let pk2 = PublicKey::from_bytes(&pubkey2.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+
+    Ok(PubKey((pk1.as_point() - pk2.as_point()).compress().to_bytes()))
+}
+
+/// Negates a public point, so that `sum_public_points(pubkey, negate_public_point(pubkey))`
+/// is the identity.
+///
+/// # Arguments
+///
+/// * `pubkey` - The sr25519 public point, as an array of 32 bytes, to negate.
+///
+/// # Returns
+///
+/// The 32-byte point `-pubkey`.
+///
+/// # Raises
+///
+/// * `ValueError` - If the point is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(pubkey)")]
+pub fn negate_public_point(pubkey: PubKey) -> PyResult<PubKey> {
+    let pk = PublicKey::from_bytes(&pubkey.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+
+    Ok(PubKey((-pk.as_point()).compress().to_bytes()))
+}
+
+/// Returns the identity point, the neutral element for `sum_public_points`.
+///
+/// # Returns
+///
+/// The 32-byte encoding of the identity, which is all zeros.
+#[pyfunction]
+#[pyo3(text_signature = "()")]
+pub fn identity_public_point() -> PubKey {
+    PubKey(RistrettoPoint::identity().compress().to_bytes())
+}
+
+/// Returns whether two public points are equal, or are negations of each other.
+///
+/// Multi-signature implementations that disagree on a sign convention end up with a point
+/// where the other expects its negation, which this tells apart from an unrelated point.
+///
+/// # Arguments
+///
+/// * `pubkey1` - The first sr25519 public point, as an array of 32 bytes.
+/// * `pubkey2` - The second sr25519 public point, as an array of 32 bytes.
+///
+/// # Returns
+///
+/// True if `pubkey1` is `pubkey2` or `-pubkey2`, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If either point is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(pubkey1, pubkey2)")]
+pub fn points_equal_or_negation(pubkey1: PubKey, pubkey2: PubKey) -> PyResult<bool> {
+    let pk1 = PublicKey::from_bytes(&pubkey1.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+    let pk2 = PublicKey::from_bytes(&pubkey2.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+
+    Ok(pk1.as_point() == pk2.as_point() || *pk1.as_point() == -pk2.as_point())
+}
+
+/// Aggregates a list of public keys, as repeated calls to `sum_public_points` would.
+///
+/// # Arguments
+///
+/// * `pubkeys` - A list of sr25519 public keys, as 32-byte arrays.
+///
+/// # Returns
+///
+/// The 32-byte sum of the public points, which is the key itself for a single key.
+///
+/// # Raises
+///
+/// * `ValueError` - If the list is empty or any public key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(pubkeys)")]
+pub fn aggregate_public_keys(pubkeys: Vec<PubKey>) -> PyResult<PubKey> {
+    Ok(PubKey(_sum_public_keys(&pubkeys)?.compress().to_bytes()))
+}
+
+/// Aggregates the `R` points of the parties to a `multi_sign` signature.
+///
+/// # Arguments
+///
+/// * `rs` - A list of the parties' 32-byte `R` points.
+///
+/// # Returns
+///
+/// The 32-byte sum of the points, to pass to `multi_sign` as `R`.
+///
+/// # Raises
+///
+/// * `ValueError` - If the list is empty or any point is invalid.
+#[pyfunction]
+#[pyo3(name = "aggregate_signature_R", text_signature = "(rs)")]
+pub fn aggregate_signature_r(rs: Vec<PubKey>) -> PyResult<PubKey> {
+    aggregate_public_keys(rs)
+}
+
+/// Verifies that a `multi_sign` signature was made by at least `threshold` of the given keys.
+///
+/// The aggregate is the 64-byte signature followed by a bitmap of `ceil(len(pubkeys) / 8)`
+/// bytes naming the signers: bit `i % 8` of byte `i / 8`, counting from the least significant
+/// bit, is set if `pubkeys[i]` took part. The signers sum their `R` points with
+/// `aggregate_signature_R`, each signs with `multi_sign` under the sum of their public keys,
+/// and their `s` scalars are summed into one signature. Verification rebuilds that sum from
+/// the bitmap and checks the signature against it under the `substrate` context.
+///
+/// Plain key summing is open to rogue key attacks: a party that lists the key
+/// `x * B - pubkeys[0]` can sign alone, with `x`, for itself and `pubkeys[0]` together. So
+/// each key must come with a proof of possession, a `sign` signature by that key over the
+/// 32 bytes of its own public key, which only the holder of the secret key can make. Every
+/// proof is checked with `verify`. The list itself is checked as `validate_participant_set`
+/// does, since a key listed twice would count as two signers.
+///
+/// # Arguments
+///
+/// * `aggregate_signature` - The 64-byte signature followed by the signer bitmap.
+/// * `message` - The binary message that was signed.
+/// * `pubkeys` - The list of all possible signers' sr25519 public keys.
+/// * `threshold` - The least number of signers to accept.
+/// * `possession_proofs` - One 64-byte signature per key, made with `sign` by that key over
+///   its own public key.
+///
+/// # Returns
+///
+/// True if at least `threshold` keys are marked in the bitmap and the signature is valid for
+/// their sum, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If the aggregate has the wrong length, the bitmap marks a key beyond the
+///   end of the list, the threshold is 0 or above the number of keys, the signature or any
+///   public key is invalid, a public key is the identity or appears twice, or there is not
+///   exactly one valid proof of possession per key.
+#[pyfunction]
+#[pyo3(text_signature = "(aggregate_signature, message, pubkeys, threshold, possession_proofs)")]
+pub fn verify_threshold(aggregate_signature: Message, message: Message, pubkeys: Vec<PubKey>, threshold: usize, possession_proofs: Vec<Sig>) -> PyResult<bool> {
+    validate_participant_set(pubkeys.iter().map(|pubkey| PubKey(pubkey.0)).collect())?;
+    if possession_proofs.len() != pubkeys.len() {
+        return Err(_error("INVALID_LENGTH", format!(
+            "Expected one proof of possession per key, got {} for {} keys", possession_proofs.len(), pubkeys.len())));
+    }
+    for (index, (pubkey, proof)) in pubkeys.iter().zip(possession_proofs.iter()).enumerate() {
+        if !verify(Sig(proof.0), Message(pubkey.0.to_vec()), PubKey(pubkey.0))? {
+            return Err(_error("INVALID_KEY", format!("Invalid public key at index {}: proof of possession does not verify", index)));
+        }
+    }
+    if threshold == 0 || threshold > pubkeys.len() {
+        return Err(_error("INVALID_ARGUMENT", format!("Expected a threshold between 1 and {}, got {}", pubkeys.len(), threshold)));
+    }
+    let expected_len = SIGNATURE_LENGTH + pubkeys.len().div_ceil(8);
+    if aggregate_signature.0.len() != expected_len {
+        return Err(_error("INVALID_LENGTH", format!("Expected an aggregate signature of length {}, got {}", expected_len, aggregate_signature.0.len())));
+    }
+
+    let bitmap = &aggregate_signature.0[SIGNATURE_LENGTH..];
+    let signers: Vec<PubKey> = (0..bitmap.len() * 8)
+        .filter(|index| (bitmap[index / 8] >> (index % 8)) & 1 == 1)
+        .map(|index| pubkeys.get(index).map(|pubkey| PubKey(pubkey.0)))
+        .collect::<Option<_>>()
+        .ok_or_else(|| _error("INVALID_ENCODING", "Invalid signer bitmap: marks a key beyond the end of the list"))?;
+    if signers.len() < threshold {
+        return Ok(false);
+    }
+
+    let mut signature = [0u8; SIGNATURE_LENGTH];
+    signature.copy_from_slice(&aggregate_signature.0[..SIGNATURE_LENGTH]);
+    verify(Sig(signature), message, PubKey(_sum_public_keys(&signers)?.compress().to_bytes()))
+}
+
+/// Multiplies a public point by a scalar.
+///
+/// The scalar is read as a little-endian integer and reduced modulo the group order, so
+/// any 32 bytes are accepted, and scalars that differ by a multiple of the order give the
+/// same point.
+///
+/// # Arguments
+///
+/// * `pubkey` - The sr25519 public point, as an array of 32 bytes.
+/// * `scalar` - The 32-byte little-endian scalar.
+///
+/// # Returns
+///
+/// The 32-byte point `scalar * pubkey`.
+///
+/// # Raises
+///
+/// * `ValueError` - If the point is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(pubkey, scalar)")]
+pub fn scalar_mul_public_point(pubkey: PubKey, scalar: ScalarBytes) -> PyResult<PubKey> {
+    let point = _decompress_point(&pubkey)?;
+    Ok(PubKey((Scalar::from_bytes_mod_order(scalar.0) * point).compress().to_bytes()))
+}
+
+/// Returns whether the given bytes are a valid Ristretto point encoding.
+///
+/// Every Ristretto point has exactly one encoding, and decoding rejects any other bytes,
+/// so a valid encoding is always the canonical one.
+///
+/// # Arguments
+///
+/// * `data` - The bytes to check.
+///
+/// # Returns
+///
+/// True if `data` is 32 bytes long and decodes to a Ristretto point, false otherwise.
+#[pyfunction]
+#[pyo3(text_signature = "(data)")]
+pub fn is_canonical_point(data: Message) -> bool {
+    data.0.len() == PUBLIC_KEY_LENGTH && CompressedRistretto::from_slice(&data.0).decompress().is_some()
+}
+
+/// Decodes and re-encodes a Ristretto point, returning its canonical encoding.
+///
+/// As Ristretto encodings are unique, this returns valid input unchanged; its use is in
+/// rejecting invalid input before any arithmetic is done with it.
+///
+/// # Arguments
+///
+/// * `data` - The 32-byte point encoding.
+///
+/// # Returns
+///
+/// The canonical 32-byte encoding of the point.
+///
+/// # Raises
+///
+/// * `ValueError` - If the bytes are not a valid Ristretto point encoding.
+#[pyfunction]
+#[pyo3(text_signature = "(data)")]
+pub fn normalize_point(data: PubKey) -> PyResult<PubKey> {
+    Ok(PubKey(_decompress_point(&data)?.compress().to_bytes()))
+}
+
+/// Proves that two points have the same discrete logarithm with respect to two bases.
+///
+/// With `x` the secret key scalar, the points are `P1 = x * B1` and `P2 = x * B2`, so `P1`
+/// is the public key when `B1` is the Ristretto basepoint. The proof is a Chaum-Pedersen
+/// proof made non-interactive with a merlin transcript labelled `sr25519-dleq`, into which
+/// `B1`, `B2`, `P1` and `P2` and then the commitments `R1 = k * B1` and `R2 = k * B2` are
+/// appended. The proof is the challenge `c` followed by `s = k - c * x`, each as 32 bytes.
+/// The nonce `k` is drawn from the transcript, keyed with the secret key and fresh
+/// randomness.
+///
+/// # Arguments
+///
+/// * `secret_key` - The 64-byte sr25519 secret key holding `x`.
+/// * `base_point` - The first base `B1`, as a 32-byte Ristretto point.
+/// * `other_base` - The second base `B2`, as a 32-byte Ristretto point.
+///
+/// # Returns
+///
+/// A tuple of the 64-byte proof, `P1` and `P2`.
+///
+/// # Raises
+///
+/// * `ValueError` - If the secret key is invalid, or either base is invalid or the identity.
+#[pyfunction]
+#[pyo3(text_signature = "(secret_key, base_point, other_base)")]
+pub fn dleq_prove<'p>(py: Python<'p>, secret_key: PrivKey, base_point: PubKey, other_base: PubKey) -> PyResult<(&'p PyBytes, PubKey, PubKey)> {
+    let secret = SecretKey::from_bytes(&secret_key.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+    let b1 = _dleq_base(&base_point)?;
+    let b2 = _dleq_base(&other_base)?;
+    let x = _secret_scalar(&secret);
+    let (p1, p2) = (x * b1, x * b2);
+
+    let mut t = _dleq_transcript(&b1, &b2, &p1, &p2);
+    let mut rng = t.build_rng()
+        .rekey_with_witness_bytes(b"secret", &secret.to_bytes())
+        .finalize(&mut OsRng);
+    let k = Scalar::random(&mut rng);
+    let c = _dleq_challenge(&mut t, &(k * b1), &(k * b2));
+    let s = k - c * x;
+
+    let proof = [c.to_bytes(), s.to_bytes()].concat();
+    Ok((PyBytes::new(py, &proof), PubKey(p1.compress().to_bytes()), PubKey(p2.compress().to_bytes())))
+}
+
+/// Verifies a proof from `dleq_prove` that `point1 = x * base1` and `point2 = x * base2`
+/// for the same `x`.
+///
+/// # Arguments
+///
+/// * `proof` - The 64-byte proof.
+/// * `point1` - The point `P1`.
+/// * `base1` - The base `B1`.
+/// * `point2` - The point `P2`.
+/// * `base2` - The base `B2`.
+///
+/// # Returns
+///
+/// True if the proof is valid, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If the proof is not 64 bytes, any point is invalid, or either base is the identity.
+#[pyfunction]
+#[pyo3(text_signature = "(proof, point1, base1, point2, base2)")]
+pub fn dleq_verify(proof: Message, point1: PubKey, base1: PubKey, point2: PubKey, base2: PubKey) -> PyResult<bool> {
+    let proof = _check_bytes_len(proof.0, 2 * SCALAR_LENGTH)?;
+    let b1 = _dleq_base(&base1)?;
+    let b2 = _dleq_base(&base2)?;
+    let p1 = _decompress_point(&point1)?;
+    let p2 = _decompress_point(&point2)?;
+
+    let mut c_bytes = [0u8; SCALAR_LENGTH];
+    let mut s_bytes = [0u8; SCALAR_LENGTH];
+    c_bytes.copy_from_slice(&proof[..SCALAR_LENGTH]);
+    s_bytes.copy_from_slice(&proof[SCALAR_LENGTH..]);
+    let (c, s) = match (Scalar::from_canonical_bytes(c_bytes), Scalar::from_canonical_bytes(s_bytes)) {
+        (Some(c), Some(s)) => (c, s),
+        _ => return Ok(false),
+    };
+
+    let mut t = _dleq_transcript(&b1, &b2, &p1, &p2);
+    let expected = _dleq_challenge(&mut t, &(s * b1 + c * p1), &(s * b2 + c * p2));
+    Ok(expected == c)
+}
+
+fn _decompress_point(point: &PubKey) -> PyResult<RistrettoPoint> {
+    CompressedRistretto(point.0).decompress()
+        .ok_or_else(|| _error("INVALID_POINT", "Invalid point: not a valid Ristretto encoding"))
+}
+
+fn _dleq_base(base: &PubKey) -> PyResult<RistrettoPoint> {
+    let point = _decompress_point(base)?;
+    if point.is_identity() {
+        return Err(_error("INVALID_POINT", "Invalid base point: identity point"));
+    }
+    Ok(point)
+}
+
+fn _dleq_transcript(b1: &RistrettoPoint, b2: &RistrettoPoint, p1: &RistrettoPoint, p2: &RistrettoPoint) -> Transcript {
+    let mut t = Transcript::new(b"sr25519-dleq");
+    t.commit_point(b"B1", &b1.compress());
+    t.commit_point(b"B2", &b2.compress());
+    t.commit_point(b"P1", &p1.compress());
+    t.commit_point(b"P2", &p2.compress());
+    t
+}
+
+fn _dleq_challenge(t: &mut Transcript, r1: &RistrettoPoint, r2: &RistrettoPoint) -> Scalar {
+    t.commit_point(b"R1", &r1.compress());
+    t.commit_point(b"R2", &r2.compress());
+    t.challenge_scalar(b"c")
+}
+
+/// Encrypts a message to an sr25519 public key.
+///
+/// The scheme is ECIES over Ristretto255 with XChaCha20-Poly1305:
+///
+/// 1. A fresh ephemeral keypair `(e, E)` is generated.
+/// 2. The shared point `S = e * P` is computed, with `P` the recipient public key.
+/// 3. 56 bytes are derived with HKDF-SHA512, using `S || E || P` (compressed) as input key
+///    material, no salt, and `b"sr25519-ecies-xchacha20poly1305"` as info. The first 32 bytes
+///    are the key and the last 24 bytes the nonce.
+/// 4. The plaintext is sealed with XChaCha20-Poly1305, with `E || P` as associated data.
+///
+/// The key and nonce are never reused since every encryption uses a new ephemeral key.
+///
+/// # Arguments
+///
+/// * `recipient_public` - The sr25519 public key of the recipient.
+/// * `plaintext` - The binary message to encrypt.
+///
+/// # Returns
+///
+/// A tuple of the 32-byte ephemeral public key and the ciphertext (with its 16-byte tag).
+///
+/// # Raises
+///
+/// * `ValueError` - If the recipient public key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(recipient_public, plaintext)")]
+pub fn encrypt_to_public<'p>(py: Python<'p>, recipient_public: PubKey, plaintext: Message) -> PyResult<(PubKey, &'p PyBytes)> {
+    let recipient = PublicKey::from_bytes(&recipient_public.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+    let ephemeral = MiniSecretKey::generate().expand_to_keypair(ExpansionMode::Ed25519);
+
+    let shared = _secret_scalar(&ephemeral.secret) * recipient.as_point();
+    let cipher = _ecies_cipher(&shared, &ephemeral.public, &recipient);
+    let ciphertext = cipher.0.encrypt(&cipher.1, Payload { msg: &plaintext.0, aad: &cipher.2 })
+        .map_err(|_| _error("INVALID_ARGUMENT", "Encryption failed: plaintext too long"))?;
+
+    Ok((PubKey(ephemeral.public.to_bytes()), PyBytes::new(py, &ciphertext)))
+}
+
+/// Decrypts a message produced by `encrypt_to_public` with the recipient's secret key.
+///
+/// # Arguments
+///
+/// * `recipient_secret` - The sr25519 secret key of the recipient.
+/// * `ephemeral_public` - The 32-byte ephemeral public key returned by `encrypt_to_public`.
+/// * `ciphertext` - The ciphertext returned by `encrypt_to_public`.
+///
+/// # Returns
+///
+/// The decrypted plaintext.
+///
+/// # Raises
+///
+/// * `ValueError` - If a key is invalid, or the ciphertext fails authentication, with the
+///   code `DECRYPTION_FAILED`.
+#[pyfunction]
+#[pyo3(text_signature = "(recipient_secret, ephemeral_public, ciphertext)")]
+pub fn decrypt_with_secret<'p>(py: Python<'p>, recipient_secret: PrivKey, ephemeral_public: PubKey, ciphertext: Message) -> PyResult<&'p PyBytes> {
+    let secret = SecretKey::from_bytes(&recipient_secret.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+    let ephemeral = PublicKey::from_bytes(&ephemeral_public.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+
+    let shared = _secret_scalar(&secret) * ephemeral.as_point();
+    let cipher = _ecies_cipher(&shared, &ephemeral, &secret.to_public());
+    let plaintext = cipher.0.decrypt(&cipher.1, Payload { msg: &ciphertext.0, aad: &cipher.2 })
+        .map_err(|_| _error("DECRYPTION_FAILED", "Decryption failed: ciphertext could not be authenticated"))?;
+
+    Ok(PyBytes::new(py, &plaintext))
+}
+
+/// Encrypts a keypair into a polkadot-js JSON keystore.
+///
+/// The keystore uses version 3 of the polkadot-js format: the keypair is PKCS8 encoded,
+/// with the secret key in its Ed25519 form, and sealed with XSalsa20-Poly1305 under a key
+/// derived from the password with scrypt (`N = 32768`, `p = 1`, `r = 8`). The `encoded`
+/// field is the base64 of the 32-byte salt, the scrypt `N`, `p` and `r` as little-endian
+/// u32s, the 24-byte nonce, and the ciphertext. The salt and nonce are random.
+///
+/// # Arguments
+///
+/// * `keypair` - The sr25519 keypair to encrypt, as a tuple of (public_bytes, private_bytes)
+/// * `password` - The password to encrypt with.
+///
+/// # Returns
+///
+/// The keystore JSON, with the address in the generic substrate SS58 format.
+///
+/// # Raises
+///
+/// * `ValueError` - If either the public or private key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(keypair, password)")]
+pub fn encrypt_keystore(keypair: Keypair, password: &str) -> PyResult<String> {
+    let kp = _to_schnorrkel_keypair(&keypair)?;
+    let mut salt = [0u8; KEYSTORE_SALT_LENGTH];
+    let mut nonce = [0u8; KEYSTORE_NONCE_LENGTH];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let (n, p, r) = KEYSTORE_SCRYPT_PARAMS;
+    let mut key = _keystore_key(password, &salt, n, p, r)?;
+    let mut plaintext = [PKCS8_HEADER, &kp.secret.to_ed25519_bytes()[..], PKCS8_DIVIDER, &keypair.0[..]].concat();
+    let ciphertext = XSalsa20Poly1305::new(&key.into()).encrypt(&nonce.into(), &plaintext[..])
+        .map_err(|_| _error("KEYSTORE_ERROR", "Encryption failed"))?;
+    key.zeroize();
+    plaintext.zeroize();
+
+    let encoded = [&salt[..], &n.to_le_bytes(), &p.to_le_bytes(), &r.to_le_bytes(), &nonce[..], &ciphertext[..]].concat();
+    let document = json!({
+        "encoded": base64::encode(&encoded),
+        "encoding": {
+            "content": ["pkcs8", "sr25519"],
+            "type": ["scrypt", "xsalsa20-poly1305"],
+            "version": "3",
+        },
+        "address": ss58_encode(PubKey(keypair.0), 42)?,
+        "meta": {},
+    });
+    Ok(document.to_string())
+}
+
+/// Decrypts a polkadot-js JSON keystore, as written by `encrypt_keystore`.
+///
+/// Only version 3 keystores of sr25519 keys, encrypted with scrypt and XSalsa20-Poly1305,
+/// are supported, and only with the scrypt parameters polkadot-js writes (N=32768, p=1, r=8).
+///
+/// # Arguments
+///
+/// * `json_str` - The keystore JSON.
+/// * `password` - The password the keystore was encrypted with.
+///
+/// # Returns
+///
+/// A tuple containing the 32-byte public key and 64-byte secret key, in that order.
+///
+/// # Raises
+///
+/// * `ValueError` - If the keystore is malformed or unsupported, the password is wrong, or
+///   the ciphertext is corrupted.
+#[pyfunction]
+#[pyo3(text_signature = "(json_str, password)")]
+pub fn decrypt_keystore(json_str: &str, password: &str) -> PyResult<Keypair> {
+    let invalid = |reason: &str| _error("KEYSTORE_ERROR", format!("Invalid keystore: {}", reason));
+    let document: serde_json::Value = serde_json::from_str(json_str)
+        .map_err(|err| invalid(&err.to_string()))?;
+    let encoding = &document["encoding"];
+    if encoding["version"] != "3"
+        || encoding["type"] != json!(["scrypt", "xsalsa20-poly1305"])
+        || !encoding["content"].as_array().is_some_and(|content| content.contains(&json!("sr25519"))) {
+        return Err(invalid("only version 3 sr25519 keystores encrypted with scrypt and xsalsa20-poly1305 are supported"));
+    }
+    let encoded = document["encoded"].as_str()
+        .ok_or_else(|| invalid("missing encoded field"))
+        .and_then(|encoded| base64::decode(encoded).map_err(|err| invalid(&err.to_string())))?;
+
+    let params_end = KEYSTORE_SALT_LENGTH + 12;
+    let nonce_end = params_end + KEYSTORE_NONCE_LENGTH;
+    if encoded.len() < nonce_end {
+        return Err(invalid("encoded data too short"));
+    }
+    let param = |index: usize| {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&encoded[KEYSTORE_SALT_LENGTH + 4 * index..KEYSTORE_SALT_LENGTH + 4 * index + 4]);
+        u32::from_le_bytes(bytes)
+    };
+    let mut key = _keystore_key(password, &encoded[..KEYSTORE_SALT_LENGTH], param(0), param(1), param(2))?;
+    let plaintext = XSalsa20Poly1305::new(&key.into())
+        .decrypt(XSalsaNonce::from_slice(&encoded[params_end..nonce_end]), &encoded[nonce_end..]);
+    key.zeroize();
+    let mut plaintext = plaintext
+        .map_err(|_| _error("KEYSTORE_ERROR", "Decryption failed: wrong password or corrupted keystore"))?;
+
+    let secret_start = PKCS8_HEADER.len();
+    let public_start = secret_start + SECRET_KEY_LENGTH + PKCS8_DIVIDER.len();
+    let keypair = if plaintext.len() != public_start + PUBLIC_KEY_LENGTH
+        || &plaintext[..secret_start] != PKCS8_HEADER
+        || &plaintext[public_start - PKCS8_DIVIDER.len()..public_start] != PKCS8_DIVIDER {
+        Err(invalid("malformed PKCS8 contents"))
+    } else {
+        SecretKey::from_ed25519_bytes(&plaintext[secret_start..secret_start + SECRET_KEY_LENGTH])
+            .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))
+            .and_then(|secret| {
+                let public = secret.to_public();
+                if public.to_bytes()[..] != plaintext[public_start..] {
+                    return Err(invalid("public key does not match the secret key"));
+                }
+                Ok(Keypair(public.to_bytes(), secret.to_bytes()))
+            })
+    };
+    plaintext.zeroize();
+    keypair
+}
+
+fn _keystore_key(password: &str, salt: &[u8], n: u32, p: u32, r: u32) -> PyResult<[u8; 32]> {
+    // The parameters come from the keystore itself, and large ones would make scrypt exhaust
+    // memory or run for minutes, so only polkadot-js's own are accepted, as it does
+    let invalid = || _error("KEYSTORE_ERROR", format!("Invalid keystore: unsupported scrypt parameters N={}, p={}, r={}", n, p, r));
+    if (n, p, r) != KEYSTORE_SCRYPT_PARAMS {
+        return Err(invalid());
+    }
+    let params = ScryptParams::new(n.trailing_zeros() as u8, r, p).map_err(|_| invalid())?;
+    let mut key = [0u8; 32];
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut key).map_err(|_| invalid())?;
+    Ok(key)
+}
+
+fn _secret_scalar(secret: &SecretKey) -> Scalar {
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&secret.to_bytes()[..32]);
+    Scalar::from_canonical_bytes(key).expect("secret keys hold a canonical scalar; qed")
+}
+
+fn _ecies_cipher(shared: &RistrettoPoint, ephemeral: &PublicKey, recipient: &PublicKey) -> (XChaCha20Poly1305, XNonce, Vec<u8>) {
+    let aad = [&ephemeral.to_bytes()[..], &recipient.to_bytes()[..]].concat();
+    let ikm = [&shared.compress().to_bytes()[..], &aad[..]].concat();
+    let mut okm = [0u8; 56];
+    Hkdf::<Sha512>::new(None, &ikm)
+        .expand(ECIES_INFO, &mut okm)
+        .expect("56 bytes is a valid HKDF-SHA512 output length; qed");
+
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&okm[..32]));
+    (cipher, *XNonce::from_slice(&okm[32..]), aad)
+}
+
+/// Validates the participant set of a multi-signature round and returns its aggregate key.
+///
+/// Every key must be a valid public key other than the identity, and no key may appear
+/// twice. The aggregate is the plain sum of the public points, as with `sum_public_points`.
+///
+/// # Arguments
+///
+/// * `pubkeys` - A list of sr25519 public keys, as 32-byte arrays.
+///
+/// # Returns
+///
+/// The 32-byte aggregate public key.
+///
+/// # Raises
+///
+/// * `ValueError` - If the list is empty, naming the index of the first invalid or identity
+///   key, or the pair of indices of the first duplicate key.
+#[pyfunction]
+#[pyo3(text_signature = "(pubkeys)")]
+pub fn validate_participant_set(pubkeys: Vec<PubKey>) -> PyResult<PubKey> {
+    for (index, pubkey) in pubkeys.iter().enumerate() {
+        let pk = PublicKey::from_bytes(&pubkey.0)
+            .map_err(|err| _error("INVALID_KEY", format!("Invalid public key at index {}: {}", index, err)))?;
+        if pk.as_point().is_identity() {
+            return Err(_error("INVALID_KEY", format!("Invalid public key at index {}: identity point", index)));
+        }
+        if let Some(first) = pubkeys[..index].iter().position(|other| other.0 == pubkey.0) {
+            return Err(_error("INVALID_KEY", format!("Duplicate public key at indices {} and {}", first, index)));
+        }
+    }
+    Ok(PubKey(_sum_public_keys(&pubkeys)?.compress().to_bytes()))
+}
+
+/// Returns the aggregate public key of a group of keypairs generated from seeds.
+///
+/// Each seed is expanded as in `pair_from_seed`, and the aggregate is the plain sum of the
+/// public points, as with `sum_public_points`. This is meant for tests that need a fixed,
+/// reproducible signing group.
+///
+/// # Arguments
+///
+/// * `seeds` - A list of 32-byte seeds, one per group member.
+///
+/// # Returns
+///
+/// The 32-byte aggregate public key.
+///
+/// # Raises
+///
+/// * `ValueError` - If the list is empty or any seed is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(seeds)")]
+pub fn group_public_from_seeds(seeds: Vec<Seed>) -> PyResult<PubKey> {
+    let pubkeys = seeds.into_iter()
+        .map(|seed| pair_from_seed(seed, "ed25519").map(|keypair| PubKey(keypair.0)))
+        .collect::<PyResult<Vec<PubKey>>>()?;
+    Ok(PubKey(_sum_public_keys(&pubkeys)?.compress().to_bytes()))
+}
+
+/// Aggregates a set of public keys and returns a hash binding the aggregate to that set.
+///
+/// This gives no security against rogue key attacks. Despite its name, the "proof" is only
+/// a hash of public data: anyone who knows the keys can compute it, and checking it shows
+/// no more than that the aggregate is the sum of exactly these keys. It is not a proof of
+/// possession, so a key chosen as `x * B - X_1` to cancel another key passes like any other.
+///
+/// The aggregate is the plain sum of the public points, as with `sum_public_points`. The
+/// proof is a 32-byte challenge from a merlin transcript labelled `sr25519-aggregation-proof`,
+/// into which the number of keys (`n`, as a u64), each key in ascending byte order (`pk`),
+/// and the compressed aggregate (`agg`) are appended. Because the keys are sorted first,
+/// the proof does not depend on the order they were given in. It lets a verifier check
+/// which keys an aggregate claims to be made of, and detect any key being added or removed.
+///
+/// # Arguments
+///
+/// * `pubkeys` - A list of sr25519 public keys, as 32-byte arrays.
+///
+/// # Returns
+///
+/// A tuple of the 32-byte aggregate public key and the 32-byte proof, which is a hash and
+/// not a point.
+///
+/// # Raises
+///
+/// * `ValueError` - If the list is empty or any public key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(pubkeys)")]
+pub fn aggregation_proof<'p>(py: Python<'p>, pubkeys: Vec<PubKey>) -> PyResult<(PubKey, &'p PyBytes)> {
+    let aggregate = _sum_public_keys(&pubkeys)?.compress();
+    let proof = _aggregation_proof(&aggregate, &pubkeys);
+    Ok((PubKey(aggregate.to_bytes()), PyBytes::new(py, &proof)))
+}
+
+/// Verifies a proof from `aggregation_proof` for an aggregate and a set of public keys.
+///
+/// As with `aggregation_proof`, this gives no security against rogue keys: it only checks
+/// that the aggregate is the sum of exactly these keys.
+///
+/// # Arguments
+///
+/// * `aggregate` - The 32-byte aggregate public key.
+/// * `pubkeys` - The list of sr25519 public keys the aggregate claims to be made of.
+/// * `proof` - The 32-byte proof.
+///
+/// # Returns
+///
+/// True if the aggregate is the sum of exactly these keys and the proof matches, false otherwise.
+///
+/// # Raises
+///
+/// * `ValueError` - If the list is empty or any public key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(aggregate, pubkeys, proof)")]
+pub fn verify_aggregation_proof(aggregate: PubKey, pubkeys: Vec<PubKey>, proof: Message) -> PyResult<bool> {
+    let expected = _sum_public_keys(&pubkeys)?.compress();
+    if expected.to_bytes() != aggregate.0 {
+        return Ok(false);
+    }
+    let expected_proof = _aggregation_proof(&expected, &pubkeys);
+    Ok(bool::from(expected_proof[..].ct_eq(&proof.0[..])))
+}
+
+fn _aggregation_proof(aggregate: &CompressedRistretto, pubkeys: &[PubKey]) -> [u8; 32] {
+    let mut sorted: Vec<&[u8; PUBLIC_KEY_LENGTH]> = pubkeys.iter().map(|pk| &pk.0).collect();
+    sorted.sort();
+
+    let mut t = Transcript::new(b"sr25519-aggregation-proof");
+    t.append_u64(b"n", sorted.len() as u64);
+    for pk in sorted {
+        t.append_message(b"pk", pk);
+    }
+    t.append_message(b"agg", aggregate.as_bytes());
+    let mut proof = [0u8; 32];
+    t.challenge_bytes(b"proof", &mut proof);
+    proof
+}
+
+/// Aggregates the signers' public keys into a MuSig2 aggregate key.
+///
+/// The aggregate key is `X = sum(a_i * X_i)` over the public keys in the given order. The
+/// key coefficient `a_i` is the `coef` challenge scalar of a merlin transcript labelled
+/// `sr25519-musig2-keyagg`, into which every public key (`pk`) and then `X_i` itself
+/// (`signer`) are appended.
+///
+/// This MuSig2 is specific to schnorrkel and this module: every coefficient here and in
+/// the other `musig2_` functions comes from a merlin transcript rather than the tagged
+/// hashes of BIP-327, so it does not interoperate with standard MuSig2 implementations.
+///
+/// # Arguments
+///
+/// * `pubkeys` - The list of all signers' public keys, in the order agreed by the signers.
+///
+/// # Returns
+///
+/// A tuple of the 32-byte aggregate public key, which the final signature verifies under,
+/// and the list of each signer's 32-byte key coefficient, in the order of `pubkeys`.
+///
+/// # Raises
+///
+/// * `ValueError` - If the list is empty or any public key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(pubkeys)")]
+pub fn musig2_key_agg(pubkeys: Vec<PubKey>) -> PyResult<(PubKey, Vec<ScalarBytes>)> {
+    let (aggregate, coefficients) = _musig2_key_aggregation(&pubkeys)?;
+    Ok((
+        PubKey(aggregate.compress().to_bytes()),
+        coefficients.iter().map(|coefficient| ScalarBytes(coefficient.to_bytes())).collect(),
+    ))
+}
+
+/// Generates a signer's pair of MuSig2 nonces for signing a message with a set of keys.
+///
+/// MuSig2 has each signer contribute two nonces `r1, r2` with points `R1 = r1 * B` and
+/// `R2 = r2 * B`. Here each nonce is a witness scalar from a merlin transcript labelled
+/// `sr25519-musig2-nonce`, into which the aggregate key (`X`, as in `musig2_key_agg`) and
+/// the message (`m`) are appended, mixed with the secret key's nonce seed and fresh
+/// randomness from the OS. Nonces are therefore never repeated, and each pair must be used
+/// for one signing session only: reusing secret nonces across two signatures leaks the
+/// secret key.
+///
+/// # Arguments
+///
+/// * `secret` - The signer's 64-byte sr25519 secret key.
+/// * `message` - The binary message to be signed.
+/// * `pubkeys` - The list of all signers' public keys, in the order agreed by the signers.
+///
+/// # Returns
+///
+/// A tuple of the nonce points `R1` and `R2` to share with the other signers, followed by
+/// the secret nonces `r1` and `r2` as 32-byte scalars to keep for signing.
+///
+/// # Raises
+///
+/// * `ValueError` - If the secret key or any public key is invalid, or the list is empty.
+#[pyfunction]
+#[pyo3(text_signature = "(secret, message, pubkeys)")]
+pub fn musig2_nonce_gen(secret: PrivKey, message: Message, pubkeys: Vec<PubKey>) -> PyResult<(PubKey, PubKey, ScalarBytes, ScalarBytes)> {
+    let secret = SecretKey::from_bytes(&secret.0)
+        .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+    let (aggregate, _) = _musig2_key_aggregation(&pubkeys)?;
+
+    let mut t = Transcript::new(b"sr25519-musig2-nonce");
+    t.commit_point(b"X", &aggregate.compress());
+    t.append_message(b"m", &message.0);
+    let nonce_seed = &secret.to_bytes()[32..];
+    let r1 = t.witness_scalar(b"r1", &[nonce_seed]);
+    let r2 = t.witness_scalar(b"r2", &[nonce_seed]);
+
+    Ok((
+        PubKey((&r1 * &RISTRETTO_BASEPOINT_TABLE).compress().to_bytes()),
+        PubKey((&r2 * &RISTRETTO_BASEPOINT_TABLE).compress().to_bytes()),
+        ScalarBytes(r1.to_bytes()),
+        ScalarBytes(r2.to_bytes()),
+    ))
+}
+
+/// Aggregates the signers' MuSig2 nonce points into the session nonce.
+///
+/// The points are summed into `R1 = sum(R1_i)` and `R2 = sum(R2_i)`, which are combined
+/// into `R = R1 + b * R2`. The nonce coefficient `b` is the `b` challenge scalar of a merlin
+/// transcript labelled `sr25519-musig2-noncecoef`, into which the aggregate key (`X`, as in
+/// `musig2_key_agg`), `R1`, `R2` and the message (`m`) are appended.
+///
+/// Each signer then makes a partial signature with `musig2_partial_sign`, and
+/// `musig2_partial_sig_agg` combines them into the final signature.
+///
+/// # Arguments
+///
+/// * `nonce_lists` - The list of every signer's `(R1, R2)` nonce points.
+/// * `pubkeys` - The list of all signers' public keys, in the order agreed by the signers.
+/// * `message` - The binary message to be signed.
+///
+/// # Returns
+///
+/// A tuple of the 32-byte session nonce point `R` and the 32-byte nonce coefficient `b`.
+///
+/// # Raises
+///
+/// * `ValueError` - If either list is empty or any point or public key is invalid.
+#[pyfunction]
+#[pyo3(text_signature = "(nonce_lists, pubkeys, message)")]
+pub fn musig2_nonce_agg(nonce_lists: Vec<(PubKey, PubKey)>, pubkeys: Vec<PubKey>, message: Message) -> PyResult<(PubKey, ScalarBytes)> {
+    let (aggregate, _) = _musig2_key_aggregation(&pubkeys)?;
+    let (r, b) = _musig2_session_nonce(&aggregate, &nonce_lists, &message.0)?;
+    Ok((PubKey(r.compress().to_bytes()), ScalarBytes(b.to_bytes())))
+}
+
+/// Makes a signer's MuSig2 partial signature.
+///
+/// The partial signature is `s_i = r1_i + b * r2_i + c * a_i * x_i`, where `R` and `b` are
+/// the session nonce and nonce coefficient of `musig2_nonce_agg`, `a_i` is the signer's key
+/// coefficient from `musig2_key_agg`, `x_i` is the signer's secret scalar, and `c` is the
+/// challenge of a standard signature by the aggregate key with nonce `R` under the
+/// `substrate` context, as `verify` checks it.
+///
+/// # Arguments
+///
+/// * `keypair` - The signer's sr25519 keypair, as a tuple of (public_bytes, private_bytes)
+/// * `secret_nonces` - The signer's secret nonces `(r1, r2)` from `musig2_nonce_gen`, which
+///   must not be used for any other session.
+/// * `nonce_lists` - The list of every signer's `(R1, R2)` nonce points.
+/// * `pubkeys` - The list of all signers' public keys, in the order agreed by the signers.
+/// * `message` - The binary message to be signed.
+///
+/// # Returns
+///
+/// The 32-byte partial signature `s_i`.
+///
+/// # Raises
+///
+/// * `ValueError` - If any key, point or nonce is invalid, the keypair's public key is not
+///   in `pubkeys`, or the secret nonces don't match any of the nonce points.
+#[pyfunction]
+#[pyo3(text_signature = "(keypair, secret_nonces, nonce_lists, pubkeys, message)")]
+pub fn musig2_partial_sign(keypair: Keypair, secret_nonces: (ScalarBytes, ScalarBytes), nonce_lists: Vec<(PubKey, PubKey)>, pubkeys: Vec<PubKey>, message: Message) -> PyResult<ScalarBytes> {
+    let keypair = _to_schnorrkel_keypair(&keypair)?;
+    let (aggregate, coefficients) = _musig2_key_aggregation(&pubkeys)?;
+    let index = pubkeys.iter().position(|pubkey| pubkey.0 == keypair.public.to_bytes())
+        .ok_or_else(|| _error("MUSIG_ERROR", "The keypair's public key is not one of the signers"))?;
+
+    let nonce = |bytes: &ScalarBytes| Scalar::from_canonical_bytes(bytes.0)
+        .ok_or_else(|| _error("MUSIG_ERROR", "Invalid secret nonce: not canonical"));
+    let (r1, r2) = (nonce(&secret_nonces.0)?, nonce(&secret_nonces.1)?);
+    let points = (
+        (&r1 * &RISTRETTO_BASEPOINT_TABLE).compress().to_bytes(),
+        (&r2 * &RISTRETTO_BASEPOINT_TABLE).compress().to_bytes(),
+    );
+    if !nonce_lists.iter().any(|nonces| (nonces.0.0, nonces.1.0) == points) {
+        return Err(_error("MUSIG_ERROR", "The secret nonces don't match any of the nonce points"));
+    }
+
+    let (r, b) = _musig2_session_nonce(&aggregate, &nonce_lists, &message.0)?;
+    let c = _signature_challenge(&message.0, &PublicKey::from_point(aggregate), &r.compress());
+    let s = r1 + b * r2 + c * coefficients[index] * _secret_scalar(&keypair.secret);
+    Ok(ScalarBytes(s.to_bytes()))
+}
+
+/// Combines the signers' MuSig2 partial signatures into a signature.
+///
+/// The signature is the session nonce `R` from `musig2_nonce_agg` together with the sum of
+/// the partial signatures. It is a standard 64-byte sr25519 signature, which `verify`
+/// checks against the aggregate key from `musig2_key_agg`.
+///
+/// # Arguments
+///
+/// * `session_nonce` - The 32-byte session nonce point `R` from `musig2_nonce_agg`.
+/// * `partial_sigs` - The list of every signer's 32-byte partial signature.
+///
+/// # Returns
+///
+/// A 64-byte signature.
+///
+/// # Raises
+///
+/// * `ValueError` - If the list is empty, the nonce is not a valid point, or any partial
+///   signature is not a canonical scalar.
+#[pyfunction]
+#[pyo3(text_signature = "(session_nonce, partial_sigs)")]
+pub fn musig2_partial_sig_agg(session_nonce: PubKey, partial_sigs: Vec<ScalarBytes>) -> PyResult<Sig> {
+    if partial_sigs.is_empty() {
+        return Err(_error("INVALID_ARGUMENT", "Expected at least one partial signature"));
+    }
+    CompressedRistretto(session_nonce.0).decompress()
+        .ok_or_else(|| _error("INVALID_POINT", "Invalid session nonce: not a Ristretto point"))?;
+    let mut s = Scalar::zero();
+    for (index, partial) in partial_sigs.iter().enumerate() {
+        s += Scalar::from_canonical_bytes(partial.0)
+            .ok_or_else(|| _error("MUSIG_ERROR", format!("Invalid partial signature at index {}: not canonical", index)))?;
+    }
+
+    let mut bytes = [0u8; SIGNATURE_LENGTH];
+    bytes[..32].copy_from_slice(&session_nonce.0);
+    bytes[32..].copy_from_slice(s.as_bytes());
+    bytes[63] |= 128;
+    Ok(Sig(bytes))
+}
+
+// MuSig2 key aggregation: the aggregate key and each key's coefficient, in order
+fn _musig2_key_aggregation(pubkeys: &[PubKey]) -> PyResult<(RistrettoPoint, Vec<Scalar>)> {
+    let points = _to_public_keys(pubkeys)?;
+    if points.is_empty() {
+        return Err(_error("INVALID_ARGUMENT", "Expected at least one public key"));
+    }
+
+    let mut t = Transcript::new(b"sr25519-musig2-keyagg");
+    for pk in &points {
+        t.commit_point(b"pk", pk.as_compressed());
+    }
+    let mut aggregate = RistrettoPoint::identity();
+    let mut coefficients = Vec::with_capacity(points.len());
+    for pk in &points {
+        let mut signer_t = t.clone();
+        signer_t.commit_point(b"signer", pk.as_compressed());
+        let coefficient = signer_t.challenge_scalar(b"coef");
+        aggregate += coefficient * pk.as_point();
+        coefficients.push(coefficient);
+    }
+    Ok((aggregate, coefficients))
+}
+
+// MuSig2 session nonce: the summed nonce points combined into `R = R1 + b * R2`, and `b`
+fn _musig2_session_nonce(aggregate: &RistrettoPoint, nonce_lists: &[(PubKey, PubKey)], message: &[u8]) -> PyResult<(RistrettoPoint, Scalar)> {
+    let first: Vec<PubKey> = nonce_lists.iter().map(|nonces| PubKey(nonces.0.0)).collect();
+    let second: Vec<PubKey> = nonce_lists.iter().map(|nonces| PubKey(nonces.1.0)).collect();
+    let r1 = _sum_public_keys(&first)?;
+    let r2 = _sum_public_keys(&second)?;
+
+    let b = _musig2_nonce_coefficient(aggregate, &r1, &r2, message);
+    Ok((r1 + b * r2, b))
+}
+
+fn _musig2_nonce_coefficient(aggregate: &RistrettoPoint, r1: &RistrettoPoint, r2: &RistrettoPoint, message: &[u8]) -> Scalar {
+    let mut t = Transcript::new(b"sr25519-musig2-noncecoef");
+    t.commit_point(b"X", &aggregate.compress());
+    t.commit_point(b"R1", &r1.compress());
+    t.commit_point(b"R2", &r2.compress());
+    t.append_message(b"m", message);
+    t.challenge_scalar(b"b")
+}
+
+/// Returns the Edwards encodings of the 8 small-order points of the curve.
+///
+/// These are the compressed Edwards encodings of the 8-torsion subgroup of Curve25519,
+/// for downstream test suites of Edwards-based code exercising small-order key rejection.
+/// They must never be used as real keys: any signature "verifying" under one says nothing
+/// about who made it.
+///
+/// They are Edwards-only. sr25519 keys are Ristretto encodings, and Ristretto has prime
+/// order, so its only small-order element is the identity. Of these 8 encodings, only the
+/// all-zero one is also a valid Ristretto encoding, of the identity; the other 7 are not
+/// valid sr25519 public keys at all.
+///
+/// # Returns
+///
+/// A list of eight 32-byte compressed Edwards point encodings.
+#[pyfunction]
+#[pyo3(text_signature = "()")]
+pub fn small_order_points() -> Vec<PubKey> {
+    EIGHT_TORSION.iter()
+        .map(|point| PubKey(point.compress().to_bytes()))
+        .collect()
+}
+
+/// Checks that a public key is not a small-order point.
+///
+/// sr25519 keys are Ristretto points, and Ristretto has prime order, so the only
+/// small-order key is the identity, whose one canonical encoding is all zeros. That is the
+/// key rejected here. The Edwards encodings of `small_order_points` other than the all-zero
+/// one are not valid Ristretto encodings, so they are not checked for: any function taking
+/// a public key already rejects them as invalid.
+///
+/// # Arguments
+///
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes, to check.
+///
+/// # Raises
+///
+/// * `ValueError` - If the public key is a small-order point.
+#[pyfunction]
+#[pyo3(text_signature = "(pubkey)")]
+pub fn reject_small_order_public_key(pubkey: PubKey) -> PyResult<()> {
+    if _is_small_order(&pubkey.0) {
+        return Err(_error("INVALID_KEY", "Invalid public key: small order point"));
+    }
+    Ok(())
+}
+
+// The identity is the only small-order Ristretto point, and has no other canonical encoding
+fn _is_small_order(bytes: &[u8; PUBLIC_KEY_LENGTH]) -> bool {
+    bytes == &[0u8; PUBLIC_KEY_LENGTH]
+}
+
+/// Converts a public key from its Ristretto encoding to a compressed Edwards point.
+///
+/// A Ristretto point stands for a class of four Edwards points that differ by a point of
+/// order 4: Ristretto hides the curve's cofactor by treating them as the same point. Only
+/// one of the four lies in the prime-order subgroup, and that is the point returned, so
+/// a public key `x * B` becomes `x * B` for the Ed25519 basepoint. The result is a valid
+/// Edwards point, but keys derived from it are not interchangeable with Ed25519 keys:
+/// schnorrkel and Ed25519 sign differently, and secret keys are not compatible.
+///
+/// # Arguments
+///
+/// * `pubkey` - The sr25519 public key, as an array of 32 bytes, to convert.
+///
+/// # Returns
+///
+/// The 32-byte compressed Edwards encoding (the y-coordinate and the sign of x).
+///
+/// # Raises
+///
+/// * `ValueError` - If the public key is not a valid Ristretto encoding.
+#[pyfunction]
+#[pyo3(text_signature = "(pubkey)")]
+pub fn public_key_to_edwards_y(pubkey: PubKey) -> PyResult<PubKey> {
+    CompressedRistretto(pubkey.0).decompress()
+        .ok_or_else(|| _error("INVALID_KEY", "Invalid public key: not a Ristretto point"))?;
+
+    let (x, y) = field::ristretto_decode(&pubkey.0);
+    let mut bytes = y.to_bytes();
+    bytes[31] |= (x.is_negative() as u8) << 7;
+    let representative = CompressedEdwardsY(bytes).decompress()
+        .expect("decoded Ristretto points lie on the curve; qed");
+
+    // The point of order 8 generates the torsion, so the even multiples are the 4-torsion
+    let point = EIGHT_TORSION.iter().step_by(2)
+        .map(|torsion| representative + torsion)
+        .find(|point| point.is_torsion_free())
+        .expect("every Ristretto coset has one point in the prime-order subgroup; qed");
+    Ok(PubKey(point.compress().to_bytes()))
+}
+
+/// Converts a compressed Edwards point to the Ristretto encoding of a public key.
+///
+/// This is the inverse of `public_key_to_edwards_y`. It is only defined for points in the
+/// prime-order subgroup: a point with a torsion component has no meaningful Ristretto
+/// equivalent, since Ristretto discards the cofactor, and is rejected.
+///
+/// # Arguments
+///
+/// * `edwards_y` - The 32-byte compressed Edwards encoding.
+///
+/// # Returns
+///
+/// The 32-byte Ristretto encoding of the point, usable as an sr25519 public key.
+///
+/// # Raises
+///
+/// * `ValueError` - If the bytes don't encode a point on the curve, or the point is not
+///   in the prime-order subgroup.
+#[pyfunction]
+#[pyo3(text_signature = "(edwards_y)")]
+pub fn public_key_from_edwards_y(edwards_y: PubKey) -> PyResult<PubKey> {
+    let point = CompressedEdwardsY(edwards_y.0).decompress()
+        .ok_or_else(|| _error("INVALID_POINT", "Invalid Edwards point: not on the curve"))?;
+    if !point.is_torsion_free() {
+        return Err(_error("INVALID_POINT", "Invalid Edwards point: not in the prime-order subgroup"));
+    }
+
+    // curve25519-dalek keeps the coordinates private, so take them from the decompressed
+    // point's canonical encoding
+    let (x, y) = field::edwards_decode(&point.compress().to_bytes());
+    Ok(PubKey(field::ristretto_encode(&x, &y)))
+}
+
+/// Multi-Signature: each party must call this function locally. resulting signatures can be
+/// aggregated to construct final signature.
+///
+/// # Arguments
+///
+/// * `keypair` - The sr25519 keypair to sign with, as a tuple of (shared_public_bytes, private_bytes)
+/// * `message` - The binary message to signScala.
+/// * 'R_compressed' - The aggregated public point R = R1 + R2
+/// * 'k' - The random scalar related to local R1/R2
+///
+/// # Returns
+///
+/// A 64-byte signature.
+///
+/// # Raises
+///
+/// * `ValueError` - If either the public or private key is invalid.
+#[allow(non_snake_case)]
+#[pyfunction]
+#[pyo3(text_signature = "(keypair, message, R, k)")]
+pub fn multi_sign(keypair: Keypair, message: Message, R_compressed: PubKey, k: PrivKey) -> PyResult<Sig> {
+    let mut public = [0u8; PUBLIC_KEY_LENGTH];
+    let mut private = [0u8; SECRET_KEY_LENGTH];
+    public.clone_from_slice(&keypair.0[0..PUBLIC_KEY_LENGTH]);
+    private.clone_from_slice(&keypair.1[0..SECRET_KEY_LENGTH]);
+    let secret = match SecretKey::from_bytes(&private) {
+        Ok(some_secret) => some_secret,
+        Err(err) => return Err(_error("INVALID_KEY", format!("Invalid secret key: {}", err))),
+    };
+
+    let k_scalar = match SecretKey::from_bytes(&k.0) {
+        Ok(some_key) => some_key,
+        Err(err) => return Err(_error("INVALID_KEY", format!("Invalid secret key: {}", err))),
+    };
+
+    let public = match PublicKey::from_bytes(&public) {
+        Ok(some_public) => some_public,
+        Err(err) => return Err(_error("INVALID_KEY", format!("Invalid public key: {}", err))),
+    };
+
+    let R_point = match PublicKey::from_bytes(&R_compressed.0) {
+        Ok(some_pk) => some_pk,
+        Err(err) => return Err(_error("INVALID_KEY", format!("Invalid public key: {}", err))),
+    };
+
+    let context = signing_context(SIGNING_CTX);
+    inner_raw_sign(secret, context.bytes(&message.0), R_point, public, k_scalar)
+
+}
+
+pub fn concat_u8(first: &[u8], second: &[u8]) -> Vec<u8> {
+    [first, second].concat()
+}
+
+#[allow(non_snake_case)]
+pub fn inner_raw_sign<T: SigningTranscript>(secret: SecretKey, mut t: T, R_point: PublicKey, public: PublicKey, k: SecretKey) ->  PyResult<Sig>
+{
+    t.proto_name(b"Schnorr-sig");
+    t.commit_point(b"sign:pk",public.as_compressed());
+
+    let R = R_point.as_compressed();
+    t.commit_point(b"sign:R",R);
+
+    let e = t.challenge_scalar(b"sign:c");  // context, message, A/public_key, R=rG
+
+    // The first half of a secret key is its signing scalar, exactly as schnorrkel's own
+    // signing uses it
+    let s = (e * _secret_scalar(&secret)) + _secret_scalar(&k);
+
+    let mut sig_bytes = [0u8; SIGNATURE_LENGTH];
+    sig_bytes[..32].copy_from_slice(R.as_bytes());
+    sig_bytes[32..].copy_from_slice(s.as_bytes());
+    // Mark the signature as schnorrkel does, since `verify` rejects signatures without it
+    sig_bytes[63] |= 128;
+    Ok(Sig(sig_bytes))
+}
+
+/// An sr25519 keypair with named access to its public and secret key.
+///
+/// Functions returning keypairs still return `(public_key, secret_key)` tuples for
+/// compatibility, and every function taking a keypair accepts either a tuple or an
+/// instance of this class. An instance is built from those two values:
+/// `Keypair(*sr25519.pair_from_seed(seed))`.
+#[pyclass(name = "Keypair")]
+pub struct PyKeypair {
+    public_key: [u8; PUBLIC_KEY_LENGTH],
+    secret_key: [u8; SECRET_KEY_LENGTH],
+}
+
+#[pymethods]
+impl PyKeypair {
+    #[new]
+    fn new(public_key: PubKey, secret_key: PrivKey) -> PyResult<Self> {
+        let keypair = Keypair(public_key.0, secret_key.0);
+        _to_schnorrkel_keypair(&keypair)?;
+        Ok(PyKeypair { public_key: keypair.0, secret_key: keypair.1 })
+    }
+
+    /// The 32-byte public key.
+    #[getter]
+    fn public_key<'p>(&self, py: Python<'p>) -> &'p PyBytes {
+        PyBytes::new(py, &self.public_key)
+    }
+
+    /// The 64-byte secret key.
+    #[getter]
+    fn secret_key<'p>(&self, py: Python<'p>) -> &'p PyBytes {
+        PyBytes::new(py, &self.secret_key)
+    }
+
+    /// Signs a message under the `substrate` context, as `sign` does.
+    #[pyo3(text_signature = "($self, message)")]
+    fn sign(&self, message: Message) -> PyResult<Sig> {
+        crate::sign(Keypair(self.public_key, self.secret_key), message)
+    }
+
+    /// Returns the 32-byte public key.
+    #[pyo3(text_signature = "($self)")]
+    fn public(&self) -> PubKey {
+        PubKey(self.public_key)
+    }
+}
+
+impl Drop for PyKeypair {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
+
+/// Holds a secret key for the duration of a `with` block and wipes it on exit.
+///
+/// Created with `load_secret`. The secret key is overwritten with zeros when the block
+/// exits, whether normally or through an exception, after which `sign` raises.
+#[pyclass]
+pub struct SecretGuard {
+    secret_key: [u8; SECRET_KEY_LENGTH],
+    wiped: bool,
+}
+
+#[pymethods]
+impl SecretGuard {
+    fn __enter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __exit__(&mut self, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) -> bool {
+        self.secret_key.zeroize();
+        self.wiped = true;
+        false
+    }
+
+    /// Signs a message under the given context with the held secret key.
+    #[pyo3(text_signature = "($self, message, context)")]
+    fn sign(&self, message: Message, context: Message) -> PyResult<Sig> {
+        if self.wiped {
+            return Err(_error("INVALID_KEY", "Secret key has been wiped"));
+        }
+        let secret = SecretKey::from_bytes(&self.secret_key)
+            .map_err(|err| _error("INVALID_KEY", format!("Invalid secret key: {}", err)))?;
+        let sig = secret.sign(signing_context(&context.0).bytes(&message.0), &secret.to_public());
+        Ok(Sig(sig.to_bytes()))
+    }
+}
+
+impl Drop for SecretGuard {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
+
+/// Signs messages with a keypair that is parsed once, on construction.
+///
+/// `sign` parses the keypair on every call, which adds up for services signing many
+/// messages with the same key. Signatures are the same as from `sign`.
+#[pyclass]
+pub struct Signer {
+    keypair: SchnorrkelKeypair,
+}
+
+#[pymethods]
+impl Signer {
+    #[new]
+    fn new(keypair: Keypair) -> PyResult<Self> {
+        Ok(Signer { keypair: _to_schnorrkel_keypair(&keypair)? })
+    }
+
+    /// Signs a message under the `substrate` context, as `sign` does.
+    #[pyo3(text_signature = "($self, message)")]
+    fn sign(&self, message: Message) -> Sig {
+        let context = signing_context(SIGNING_CTX);
+        Sig(self.keypair.sign(context.bytes(&message.0)).to_bytes())
+    }
+}
+
+/// Verifies signatures against a public key that is parsed once, on construction.
+///
+/// The counterpart of `Signer`, for services verifying many signatures from the same key.
+#[pyclass]
+pub struct Verifier {
+    public: PublicKey,
+}
+
+#[pymethods]
+impl Verifier {
+    #[new]
+    fn new(pubkey: PubKey) -> PyResult<Self> {
+        let public = PublicKey::from_bytes(&pubkey.0)
+            .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+        Ok(Verifier { public })
+    }
+
+    /// Verifies a signature under the `substrate` context, as `verify` does.
+    #[pyo3(text_signature = "($self, signature, message)")]
+    fn verify(&self, signature: Sig, message: Message) -> PyResult<bool> {
+        let sig = Signature::from_bytes(&signature.0)
+            .map_err(|err| _error("INVALID_SIGNATURE", format!("Invalid signature: {}", err)))?;
+        Ok(self.public.verify_simple(SIGNING_CTX, &message.0, &sig).is_ok())
+    }
+}
+
+/// Signs and verifies under a custom signing context, built once on construction.
+///
+/// `SigningContext(b"substrate")` gives the same signatures as `sign` and `verify`.
+#[pyclass(name = "SigningContext")]
+pub struct PySigningContext {
+    context: SchnorrkelSigningContext,
+}
+
+#[pymethods]
+impl PySigningContext {
+    #[new]
+    fn new(context: Message) -> Self {
+        PySigningContext { context: signing_context(&context.0) }
+    }
+
+    /// Signs a message under this context.
+    #[pyo3(text_signature = "($self, keypair, message)")]
+    fn sign(&self, keypair: Keypair, message: Message) -> PyResult<Sig> {
+        let keypair = _to_schnorrkel_keypair(&keypair)?;
+        Ok(Sig(keypair.sign(self.context.bytes(&message.0)).to_bytes()))
+    }
+
+    /// Verifies a signature under this context.
+    #[pyo3(text_signature = "($self, signature, message, pubkey)")]
+    fn verify(&self, signature: Sig, message: Message, pubkey: PubKey) -> PyResult<bool> {
+        let sig = Signature::from_bytes(&signature.0)
+            .map_err(|err| _error("INVALID_SIGNATURE", format!("Invalid signature: {}", err)))?;
+        let pk = PublicKey::from_bytes(&pubkey.0)
+            .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+        Ok(pk.verify(self.context.bytes(&message.0), &sig).is_ok())
+    }
+}
+
+enum MuSigStage {
+    Commit(MuSig<Transcript, CommitStage<SchnorrkelKeypair>>),
+    Reveal(MuSig<Transcript, RevealStage<SchnorrkelKeypair>>),
+    Cosign(MuSig<Transcript, CosignStage>),
+}
+
+/// A MuSig multi-signature session for one signer, wrapping schnorrkel's `musig` module.
+///
+/// Every signer creates a session for the same message, then the signers exchange in turn
+/// their `commitment()`, the value returned by `reveal()`, and the value returned by
+/// `cosign()`, adding the others' with the matching `add_their_*` method before moving on.
+/// Once every cosignature is added, `signature()` returns a standard 64-byte signature
+/// under the `substrate` context, which `verify` accepts for the session's `public_key()`.
+///
+/// The aggregate public key weights each key by a hash of the whole set, so it differs from
+/// the plain sum returned by `sum_public_points`.
+#[pyclass(name = "MuSig")]
+pub struct PyMuSig {
+    stage: Option<MuSigStage>,
+}
+
+impl PyMuSig {
+    fn wrong_stage(&mut self, stage: Option<MuSigStage>, expected: &str) -> PyErr {
+        self.stage = stage;
+        _error("MUSIG_ERROR", format!("MuSig session is not in the {} stage", expected))
+    }
+}
+
+#[pymethods]
+impl PyMuSig {
+    #[new]
+    fn new(keypair: Keypair, message: Message) -> PyResult<Self> {
+        let keypair = _to_schnorrkel_keypair(&keypair)?;
+        let transcript = signing_context(SIGNING_CTX).bytes(&message.0);
+        Ok(PyMuSig { stage: Some(MuSigStage::Commit(MuSig::new(keypair, transcript))) })
+    }
+
+    /// Returns our 16-byte commitment, to send to the other signers.
+    #[pyo3(text_signature = "($self)")]
+    fn commitment<'p>(&self, py: Python<'p>) -> PyResult<&'p PyBytes> {
+        match &self.stage {
+            Some(MuSigStage::Commit(musig)) => Ok(PyBytes::new(py, &musig.our_commitment().0)),
+            _ => Err(_error("MUSIG_ERROR", "MuSig session is not in the commit stage")),
+        }
+    }
+
+    /// Adds another signer's public key and commitment.
+    #[pyo3(text_signature = "($self, public_key, commitment)")]
+    fn add_their_commitment(&mut self, public_key: PubKey, commitment: Message) -> PyResult<()> {
+        let them = PublicKey::from_bytes(&public_key.0)
+            .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+        let mut theirs = Commitment([0u8; MUSIG_COMMITMENT_LENGTH]);
+        theirs.0.copy_from_slice(&_check_bytes_len(commitment.0, MUSIG_COMMITMENT_LENGTH)?);
+        match &mut self.stage {
+            Some(MuSigStage::Commit(musig)) => musig.add_their_commitment(them, theirs)
+                .map_err(|err| _error("MUSIG_ERROR", format!("Invalid commitment: {}", err))),
+            _ => Err(_error("MUSIG_ERROR", "MuSig session is not in the commit stage")),
+        }
+    }
+
+    /// Moves to the reveal stage once every commitment is added, and returns our 96-byte reveal.
+    #[pyo3(text_signature = "($self)")]
+    fn reveal<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyBytes> {
+        match self.stage.take() {
+            Some(MuSigStage::Commit(musig)) => {
+                let musig = musig.reveal_stage();
+                let reveal = PyBytes::new(py, &musig.our_reveal().0);
+                self.stage = Some(MuSigStage::Reveal(musig));
+                Ok(reveal)
+            }
+            stage => Err(self.wrong_stage(stage, "commit")),
+        }
+    }
+
+    /// Adds the reveal of a signer whose commitment was added before.
+    #[pyo3(text_signature = "($self, public_key, reveal)")]
+    fn add_their_reveal(&mut self, public_key: PubKey, reveal: Message) -> PyResult<()> {
+        let them = PublicKey::from_bytes(&public_key.0)
+            .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+        let mut theirs = Reveal([0u8; MUSIG_REVEAL_LENGTH]);
+        theirs.0.copy_from_slice(&_check_bytes_len(reveal.0, MUSIG_REVEAL_LENGTH)?);
+        match &mut self.stage {
+            Some(MuSigStage::Reveal(musig)) => musig.add_their_reveal(them, theirs)
+                .map_err(|err| _error("MUSIG_ERROR", format!("Invalid reveal: {}", err))),
+            _ => Err(_error("MUSIG_ERROR", "MuSig session is not in the reveal stage")),
+        }
+    }
+
+    /// Moves to the cosign stage once every reveal is added, and returns our 32-byte cosignature.
+    #[pyo3(text_signature = "($self)")]
+    fn cosign<'p>(&mut self, py: Python<'p>) -> PyResult<&'p PyBytes> {
+        match self.stage.take() {
+            Some(MuSigStage::Reveal(musig)) => {
+                let musig = musig.cosign_stage();
+                let cosignature = PyBytes::new(py, &musig.our_cosignature().0);
+                self.stage = Some(MuSigStage::Cosign(musig));
+                Ok(cosignature)
+            }
+            stage => Err(self.wrong_stage(stage, "reveal")),
+        }
+    }
+
+    /// Adds the cosignature of a signer whose reveal was added before.
+    #[pyo3(text_signature = "($self, public_key, cosignature)")]
+    fn add_their_cosignature(&mut self, public_key: PubKey, cosignature: Message) -> PyResult<()> {
+        let them = PublicKey::from_bytes(&public_key.0)
+            .map_err(|err| _error("INVALID_KEY", format!("Invalid public key: {}", err)))?;
+        let mut theirs = Cosignature([0u8; SCALAR_LENGTH]);
+        theirs.0.copy_from_slice(&_check_bytes_len(cosignature.0, SCALAR_LENGTH)?);
+        match &mut self.stage {
+            Some(MuSigStage::Cosign(musig)) => musig.add_their_cosignature(them, theirs)
+                .map_err(|err| _error("MUSIG_ERROR", format!("Invalid cosignature: {}", err))),
+            _ => Err(_error("MUSIG_ERROR", "MuSig session is not in the cosign stage")),
+        }
+    }
+
+    /// Returns the 64-byte aggregate signature once every cosignature is added.
+    #[pyo3(text_signature = "($self)")]
+    fn signature(&self) -> PyResult<Sig> {
+        match &self.stage {
+            Some(MuSigStage::Cosign(musig)) => musig.sign()
+                .map(|sig| Sig(sig.to_bytes()))
+                .ok_or_else(|| _error("MUSIG_ERROR", "Missing cosignatures")),
+            _ => Err(_error("MUSIG_ERROR", "MuSig session is not in the cosign stage")),
+        }
+    }
+
+    /// Returns the 32-byte aggregate public key of the signers added so far.
+    #[pyo3(text_signature = "($self)")]
+    fn public_key(&self) -> PyResult<PubKey> {
+        let pk = match &self.stage {
+            Some(MuSigStage::Commit(musig)) => musig.expected_public_key(),
+            Some(MuSigStage::Reveal(musig)) => musig.public_key(),
+            Some(MuSigStage::Cosign(musig)) => musig.public_key(),
+            None => return Err(_error("MUSIG_ERROR", "MuSig session is not usable")),
+        };
+        Ok(PubKey(pk.to_bytes()))
+    }
+}
+
+// Convert Keypair object to a Python Keypair tuple
+impl IntoPy<PyObject> for Keypair {
+    fn into_py(self, py: Python) -> PyObject {
+        let public = PyBytes::new(py, &self.0);
+        let secret = PyBytes::new(py, &self.1);
+        PyTuple::new(py, vec![public, secret]).into_py(py)
+    }
+}
+
+// Convert Python Keypair into Rust
+impl<'a> FromPyObject<'a> for Keypair {
+    fn extract(obj: &'a PyAny) -> PyResult<Self> {
+        if let Ok(keypair) = obj.extract::<PyRef<PyKeypair>>() {
+            return Ok(Keypair(keypair.public_key, keypair.secret_key));
+        }
+        let keypair = obj
+            .downcast::<PyTuple>()
+            .map_err(|_| exceptions::PyTypeError::new_err("Invalid Keypair: expected a tuple"))?;
+        if keypair.len() < 2 {
+            return Err(exceptions::PyIndexError::new_err(format!("Expected tuple of size 2, got {}", keypair.len())));
+        }
+
+        // Convert bytes to fixed width arrays
+        let mut public: [u8; PUBLIC_KEY_LENGTH] = [0u8; PUBLIC_KEY_LENGTH];
+        let mut private: [u8; SECRET_KEY_LENGTH] = [0u8; SECRET_KEY_LENGTH];
+        public.clone_from_slice(
+            &_extract_tuple_item(keypair, 0, PUBLIC_KEY_LENGTH, "Invalid PubKey: expected a bytes-like object")?);
+        private.clone_from_slice(
+            &_extract_tuple_item(keypair, 1, SECRET_KEY_LENGTH, "Invalid SecretKey: expected a bytes-like object")?);
+        let keypair = Keypair(public, private);
+        Ok(keypair)
+    }
+}
+
+// Convert Sig struct to a PyObject
+impl IntoPy<PyObject> for Sig {
+    fn into_py(self, py: Python) -> PyObject {
+        let sig = PyBytes::new(py, &self.0);
+        sig.into_py(py)
+    }
+}
+
+// Convert a PyBytes object of size 64 to a Sig object
+impl<'a> FromPyObject<'a> for Sig {
+    fn extract(obj: &'a PyAny) -> PyResult<Self> {
+        let signature = _extract_bytes(obj)
+            .ok_or_else(|| exceptions::PyTypeError::new_err(format!("Expected {} byte signature", SIGNATURE_LENGTH)))
+            .and_then(|b| _check_bytes_len(b, SIGNATURE_LENGTH))?;
+
+        // Convert bytes to fixed width array
+        let mut fixed: [u8; SIGNATURE_LENGTH] = [0u8; SIGNATURE_LENGTH];
+        fixed.clone_from_slice(&signature);
+        Ok(Sig(fixed))
+    }
+}
+
+// Convert Seed struct to a PyObject
+impl IntoPy<PyObject> for Seed {
+    fn into_py(self, py: Python) -> PyObject {
+        let seed = PyBytes::new(py, &self.0);
+        seed.into_py(py)
+    }
+}
+
+// Convert a PyBytes object into a Seed
+impl<'a> FromPyObject<'a> for Seed {
+    fn extract(obj: &'a PyAny) -> PyResult<Self> {
+        let seed = _extract_bytes(obj)
+            .ok_or_else(|| PyErr::new::<exceptions::PyTypeError, _>("Expected a bytestring"))?;
+
+        if seed.len() != MINI_SECRET_KEY_LENGTH {
+            return Err(PyErr::new::<exceptions::PyIndexError, _>(
+                format!("Expected seed with length: {}", MINI_SECRET_KEY_LENGTH),
+            ));
+        }
+
+        // Convert bytes to fixed width array
+        let mut fixed: [u8; MINI_SECRET_KEY_LENGTH] = Default::default();
+        fixed.copy_from_slice(&seed);
+        Ok(Seed(fixed))
+    }
+}
+
+// Convert PubKey struct to a PyObject
+impl IntoPy<PyObject> for PubKey {
+    fn into_py(self, py: Python) -> PyObject {
+        let key = PyBytes::new(py, &self.0);
+        key.into_py(py)
+    }
+}
+
+// Convert a PyBytes object of size 32 to a PublicKey struct
+impl<'a> FromPyObject<'a> for PubKey {
+    fn extract(obj: &'a PyAny) -> PyResult<Self> {
+        let pubkey = _extract_bytes(obj)
+            .ok_or_else(|| exceptions::PyTypeError::new_err("Invalid PubKey, expected bytes object"))
+            .and_then(|b| _check_bytes_len(b, PUBLIC_KEY_LENGTH))?;
+
+        // Convert bytes to fixed width array
+        let mut fixed: [u8; PUBLIC_KEY_LENGTH] = Default::default();
+        fixed.clone_from_slice(&pubkey);
+        Ok(PubKey(fixed))
+    }
+}
+
+// Convert PrivKey struct to a PyObject
+impl IntoPy<PyObject> for PrivKey {
+    fn into_py(self, py: Python) -> PyObject {
+        let key = PyBytes::new(py, &self.0);
+        key.into_py(py)
+    }
+}
+
+// Convert a PyBytes object of size 64 to a PrivKey object
+impl<'a> FromPyObject<'a> for PrivKey {
+    fn extract(obj: &'a PyAny) -> PyResult<Self> {
+        let secret = _extract_bytes(obj)
+            .ok_or_else(|| exceptions::PyTypeError::new_err(format!("Expected {} byte secret key", SECRET_KEY_LENGTH)))
+            .and_then(|b| _check_bytes_len(b, SECRET_KEY_LENGTH))?;
+
+        // Convert bytes to fixed width array
+        let mut fixed: [u8; SECRET_KEY_LENGTH] = [0u8; SECRET_KEY_LENGTH];
+        fixed.clone_from_slice(&secret);
+        Ok(PrivKey(fixed))
+    }
+}
+
+// Convert an arbitrary sized PyBytes object to a Message struct. A str is accepted too,
+// and is always UTF-8 encoded, so it gives the same message as `.encode()`
+impl<'a> FromPyObject<'a> for Message {
+    fn extract(obj: &PyAny) -> PyResult<Self> {
+        if let Ok(string) = obj.downcast::<PyString>() {
+            return Ok(Message(string.to_str()?.as_bytes().to_vec()));
+        }
+        let messsge = _extract_bytes(obj)
+            .ok_or_else(|| exceptions::PyTypeError::new_err("Expected bytes or str object"))?;
+        Ok(Message(messsge))
+    }
+}
+
+// Convert ExtendedPubKey into Python ExtendedPubKey tuple
+impl IntoPy<PyObject> for ExtendedPubKey {
+    fn into_py(self, py: Python) -> PyObject {
+        let chain_code = PyBytes::new(py, &self.0);
+        let public = PyBytes::new(py, &self.1);
+
+        PyTuple::new(py, vec![chain_code, public]).into_py(py)
+    }
+}
+
+// Convert Python ExtendedPubKey into Rust
+impl<'a> FromPyObject<'a> for ExtendedPubKey {
+    fn extract(obj: &'a PyAny) -> PyResult<Self> {
+        let extended = _to_pytuple(obj)?;
+        // Don't check that the length matches exactly here so that an extended
+        // private key can be passed in as well.
+        if extended.len() < 2 {
+            return Err(exceptions::PyIndexError::new_err(format!("Expected tuple of size 2, got {}", extended.len())));
+        }
+
+        // Convert bytes to fixed width arrays
+        let mut chain_code: [u8; CHAIN_CODE_LENGTH] = [0u8; CHAIN_CODE_LENGTH];
+        let mut public: [u8; PUBLIC_KEY_LENGTH] = [0u8; PUBLIC_KEY_LENGTH];
+        chain_code.clone_from_slice(
+            &_extract_tuple_item(extended, 0, CHAIN_CODE_LENGTH, "Expected bytes-like object at index 0")?);
+        public.clone_from_slice(
+            &_extract_tuple_item(extended, 1, PUBLIC_KEY_LENGTH, "Expected bytes-like object at index 1")?);
+        let extended_pubkey = ExtendedPubKey(chain_code, public);
+        Ok(extended_pubkey)
+    }
+}
+
+// Convert ExtendedKeypair into Python ExtendedKeypair tuple
+impl IntoPy<PyObject> for ExtendedKeypair {
+    fn into_py(self, py: Python) -> PyObject {
+        let chain_code = PyBytes::new(py, &self.0);
+        let public = PyBytes::new(py, &self.1);
+        let private = PyBytes::new(py, &self.2);
+
+        PyTuple::new(py, vec![chain_code, public, private]).into_py(py)
+    }
+}
+
+// Convert Python ExtendedKeypair into Rust
+impl<'a> FromPyObject<'a> for ExtendedKeypair {
+    fn extract(obj: &'a PyAny) -> PyResult<Self> {
+        let extended = _to_pytuple(obj)?;
+        if extended.len() < 3 {
+            return Err(exceptions::PyIndexError::new_err(format!("Expected tuple of size 3, got {}", extended.len())));
+        }
+
+        // Convert bytes to fixed width arrays
+        let mut chain_code: [u8; CHAIN_CODE_LENGTH] = [0u8; CHAIN_CODE_LENGTH];
+        let mut public: [u8; PUBLIC_KEY_LENGTH] = [0u8; PUBLIC_KEY_LENGTH];
+        let mut private: [u8; SECRET_KEY_LENGTH] = [0u8; SECRET_KEY_LENGTH];
+
+        chain_code.clone_from_slice(
+            &_extract_tuple_item(extended, 0, CHAIN_CODE_LENGTH, "Expected bytes-like object at index 0")?);
+        public.clone_from_slice(
+            &_extract_tuple_item(extended, 1, PUBLIC_KEY_LENGTH, "Expected bytes-like object at index 1")?);
+        private.clone_from_slice(
+            &_extract_tuple_item(extended, 2, SECRET_KEY_LENGTH, "Expected bytes-like object at index 2")?);
+        let extended_keypair = ExtendedKeypair(chain_code, public, private);
+        Ok(extended_keypair)
+    }
+}
+
+// Convert ScalarBytes struct to a PyObject
+impl IntoPy<PyObject> for ScalarBytes {
+    fn into_py(self, py: Python) -> PyObject {
+        let scalar = PyBytes::new(py, &self.0);
+        scalar.into_py(py)
+    }
+}
+
+// Convert a PyBytes object of size 32 to a ScalarBytes struct
+impl<'a> FromPyObject<'a> for ScalarBytes {
+    fn extract(obj: &'a PyAny) -> PyResult<Self> {
+        let scalar = _extract_bytes(obj)
+            .ok_or_else(|| exceptions::PyTypeError::new_err(format!("Expected {} byte scalar", SCALAR_LENGTH)))
+            .and_then(|b| _check_bytes_len(b, SCALAR_LENGTH))?;
+
+        // Convert bytes to fixed width array
+        let mut fixed: [u8; SCALAR_LENGTH] = [0u8; SCALAR_LENGTH];
+        fixed.clone_from_slice(&scalar);
+        Ok(ScalarBytes(fixed))
+    }
+}
+
+// Convert VrfOutput struct to a PyObject
+impl IntoPy<PyObject> for VrfOutput {
+    fn into_py(self, py: Python) -> PyObject {
+        let output = PyBytes::new(py, &self.0);
+        output.into_py(py)
+    }
+}
+
+// Convert VrfProof struct to a PyObject
+impl IntoPy<PyObject> for VrfProof {
+    fn into_py(self, py: Python) -> PyObject {
+        let proof = PyBytes::new(py, &self.0);
+        proof.into_py(py)
+    }
+}
+
+// Convert a PyBytes object of size 32 to a VrfOutput struct
+impl<'a> FromPyObject<'a> for VrfOutput {
+    fn extract(obj: &'a PyAny) -> PyResult<Self> {
+        let output = _extract_bytes(obj)
+            .ok_or_else(|| exceptions::PyTypeError::new_err(format!("Expected {} byte VRF output", VRF_PREOUT_LENGTH)))
+            .and_then(|b| _check_bytes_len(b, VRF_PREOUT_LENGTH))?;
+
+        // Convert bytes to fixed width array
+        let mut fixed: [u8; VRF_PREOUT_LENGTH] = [0u8; VRF_PREOUT_LENGTH];
+        fixed.clone_from_slice(&output);
+        Ok(VrfOutput(fixed))
+    }
+}
+
+// Convert a PyBytes object of size 96 to a VrfProof struct
+impl<'a> FromPyObject<'a> for VrfProof {
+    fn extract(obj: &'a PyAny) -> PyResult<Self> {
+        let proof = _extract_bytes(obj)
+            .ok_or_else(|| exceptions::PyTypeError::new_err(format!("Expected {} byte VRF proof", VRF_PROOF_BATCHABLE_LENGTH)))
+            .and_then(|b| _check_bytes_len(b, VRF_PROOF_BATCHABLE_LENGTH))?;
+
+        // Convert bytes to fixed width array
+        let mut fixed: [u8; VRF_PROOF_BATCHABLE_LENGTH] = [0u8; VRF_PROOF_BATCHABLE_LENGTH];
+        fixed.clone_from_slice(&proof);
+        Ok(VrfProof(fixed))
+    }
+}
+
+/// This module is a python module implemented in Rust.
+#[pymodule]
+fn sr25519(py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add("PUBLIC_KEY_LENGTH", PUBLIC_KEY_LENGTH)?;
+    m.add("SECRET_KEY_LENGTH", SECRET_KEY_LENGTH)?;
+    m.add("MINI_SECRET_KEY_LENGTH", MINI_SECRET_KEY_LENGTH)?;
+    m.add("SIGNATURE_LENGTH", SIGNATURE_LENGTH)?;
+    m.add("CHAIN_CODE_LENGTH", CHAIN_CODE_LENGTH)?;
+    m.add("DEFAULT_CONTEXT", PyBytes::new(py, SIGNING_CTX))?;
+    m.add("Sr25519Error", py.get_type::<Sr25519Error>())?;
+    m.add_class::<PyKeypair>()?;
+    m.add_class::<SecretGuard>()?;
+    m.add_class::<PyMuSig>()?;
+    m.add_class::<Signer>()?;
+    m.add_class::<Verifier>()?;
+    m.add_class::<PySigningContext>()?;
+    m.add_wrapped(wrap_pyfunction!(pair_from_seed))?;
+    m.add_wrapped(wrap_pyfunction!(pairs_from_seeds))?;
+    m.add_wrapped(wrap_pyfunction!(expand_mini_secret))?;
+    m.add_wrapped(wrap_pyfunction!(sign_from_seed))?;
+    m.add_wrapped(wrap_pyfunction!(generate_random_keypair))?;
+    m.add_wrapped(wrap_pyfunction!(pair_from_mnemonic))?;
+    m.add_wrapped(wrap_pyfunction!(pair_from_entropy))?;
+    m.add_wrapped(wrap_pyfunction!(keypair_from_password))?;
+    m.add_wrapped(wrap_pyfunction!(dev_keypair))?;
+    m.add_wrapped(wrap_pyfunction!(derive_path))?;
+    m.add_wrapped(wrap_pyfunction!(derive_path_pubkey))?;
+    m.add_wrapped(wrap_pyfunction!(load_secret))?;
+    m.add_wrapped(wrap_pyfunction!(sign_encoded))?;
+    m.add_wrapped(wrap_pyfunction!(sign_checked))?;
+    m.add_wrapped(wrap_pyfunction!(sign_wrapped))?;
+    m.add_wrapped(wrap_pyfunction!(verify))?;
+    m.add_wrapped(wrap_pyfunction!(verify_with_keypair))?;
+    m.add_wrapped(wrap_pyfunction!(verify_wrapped))?;
+    m.add_wrapped(wrap_pyfunction!(quick_reject))?;
+    m.add_wrapped(wrap_pyfunction!(verify_strict))?;
+    m.add_wrapped(wrap_pyfunction!(verify_compat))?;
+    m.add_wrapped(wrap_pyfunction!(sign_prehashed))?;
+    m.add_wrapped(wrap_pyfunction!(verify_prehashed))?;
+    m.add_wrapped(wrap_pyfunction!(sign_hex))?;
+    m.add_wrapped(wrap_pyfunction!(verify_hex))?;
+    m.add_wrapped(wrap_pyfunction!(public_key_from_hex))?;
+    m.add_wrapped(wrap_pyfunction!(secret_key_from_hex))?;
+    m.add_wrapped(wrap_pyfunction!(sign_transcript))?;
+    m.add_wrapped(wrap_pyfunction!(verify_transcript))?;
+    m.add_wrapped(wrap_pyfunction!(sign_rfc6979))?;
+    m.add_wrapped(wrap_pyfunction!(sign_deterministic))?;
+    m.add_wrapped(wrap_pyfunction!(sign_with_rng_seed))?;
+    m.add_wrapped(wrap_pyfunction!(resign_under_context))?;
+    m.add_wrapped(wrap_pyfunction!(sign_multi_context))?;
+    m.add_wrapped(wrap_pyfunction!(sign_session))?;
+    m.add_wrapped(wrap_pyfunction!(verify_session))?;
+    m.add_wrapped(wrap_pyfunction!(sign_with_expiry_block))?;
+    m.add_wrapped(wrap_pyfunction!(verify_with_expiry_block))?;
+    m.add_wrapped(wrap_pyfunction!(vrf_sign))?;
+    m.add_wrapped(wrap_pyfunction!(vrf_verify))?;
+    m.add_wrapped(wrap_pyfunction!(vrf_sign_extra))?;
+    m.add_wrapped(wrap_pyfunction!(vrf_verify_extra))?;
+    m.add_wrapped(wrap_pyfunction!(vrf_output_bytes))?;
+    m.add_wrapped(wrap_pyfunction!(sign_auto_prehash))?;
+    m.add_wrapped(wrap_pyfunction!(verify_auto_prehash))?;
+    m.add_wrapped(wrap_pyfunction!(sign_fresh))?;
+    m.add_wrapped(wrap_pyfunction!(verify_fresh))?;
+    m.add_wrapped(wrap_pyfunction!(verify_batch))?;
+    m.add_wrapped(wrap_pyfunction!(verify_batch_contexts))?;
+    m.add_wrapped(wrap_pyfunction!(verify_batch_single_key))?;
+    m.add_wrapped(wrap_pyfunction!(verify_each))?;
+    m.add_wrapped(wrap_pyfunction!(aggregate_signatures_compact))?;
+    m.add_wrapped(wrap_pyfunction!(verify_aggregate_compact))?;
+    m.add_wrapped(wrap_pyfunction!(verify_extrinsic_signature))?;
+    m.add_wrapped(wrap_pyfunction!(verify_delegation_chain))?;
+    m.add_wrapped(wrap_pyfunction!(timing_self_test))?;
+    m.add_wrapped(wrap_pyfunction!(assemble_signature))?;
+    m.add_wrapped(wrap_pyfunction!(signature_components))?;
+    m.add_wrapped(wrap_pyfunction!(signature_from_components))?;
+    m.add_wrapped(wrap_pyfunction!(public_from_secret_key))?;
+    m.add_wrapped(wrap_pyfunction!(pair_from_secret_key))?;
+    m.add_wrapped(wrap_pyfunction!(keypair_to_bytes))?;
+    m.add_wrapped(wrap_pyfunction!(keypair_from_bytes))?;
+    m.add_wrapped(wrap_pyfunction!(public_from_keypair))?;
+    m.add_wrapped(wrap_pyfunction!(is_valid_public_key))?;
+    m.add_wrapped(wrap_pyfunction!(is_valid_secret_key))?;
+    m.add_wrapped(wrap_pyfunction!(secret_key_to_mini_secret))?;
+    m.add_wrapped(wrap_pyfunction!(secret_key_to_ed25519_bytes))?;
+    m.add_wrapped(wrap_pyfunction!(secret_from_ed25519_bytes))?;
+    m.add_wrapped(wrap_pyfunction!(secret_keys_equal))?;
+    m.add_wrapped(wrap_pyfunction!(public_keys_equal))?;
+    m.add_wrapped(wrap_pyfunction!(chain_code_from_bytes))?;
+    m.add_wrapped(wrap_pyfunction!(derive_pubkey))?;
+    m.add_wrapped(wrap_pyfunction!(derive_pubkey_index))?;
+    m.add_wrapped(wrap_pyfunction!(hard_derive_pubkey))?;
+    m.add_wrapped(wrap_pyfunction!(derive_keypair))?;
+    m.add_wrapped(wrap_pyfunction!(derive_keypair_deterministic))?;
+    m.add_wrapped(wrap_pyfunction!(derive_keypairs_range))?;
+    m.add_wrapped(wrap_pyfunction!(hard_derive_keypair))?;
+    m.add_wrapped(wrap_pyfunction!(hard_derive_mini_secret))?;
+    m.add_wrapped(wrap_pyfunction!(derive_counter_keypair))?;
+    m.add_wrapped(wrap_pyfunction!(ss58_encode))?;
+    m.add_wrapped(wrap_pyfunction!(ss58_decode))?;
+    m.add_wrapped(wrap_pyfunction!(public_key_to_account_id))?;
+    m.add_wrapped(wrap_pyfunction!(keypair_to_subkey_json))?;
+    m.add_wrapped(wrap_pyfunction!(extended_key_fingerprint))?;
+    m.add_wrapped(wrap_pyfunction!(sum_public_points))?;
+    m.add_wrapped(wrap_pyfunction!(subtract_public_points))?;
+    m.add_wrapped(wrap_pyfunction!(negate_public_point))?;
+    m.add_wrapped(wrap_pyfunction!(identity_public_point))?;
+    m.add_wrapped(wrap_pyfunction!(points_equal_or_negation))?;
+    m.add_wrapped(wrap_pyfunction!(aggregate_public_keys))?;
+    m.add_wrapped(wrap_pyfunction!(aggregate_signature_r))?;
+    m.add_wrapped(wrap_pyfunction!(verify_threshold))?;
+    m.add_wrapped(wrap_pyfunction!(scalar_mul_public_point))?;
+    m.add_wrapped(wrap_pyfunction!(is_canonical_point))?;
+    m.add_wrapped(wrap_pyfunction!(normalize_point))?;
+    m.add_wrapped(wrap_pyfunction!(dleq_prove))?;
+    m.add_wrapped(wrap_pyfunction!(dleq_verify))?;
+    m.add_wrapped(wrap_pyfunction!(multi_sign))?;
+    m.add_wrapped(wrap_pyfunction!(encrypt_to_public))?;
+    m.add_wrapped(wrap_pyfunction!(decrypt_with_secret))?;
+    m.add_wrapped(wrap_pyfunction!(encrypt_keystore))?;
+    m.add_wrapped(wrap_pyfunction!(decrypt_keystore))?;
+    m.add_wrapped(wrap_pyfunction!(aggregation_proof))?;
+    m.add_wrapped(wrap_pyfunction!(validate_participant_set))?;
+    m.add_wrapped(wrap_pyfunction!(group_public_from_seeds))?;
+    m.add_wrapped(wrap_pyfunction!(musig2_nonce_gen))?;
+    m.add_wrapped(wrap_pyfunction!(musig2_key_agg))?;
+    m.add_wrapped(wrap_pyfunction!(musig2_nonce_agg))?;
+    m.add_wrapped(wrap_pyfunction!(musig2_partial_sign))?;
+    m.add_wrapped(wrap_pyfunction!(musig2_partial_sig_agg))?;
+    m.add_wrapped(wrap_pyfunction!(verify_aggregation_proof))?;
+    m.add_wrapped(wrap_pyfunction!(small_order_points))?;
+    m.add_wrapped(wrap_pyfunction!(reject_small_order_public_key))?;
+    m.add_wrapped(wrap_pyfunction!(public_key_to_edwards_y))?;
+    m.add_wrapped(wrap_pyfunction!(public_key_from_edwards_y))?;
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use pyo3::types::PyDict;
+
+    static TEST_SEED: [u8; MINI_SECRET_KEY_LENGTH] = hex!("f30eb58ad9bde4a702da3c723709cbfaf7030b22d5e4d16bcbf733c9c09bf6bd");
+    static TEST_CHAIN_CODE: [u8; CHAIN_CODE_LENGTH] = hex!("79f708602879cb5cecfff56f57a8551ff170025d77a42d053a9caf7ac4c543b5");
+
+    static TEST_PUBKEY: [u8; PUBLIC_KEY_LENGTH] = hex!("0e563c7dcb4446c0ed7e7a9d9f0a3a3d41c8767a87f205bd48fb8ef5db066b6b");
+    static TEST_PRIVKEY: [u8; SECRET_KEY_LENGTH] = hex!("1a470f5b685a943fc90d8c0ec0cddb4ace28e26fd3e0091eb39a4333277def0bc5cb44ce61338968b0d5f2022346684a90ba8e526dd9d1c0616f1e76be5edcff");
+
+    static CHILD_CHAIN_CODE: [u8; CHAIN_CODE_LENGTH] = hex!("6c623b771ab68008e4d3c739abf5ae322a2be44e2bd477e3dec237a0fe5ede1e");
+    static CHILD_PUBKEY: [u8; PUBLIC_KEY_LENGTH] = hex!("5e578b800520128de3056e59e2e11aad250dd72a28dddf5886ab7f7808f7642f");
+    static CHILD_PRIVKEY: [u8; SECRET_KEY_LENGTH] = hex!("4f0db52b5d4105303aa0ef052b33040727c5fd5beeb0a7ca12cd8244ed1c6505f17d23ab6e22685f03aec29eb7722b53b7c79434ec6f380956bb904eccc6dd14");
+
+    static CHILD_CHAIN_CODE_HARD: [u8; CHAIN_CODE_LENGTH] = hex!("0e89c4b7b29d92138c0a093b9f9cf9a0132960f52664a188e6d0e3ef324316cc");
+    static CHILD_PUBKEY_HARD: [u8; PUBLIC_KEY_LENGTH] = hex!("8c0ff65769fdb4bfdf12e628261f39bfb29fba94353b6faddbcddcd455d4ea29");
+    static CHILD_PRIVKEY_HARD: [u8; SECRET_KEY_LENGTH] = hex!("1c76b89abb8cce5310fa40ef7f197b6c0fd482f2b59e1a524ec30736fbcf580881f2e63b515c6be0a484e3ceaefce0372f1826d3dd230502f8cea22a323bfbb1");
+
+    static ALICE_PUBKEY: [u8; PUBLIC_KEY_LENGTH] = hex!("d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d");
+
+    static TEST_MESSAGE: &[u8] =
+        b"All of the world's a stage \
+        And all the men and women merely players; \
+        They have their exits and their entrances, \
+        And one man in his time plays many parts, \
+        His acts being seven ages.";
+
+    #[test]
+    fn test_pair_from_seed() -> PyResult<()> {
+        let seed = Seed(TEST_SEED);
+        let keypair = pair_from_seed(seed, "ed25519")?;
+
+        assert_eq!(keypair.0, TEST_PUBKEY);
+        assert_eq!(&keypair.1[0..SECRET_KEY_LENGTH], &TEST_PRIVKEY[0..SECRET_KEY_LENGTH]);
+
+        let uniform = pair_from_seed(Seed(TEST_SEED), "uniform")?;
+        assert_ne!(uniform.0, TEST_PUBKEY);
+        assert_ne!(&uniform.1[..], &TEST_PRIVKEY[..]);
+        assert!(pair_from_seed(Seed(TEST_SEED), "Ed25519").is_err());
+
+        // Degenerate seeds still give keypairs rather than panicking
+        for seed in [[0u8; MINI_SECRET_KEY_LENGTH], [0xff; MINI_SECRET_KEY_LENGTH]] {
+            assert!(pair_from_seed(Seed(seed), "ed25519").is_ok());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_mini_secret() -> PyResult<()> {
+        for mode in ["ed25519", "uniform"] {
+            let secret = expand_mini_secret(Seed(TEST_SEED), mode)?;
+            assert_eq!(&secret.0[..], &pair_from_seed(Seed(TEST_SEED), mode)?.1[..]);
+        }
+        assert_eq!(&expand_mini_secret(Seed(TEST_SEED), "ed25519")?.0[..], &TEST_PRIVKEY[..]);
+        assert!(expand_mini_secret(Seed(TEST_SEED), "other").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sign_from_seed() -> PyResult<()> {
+        // Signatures are randomized, so check it verifies under the expanded keypair's public key
+        let keypair = pair_from_seed(Seed(TEST_SEED), "ed25519")?;
+        let sig = sign_from_seed(Seed(TEST_SEED), Message(Vec::from(TEST_MESSAGE)))?;
+        assert!(verify(Sig(sig.0), Message(Vec::from(TEST_MESSAGE)), PubKey(keypair.0))?);
+        assert!(!verify(sig, Message(Vec::from(TEST_MESSAGE)), PubKey(CHILD_PUBKEY))?);
+
+        let expanded_sig = sign(Keypair(keypair.0, keypair.1), Message(Vec::from(TEST_MESSAGE)))?;
+        assert!(verify(expanded_sig, Message(Vec::from(TEST_MESSAGE)), PubKey(keypair.0))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pairs_from_seeds() -> PyResult<()> {
+        let seeds = [TEST_SEED, [1u8; MINI_SECRET_KEY_LENGTH], [2u8; MINI_SECRET_KEY_LENGTH]];
+        let keypairs = pairs_from_seeds(seeds.iter().map(|seed| Message(seed.to_vec())).collect())?;
+        assert_eq!(keypairs.len(), seeds.len());
+        for (keypair, seed) in keypairs.iter().zip(seeds.iter()) {
+            let expected = pair_from_seed(Seed(*seed), "ed25519")?;
+            assert_eq!(keypair.0, expected.0);
+            assert_eq!(&keypair.1[..], &expected.1[..]);
+        }
+
+        let err = pairs_from_seeds(vec![Message(TEST_SEED.to_vec()), Message(vec![0u8; 31])]).err().unwrap();
+        assert!(Python::with_gil(|py| err.into_py(py).as_ref(py).str().unwrap().to_string()).contains("index 1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_random_keypair() -> PyResult<()> {
+        let (seed, keypair) = generate_random_keypair()?;
+        let (other_seed, other_keypair) = generate_random_keypair()?;
+        assert_ne!(seed.0, other_seed.0);
+        assert_ne!(keypair.0, other_keypair.0);
+
+        // The seed regenerates the same keypair
+        let regenerated = pair_from_seed(seed, "ed25519")?;
+        assert_eq!(regenerated.0, keypair.0);
+        assert_eq!(&regenerated.1[..], &keypair.1[..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pair_from_mnemonic() -> PyResult<()> {
+        // Substrate's well-known development phrase and its root public key
+        let keypair = pair_from_mnemonic(DEV_PHRASE, "")?;
+        assert_eq!(keypair.0, hex!("46ebddef8cd9bb167dc30878d7113b7e168e6f0646beffd77d69d39bad76b47a"));
+
+        let with_password = pair_from_mnemonic(DEV_PHRASE, "password")?;
+        assert_ne!(keypair.0, with_password.0);
+        assert!(pair_from_mnemonic("not a valid mnemonic", "").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pair_from_entropy() -> PyResult<()> {
+        // BIP39 test vectors for 16 and 32 bytes of 0x7f entropy
+        let short = pair_from_entropy(Message(vec![0x7f; 16]), "")?;
+        let long = pair_from_entropy(Message(vec![0x7f; 32]), "")?;
+        let words = "legal winner thank year wave sausage worth useful";
+        assert_eq!(short.0, pair_from_mnemonic(&format!("{} legal winner thank yellow", words), "")?.0);
+        assert_eq!(long.0, pair_from_mnemonic(&format!("{0} {0} legal winner thank year wave sausage worth title", words), "")?.0);
+        assert_eq!(short.0, pair_from_entropy(Message(vec![0x7f; 16]), "")?.0);
+        assert_ne!(short.0, long.0);
+
+        assert!(pair_from_entropy(Message(vec![0u8; 12]), "").is_err());
+        assert!(pair_from_entropy(Message(vec![0u8; 18]), "").is_err());
+        assert!(pair_from_entropy(Message(vec![0u8; 36]), "").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_keypair_from_password() -> PyResult<()> {
+        // Cheap parameters so the test stays fast; real use should keep the defaults
+        Python::with_gil(|py| {
+            let params = Some((64, 1, 1));
+            let keypair = keypair_from_password(py, "correct horse battery staple", Message(b"alice@example".to_vec()), params)?;
+            let keypair_again = keypair_from_password(py, "correct horse battery staple", Message(b"alice@example".to_vec()), params)?;
+            let other_salt = keypair_from_password(py, "correct horse battery staple", Message(b"bob@example".to_vec()), params)?;
+
+            assert_eq!(keypair.0, keypair_again.0);
+            assert_eq!(&keypair.1[..], &keypair_again.1[..]);
+            assert_ne!(keypair.0, other_salt.0);
+            assert!(keypair_from_password(py, "password", Message(b"short".to_vec()), params).is_err());
+
+            // Parameters that would allocate terabytes are refused before any work is done
+            let err = keypair_from_password(py, "password", Message(b"alice@example".to_vec()), Some((u32::MAX, 1, 1))).err().unwrap();
+            assert!(err.to_string().contains("exceed the maximum"));
+            assert!(keypair_from_password(py, "password", Message(b"alice@example".to_vec()), Some((64, u32::MAX, 1))).is_err());
+
+            // Parameters Argon2id itself rejects are a bad argument, not a keystore failure
+            let err = keypair_from_password(py, "password", Message(b"alice@example".to_vec()), Some((0, 1, 1))).err().unwrap();
+            assert_eq!(err.into_py(py).getattr(py, "code")?.extract::<String>(py)?, "INVALID_ARGUMENT");
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_dev_keypair() -> PyResult<()> {
+        assert_eq!(dev_keypair("Alice")?.0, ALICE_PUBKEY);
+        assert_eq!(dev_keypair("Bob")?.0, hex!("8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_secret_types_zeroize_on_drop() {
+        let mut key = std::mem::ManuallyDrop::new(PrivKey(TEST_PRIVKEY));
+        assert_eq!(key.0, TEST_PRIVKEY);
+        // The storage stays valid after running the destructor, so the wiped bytes can be read
+        unsafe { std::mem::ManuallyDrop::drop(&mut key) };
+        assert_eq!(key.0, [0u8; SECRET_KEY_LENGTH]);
+
+        let mut keypair = std::mem::ManuallyDrop::new(Keypair(TEST_PUBKEY, TEST_PRIVKEY));
+        unsafe { std::mem::ManuallyDrop::drop(&mut keypair) };
+        assert_eq!(keypair.0, TEST_PUBKEY);
+        assert_eq!(keypair.1, [0u8; SECRET_KEY_LENGTH]);
+    }
+
+    #[test]
+    fn test_keypair_into_py_round_trip() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let object = Keypair(TEST_PUBKEY, TEST_PRIVKEY).into_py(py);
+            let tuple = object.as_ref(py).downcast::<PyTuple>()?;
+            assert_eq!(tuple.get_item(0).downcast::<PyBytes>()?.as_bytes(), TEST_PUBKEY);
+            assert_eq!(tuple.get_item(1).downcast::<PyBytes>()?.as_bytes(), &TEST_PRIVKEY[..]);
+
+            let keypair: Keypair = object.extract(py)?;
+            assert_eq!(keypair.0, TEST_PUBKEY);
+            assert_eq!(keypair.1, TEST_PRIVKEY);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_str_message() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let sig = sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), PyString::new(py, "tést").extract()?)?;
+            assert!(verify(Sig(sig.0), Message("tést".as_bytes().to_vec()), PubKey(TEST_PUBKEY))?);
+            assert!(verify(sig, PyString::new(py, "tést").extract()?, PubKey(TEST_PUBKEY))?);
+
+            let message: Message = PyBytes::new(py, TEST_MESSAGE).extract()?;
+            assert_eq!(message.0, TEST_MESSAGE);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_error_codes() {
+        Python::with_gil(|py| {
+            let code = |err: Option<PyErr>| -> String { err.unwrap().into_py(py).getattr(py, "code").unwrap().extract(py).unwrap() };
+            let message = || Message(Vec::from(TEST_MESSAGE));
+
+            assert_eq!(code(sign(Keypair(TEST_PUBKEY, [0xff; SECRET_KEY_LENGTH]), message()).err()), "INVALID_KEY");
+            assert_eq!(code(verify(Sig([0u8; SIGNATURE_LENGTH]), message(), PubKey(TEST_PUBKEY)).err()), "INVALID_SIGNATURE");
+            assert_eq!(code(normalize_point(PubKey([0xff; PUBLIC_KEY_LENGTH])).err()), "INVALID_POINT");
+            assert_eq!(code(verify_batch(py, vec![], vec![message()], vec![]).err()), "INVALID_LENGTH");
+            assert_eq!(code(pair_from_seed(Seed(TEST_SEED), "other").err()), "INVALID_ARGUMENT");
+        })
+    }
+
+    #[test]
+    fn test_bytes_like_arguments() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let message = PyByteArray::new(py, TEST_MESSAGE);
+            let sig = sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), message.extract()?)?;
+
+            let memoryview = |data: &[u8]| py.eval("memoryview", None, None)?.call1((PyBytes::new(py, data),));
+            let sig: Sig = memoryview(&sig.0)?.extract()?;
+            let pubkey: PubKey = memoryview(&TEST_PUBKEY)?.extract()?;
+            assert!(verify(sig, memoryview(TEST_MESSAGE)?.extract()?, pubkey)?);
+
+            let seed: Seed = PyByteArray::new(py, &TEST_SEED).extract()?;
+            assert_eq!(seed.0, TEST_SEED);
+            let secret: PrivKey = memoryview(&TEST_PRIVKEY)?.extract()?;
+            assert_eq!(secret.0, TEST_PRIVKEY);
+            assert!(PyByteArray::new(py, &[0u8; 31]).extract::<PubKey>().is_err());
+
+            // Tuple items and the fixed-size types are bytes-like too
+            let keypair: Keypair = PyTuple::new(py, [PyByteArray::new(py, &TEST_PUBKEY), PyByteArray::new(py, &TEST_PRIVKEY)]).extract()?;
+            assert!(verify(sign(keypair, Message(Vec::from(TEST_MESSAGE)))?, Message(Vec::from(TEST_MESSAGE)), PubKey(TEST_PUBKEY))?);
+            let extended: ExtendedKeypair = PyTuple::new(py, [memoryview(&TEST_CHAIN_CODE)?, memoryview(&TEST_PUBKEY)?, memoryview(&TEST_PRIVKEY)?]).extract()?;
+            assert_eq!((extended.0, extended.1), (TEST_CHAIN_CODE, TEST_PUBKEY));
+            let extended_pubkey: ExtendedPubKey = PyTuple::new(py, [memoryview(&TEST_CHAIN_CODE)?, memoryview(&TEST_PUBKEY)?]).extract()?;
+            assert_eq!(extended_pubkey.1, TEST_PUBKEY);
+            assert_eq!(PyByteArray::new(py, &[7u8; SCALAR_LENGTH]).extract::<ScalarBytes>()?.0, [7u8; SCALAR_LENGTH]);
+            assert_eq!(memoryview(&[7u8; VRF_PREOUT_LENGTH])?.extract::<VrfOutput>()?.0, [7u8; VRF_PREOUT_LENGTH]);
+            assert_eq!(PyByteArray::new(py, &[7u8; VRF_PROOF_BATCHABLE_LENGTH]).extract::<VrfProof>()?.0, [7u8; VRF_PROOF_BATCHABLE_LENGTH]);
+            assert!(PyTuple::new(py, [PyByteArray::new(py, &TEST_PUBKEY), PyByteArray::new(py, &[0u8; 63])]).extract::<Keypair>().is_err());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_keypair_class() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let keypair = PyCell::new(py, PyKeypair::new(PubKey(TEST_PUBKEY), PrivKey(TEST_PRIVKEY))?)?;
+            assert_eq!(keypair.borrow().public_key(py).as_bytes(), TEST_PUBKEY);
+            assert_eq!(keypair.borrow().secret_key(py).as_bytes(), &TEST_PRIVKEY[..]);
+            assert_eq!(keypair.borrow().public().0, TEST_PUBKEY);
+
+            let sig = keypair.borrow().sign(Message(Vec::from(TEST_MESSAGE)))?;
+            assert!(verify(sig, Message(Vec::from(TEST_MESSAGE)), PubKey(TEST_PUBKEY))?);
+
+            // Functions taking a keypair accept the class as well as a tuple
+            let extracted: Keypair = keypair.extract()?;
+            assert_eq!(extracted.0, TEST_PUBKEY);
+            assert_eq!(extracted.1, TEST_PRIVKEY);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_load_secret() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let guard = PyCell::new(py, load_secret(PrivKey(TEST_PRIVKEY))?)?;
+            let locals = PyDict::new(py);
+            locals.set_item("guard", guard)?;
+            py.run("with guard as sk:\n    sig = sk.sign(b'test', b'substrate')", None, Some(locals))?;
+
+            let sig: Sig = locals.get_item("sig").unwrap().extract()?;
+            assert!(verify(sig, Message(b"test".to_vec()), PubKey(TEST_PUBKEY))?);
+            assert_eq!(guard.borrow().secret_key, [0u8; SECRET_KEY_LENGTH]);
+            assert!(guard.borrow().sign(Message(Vec::from(TEST_MESSAGE)), Message(Vec::from(SIGNING_CTX))).is_err());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_subtract_public_points() -> PyResult<()> {
+        let sum = sum_public_points(PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY))?;
+        assert_eq!(subtract_public_points(PubKey(sum.0), PubKey(CHILD_PUBKEY))?.0, TEST_PUBKEY);
+        assert_eq!(subtract_public_points(sum, PubKey(TEST_PUBKEY))?.0, CHILD_PUBKEY);
+
+        assert!(subtract_public_points(PubKey([0xff; PUBLIC_KEY_LENGTH]), PubKey(TEST_PUBKEY)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_negate_public_point() -> PyResult<()> {
+        let negated = negate_public_point(PubKey(TEST_PUBKEY))?;
+        assert_ne!(negated.0, TEST_PUBKEY);
+        assert_eq!(sum_public_points(PubKey(TEST_PUBKEY), negated)?.0, [0u8; PUBLIC_KEY_LENGTH]);
+
+        assert!(negate_public_point(PubKey([0xff; PUBLIC_KEY_LENGTH])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_identity_public_point() -> PyResult<()> {
+        assert_eq!(identity_public_point().0, [0u8; PUBLIC_KEY_LENGTH]);
+        assert_eq!(sum_public_points(identity_public_point(), PubKey(TEST_PUBKEY))?.0, TEST_PUBKEY);
+        assert_eq!(sum_public_points(PubKey(TEST_PUBKEY), identity_public_point())?.0, TEST_PUBKEY);
+        Ok(())
+    }
+
+    #[test]
+    fn test_points_equal_or_negation() -> PyResult<()> {
+        let negated = negate_public_point(PubKey(TEST_PUBKEY))?;
+        assert!(points_equal_or_negation(PubKey(TEST_PUBKEY), PubKey(TEST_PUBKEY))?);
+        assert!(points_equal_or_negation(PubKey(TEST_PUBKEY), PubKey(negated.0))?);
+        assert!(points_equal_or_negation(negated, PubKey(TEST_PUBKEY))?);
+        assert!(!points_equal_or_negation(PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY))?);
+
+        assert!(points_equal_or_negation(PubKey([0xff; PUBLIC_KEY_LENGTH]), PubKey(TEST_PUBKEY)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_aggregate_public_keys() -> PyResult<()> {
+        let keys = || vec![PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY), PubKey(ALICE_PUBKEY)];
+        let aggregate = aggregate_public_keys(keys())?;
+
+        // Summing in either grouping gives the same point
+        let left = sum_public_points(sum_public_points(PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY))?, PubKey(ALICE_PUBKEY))?;
+        let right = sum_public_points(PubKey(TEST_PUBKEY), sum_public_points(PubKey(CHILD_PUBKEY), PubKey(ALICE_PUBKEY))?)?;
+        assert_eq!(aggregate.0, left.0);
+        assert_eq!(aggregate.0, right.0);
+        assert_eq!(aggregate_signature_r(keys())?.0, aggregate.0);
+
+        assert_eq!(aggregate_public_keys(vec![PubKey(TEST_PUBKEY)])?.0, TEST_PUBKEY);
+        assert!(aggregate_public_keys(vec![]).is_err());
+        assert!(aggregate_signature_r(vec![]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_threshold() -> PyResult<()> {
+        let keys = || vec![PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY), PubKey(ALICE_PUBKEY)];
+        let message = || Message(Vec::from(TEST_MESSAGE));
+        let possession = |public: [u8; PUBLIC_KEY_LENGTH], secret: [u8; SECRET_KEY_LENGTH]| sign(Keypair(public, secret), Message(public.to_vec()));
+        let alice = dev_keypair("Alice")?;
+        let proofs = [possession(TEST_PUBKEY, TEST_PRIVKEY)?, possession(CHILD_PUBKEY, CHILD_PRIVKEY)?, possession(ALICE_PUBKEY, alice.1)?];
+        let proofs = || proofs.iter().map(|proof| Sig(proof.0)).collect::<Vec<Sig>>();
+
+        // The first two keys sign together
+        let shared = aggregate_public_keys(vec![PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY)])?;
+        let nonces = [pair_from_seed(Seed([1u8; 32]), "ed25519")?, pair_from_seed(Seed([2u8; 32]), "ed25519")?];
+        let nonce_point = aggregate_signature_r(vec![PubKey(nonces[0].0), PubKey(nonces[1].0)])?;
+        let mut s = Scalar::zero();
+        for (secret, nonce) in [TEST_PRIVKEY, CHILD_PRIVKEY].iter().zip(nonces.iter()) {
+            let partial = multi_sign(Keypair(shared.0, *secret), message(), PubKey(nonce_point.0), PrivKey(nonce.1))?;
+            s += Scalar::from_bits(signature_components(Sig(partial.0))?.1.0);
+        }
+        let signature = signature_from_components(PubKey(nonce_point.0), ScalarBytes(s.to_bytes()))?;
+        let aggregate = |bitmap: u8| Message([&signature.0[..], &[bitmap]].concat());
+
+        assert!(verify_threshold(aggregate(0b011), message(), keys(), 2, proofs())?);
+        assert!(!verify_threshold(aggregate(0b011), message(), keys(), 3, proofs())?);
+        assert!(!verify_threshold(aggregate(0b101), message(), keys(), 2, proofs())?);
+
+        // A single signature cannot meet a threshold of two
+        let single = sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), message())?;
+        let single_aggregate = Message([&single.0[..], &[0b001]].concat());
+        assert!(verify_threshold(Message(single_aggregate.0.clone()), message(), keys(), 1, proofs())?);
+        assert!(!verify_threshold(single_aggregate, message(), keys(), 2, proofs())?);
+
+        assert!(verify_threshold(aggregate(0b1011), message(), keys(), 2, proofs()).is_err());
+        assert!(verify_threshold(aggregate(0b011), message(), keys(), 0, proofs()).is_err());
+        assert!(verify_threshold(Message(signature.0.to_vec()), message(), keys(), 2, proofs()).is_err());
+
+        // Listing a key twice would let one signer count twice
+        let duplicated = vec![PubKey(TEST_PUBKEY), PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY)];
+        let err = verify_threshold(aggregate(0b011), message(), duplicated, 2, proofs()).err().unwrap();
+        assert!(err.to_string().contains("Duplicate public key at indices 0 and 1"));
+
+        // A proof is needed for every key, and must be by that key over itself
+        assert!(verify_threshold(aggregate(0b011), message(), keys(), 2, proofs().into_iter().take(2).collect()).is_err());
+        let mut swapped = proofs();
+        swapped.swap(0, 1);
+        assert!(verify_threshold(aggregate(0b011), message(), keys(), 2, swapped).is_err());
+
+        // A rogue key `x * B - X_0` would let its owner sign alone for {0, 2} under the plain
+        // sum, but they can't prove possession of it
+        let x_public = PubKey(CHILD_PUBKEY_HARD);
+        let rogue = CompressedRistretto(CHILD_PUBKEY_HARD).decompress().unwrap() - CompressedRistretto(TEST_PUBKEY).decompress().unwrap();
+        let rogue = rogue.compress().to_bytes();
+        let rogue_keys = vec![PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY), PubKey(rogue)];
+        let forged = sign(Keypair(CHILD_PUBKEY_HARD, CHILD_PRIVKEY_HARD), message())?;
+        assert_eq!(_sum_public_keys(&[PubKey(TEST_PUBKEY), PubKey(rogue)])?.compress().to_bytes(), x_public.0);
+        assert!(verify(Sig(forged.0), message(), x_public)?);
+
+        let mut rogue_proofs = proofs();
+        rogue_proofs[2] = sign(Keypair(rogue, CHILD_PRIVKEY_HARD), Message(rogue.to_vec()))?;
+        let forged_aggregate = Message([&forged.0[..], &[0b101]].concat());
+        let err = verify_threshold(forged_aggregate, message(), rogue_keys, 2, rogue_proofs).err().unwrap();
+        assert!(err.to_string().contains("index 2: proof of possession does not verify"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_scalar_mul_public_point() -> PyResult<()> {
+        let mut two = [0u8; SCALAR_LENGTH];
+        two[0] = 2;
+        let doubled = sum_public_points(PubKey(TEST_PUBKEY), PubKey(TEST_PUBKEY))?;
+        assert_eq!(scalar_mul_public_point(PubKey(TEST_PUBKEY), ScalarBytes(two))?.0, doubled.0);
+
+        // The scalar is reduced, so l + 2 acts as 2
+        let l_plus_two = hex!("efd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010");
+        assert_eq!(scalar_mul_public_point(PubKey(TEST_PUBKEY), ScalarBytes(l_plus_two))?.0, doubled.0);
+
+        assert!(scalar_mul_public_point(PubKey([0xff; PUBLIC_KEY_LENGTH]), ScalarBytes(two)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize_point() -> PyResult<()> {
+        assert!(is_canonical_point(Message(Vec::from(TEST_PUBKEY))));
+        assert_eq!(normalize_point(PubKey(TEST_PUBKEY))?.0, TEST_PUBKEY);
+
+        // Bytes that aren't a point, such as these, are rejected
+        let blob = hex!("d9f6a4f2b0d1c5e3a7b8c9d0e1f2a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4");
+        assert!(!is_canonical_point(Message(blob.to_vec())));
+        assert!(normalize_point(PubKey(blob)).is_err());
+        assert!(!is_canonical_point(Message(Vec::from(&TEST_PUBKEY[..31]))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dleq_prove_and_verify() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let basepoint = PubKey(RISTRETTO_BASEPOINT_TABLE.basepoint().compress().to_bytes());
+            let (proof, p1, p2) = dleq_prove(py, PrivKey(TEST_PRIVKEY), PubKey(basepoint.0), PubKey(CHILD_PUBKEY))?;
+            assert_eq!(p1.0, TEST_PUBKEY);
+            let proof = proof.as_bytes().to_vec();
+            let check = |proof: &[u8], p2: &PubKey| dleq_verify(
+                Message(proof.to_vec()), PubKey(TEST_PUBKEY), PubKey(basepoint.0), PubKey(p2.0), PubKey(CHILD_PUBKEY));
+            assert!(check(&proof, &p2)?);
+
+            // A tampered proof, or a second point with another discrete log, fails
+            let mut tampered = proof.clone();
+            tampered[SCALAR_LENGTH] ^= 1;
+            assert!(!check(&tampered, &p2)?);
+            let other = sum_public_points(PubKey(p2.0), PubKey(basepoint.0))?;
+            assert!(!check(&proof, &other)?);
+
+            let identity = PubKey([0u8; PUBLIC_KEY_LENGTH]);
+            assert!(dleq_prove(py, PrivKey(TEST_PRIVKEY), identity, PubKey(CHILD_PUBKEY)).is_err());
+            assert!(check(&proof[..63], &p2).is_err());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_multi_sign() -> PyResult<()> {
+        let shared = sum_public_points(PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY))?;
+        let nonces = [pair_from_seed(Seed([1u8; 32]), "ed25519")?, pair_from_seed(Seed([2u8; 32]), "ed25519")?];
+        let nonce_point = sum_public_points(PubKey(nonces[0].0), PubKey(nonces[1].0))?;
+
+        let partials = [
+            multi_sign(Keypair(shared.0, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)), PubKey(nonce_point.0), PrivKey(nonces[0].1))?,
+            multi_sign(Keypair(shared.0, CHILD_PRIVKEY), Message(Vec::from(TEST_MESSAGE)), PubKey(nonce_point.0), PrivKey(nonces[1].1))?,
+        ];
+
+        // Every party commits to the same R, and the aggregate s is the sum of their s values
+        let mut s = Scalar::zero();
+        for partial in partials.iter() {
+            assert!(Signature::from_bytes(&partial.0).is_ok());
+            assert_eq!(&partial.0[..32], &nonce_point.0[..]);
+            let mut s_bytes = [0u8; 32];
+            s_bytes.copy_from_slice(&partial.0[32..]);
+            s_bytes[31] &= 127;
+            s += Scalar::from_canonical_bytes(s_bytes).unwrap();
+        }
+        let mut aggregate = [0u8; SIGNATURE_LENGTH];
+        aggregate[..32].copy_from_slice(&nonce_point.0);
+        aggregate[32..].copy_from_slice(s.as_bytes());
+        aggregate[63] |= 128;
+
+        assert!(verify(Sig(aggregate), Message(Vec::from(TEST_MESSAGE)), shared)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_signer_class() -> PyResult<()> {
+        let signer = Signer::new(Keypair(TEST_PUBKEY, TEST_PRIVKEY))?;
+        let messages: Vec<Vec<u8>> = (0..100).map(|i| format!("message {}", i).into_bytes()).collect();
+
+        let signatures: Vec<Sig> = messages.iter().map(|message| signer.sign(Message(message.clone()))).collect();
+
+        for (sig, message) in signatures.into_iter().zip(messages) {
+            assert!(verify(sig, Message(message), PubKey(TEST_PUBKEY))?);
+        }
+        assert!(Signer::new(Keypair(TEST_PUBKEY, [0xff; SECRET_KEY_LENGTH])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verifier_class() -> PyResult<()> {
+        let verifier = Verifier::new(PubKey(TEST_PUBKEY))?;
+        for i in 0..10 {
+            let message = format!("message {}", i).into_bytes();
+            let sig = sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(message.clone()))?;
+            assert!(verifier.verify(Sig(sig.0), Message(message))?);
+            assert!(!verifier.verify(sig, Message(b"other".to_vec()))?);
+        }
+
+        assert!(Verifier::new(PubKey([0xff; PUBLIC_KEY_LENGTH])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_signing_context_class() -> PyResult<()> {
+        let context = PySigningContext::new(Message(b"x".to_vec()));
+        let sig = context.sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)))?;
+        assert!(context.verify(Sig(sig.0), Message(Vec::from(TEST_MESSAGE)), PubKey(TEST_PUBKEY))?);
+
+        let other = PySigningContext::new(Message(b"y".to_vec()));
+        assert!(!other.verify(Sig(sig.0), Message(Vec::from(TEST_MESSAGE)), PubKey(TEST_PUBKEY))?);
+
+        // The substrate context matches the plain functions
+        let substrate = PySigningContext::new(Message(SIGNING_CTX.to_vec()));
+        let sig = sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)))?;
+        assert!(substrate.verify(sig, Message(Vec::from(TEST_MESSAGE)), PubKey(TEST_PUBKEY))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_musig_class() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let keys = [(TEST_PUBKEY, TEST_PRIVKEY), (CHILD_PUBKEY, CHILD_PRIVKEY)];
+            let mut sessions: Vec<PyMuSig> = keys.iter()
+                .map(|(public, secret)| PyMuSig::new(Keypair(*public, *secret), Message(Vec::from(TEST_MESSAGE))).unwrap())
+                .collect();
+            let bytes = |value: &PyBytes| Message(value.as_bytes().to_vec());
+
+            // Each round, every signer adds the other's value
+            let commitments: Vec<Message> = sessions.iter().map(|s| bytes(s.commitment(py).unwrap())).collect();
+            sessions[0].add_their_commitment(PubKey(CHILD_PUBKEY), Message(commitments[1].0.clone()))?;
+            sessions[1].add_their_commitment(PubKey(TEST_PUBKEY), Message(commitments[0].0.clone()))?;
+            let reveals: Vec<Message> = sessions.iter_mut().map(|s| bytes(s.reveal(py).unwrap())).collect();
+            sessions[0].add_their_reveal(PubKey(CHILD_PUBKEY), Message(reveals[1].0.clone()))?;
+            sessions[1].add_their_reveal(PubKey(TEST_PUBKEY), Message(reveals[0].0.clone()))?;
+            let cosignatures: Vec<Message> = sessions.iter_mut().map(|s| bytes(s.cosign(py).unwrap())).collect();
+            assert!(sessions[0].signature().is_err());
+            sessions[0].add_their_cosignature(PubKey(CHILD_PUBKEY), Message(cosignatures[1].0.clone()))?;
+            sessions[1].add_their_cosignature(PubKey(TEST_PUBKEY), Message(cosignatures[0].0.clone()))?;
+
+            let signature = sessions[0].signature()?;
+            assert_eq!(signature.0, sessions[1].signature()?.0);
+            let aggregate = sessions[0].public_key()?;
+            assert!(verify(signature, Message(Vec::from(TEST_MESSAGE)), aggregate)?);
+
+            // Steps out of order are rejected without losing the session
+            assert!(sessions[0].reveal(py).is_err());
+            assert!(sessions[0].public_key().is_ok());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_public_key_edwards_round_trip() -> PyResult<()> {
+        // The public key x * B maps to x * B for the Ed25519 basepoint
+        let x = _secret_scalar(&SecretKey::from_bytes(&TEST_PRIVKEY).unwrap());
+        let expected = (&x * &curve25519_dalek::constants::ED25519_BASEPOINT_TABLE).compress();
+        let edwards = public_key_to_edwards_y(PubKey(TEST_PUBKEY))?;
+        assert_eq!(edwards.0, expected.to_bytes());
+        assert_eq!(public_key_from_edwards_y(edwards)?.0, TEST_PUBKEY);
+
+        for pubkey in [CHILD_PUBKEY, CHILD_PUBKEY_HARD, ALICE_PUBKEY, [0u8; PUBLIC_KEY_LENGTH]] {
+            assert_eq!(public_key_from_edwards_y(public_key_to_edwards_y(PubKey(pubkey))?)?.0, pubkey);
+        }
+
+        // A point with a torsion component has no Ristretto equivalent
+        let torsioned = (expected.decompress().unwrap() + EIGHT_TORSION[1]).compress();
+        assert!(public_key_from_edwards_y(PubKey(torsioned.to_bytes())).is_err());
+        assert!(public_key_to_edwards_y(PubKey([0xff; PUBLIC_KEY_LENGTH])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_path() -> PyResult<()> {
+        let root = pair_from_mnemonic(DEV_PHRASE, "")?;
+        let root = || ExtendedKeypair(TEST_CHAIN_CODE, root.0, root.1);
+        assert_eq!(derive_path(root(), "//Alice")?.1, ALICE_PUBKEY);
+
+        // Junctions are applied one after the other
+        let alice = derive_path(root(), "//Alice")?;
+        let stash = derive_path(root(), "//Alice/stash//0")?;
+        let stepwise = derive_path(derive_path(alice, "/stash")?, "//0")?;
+        assert_eq!(stash.1, stepwise.1);
+        assert_ne!(stash.1, derive_path(root(), "//Alice/stash/0")?.1);
+
+        assert_eq!(derive_path(root(), "")?.1, root().1);
+        assert!(derive_path(root(), "Alice").is_err());
+        assert!(derive_path(root(), "//Alice//").is_err());
+        assert!(derive_path(root(), "///password").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_path_pubkey() -> PyResult<()> {
+        let alice = derive_path(ExtendedKeypair(TEST_CHAIN_CODE, TEST_PUBKEY, TEST_PRIVKEY), "//Alice")?;
+        let alice_pub = ExtendedPubKey(alice.0, alice.1);
+
+        // A two-segment soft path gives the same public key as deriving with the secret
+        let derived = derive_path_pubkey(ExtendedPubKey(alice_pub.0, alice_pub.1), "/stash/0")?;
+        let derived_priv = derive_path(alice, "/stash/0")?;
+        assert_eq!(derived.1, derived_priv.1);
+        assert_eq!(derived.0, derived_priv.0);
 
-    let R_point = match PublicKey::from_bytes(&R_compressed.0) {
-        Ok(some_pk) => some_pk,
-        Err(err) => return Err(exceptions::ValueError::py_err(format!("Invalid public key: {}", err.to_string()))),
-    };
+        assert!(derive_path_pubkey(alice_pub, "/stash//0").is_err());
+        Ok(())
+    }
 
-    let context = signing_context(SIGNING_CTX);
-    inner_raw_sign(secret, context.bytes(&message.0), R_point, public, k_scalar)
+    #[test]
+    fn test_sign_and_verify() -> PyResult<()> {
+        let signer_keypair = Keypair(TEST_PUBKEY, TEST_PRIVKEY);
+        let signer_pubkey = PubKey(TEST_PUBKEY);
 
-}
+        let test_message = Message(Vec::from(TEST_MESSAGE));
+        let test_message_copy = Message(Vec::from(TEST_MESSAGE));
 
-pub fn concat_u8(first: &[u8], second: &[u8]) -> Vec<u8> {
-    [first, second].concat()
-}
+        let signature = sign(signer_keypair, test_message)?;
+        let is_good = verify(signature, test_message_copy, signer_pubkey)?;
+        assert!(is_good);
+        Ok(())
+    }
 
-pub fn inner_raw_sign<T: SigningTranscript>(secret: SecretKey, mut t: T, R_point: PublicKey, public: PublicKey, k: SecretKey) ->  PyResult<Sig>
-{
-    t.proto_name(b"Schnorr-sig");
-    t.commit_point(b"sign:pk",public.as_compressed());
+    #[test]
+    fn test_verify_with_keypair() -> PyResult<()> {
+        let sig = sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)))?;
+        for message in [TEST_MESSAGE, b"other"] {
+            assert_eq!(
+                verify_with_keypair(Sig(sig.0), Message(message.to_vec()), Keypair(TEST_PUBKEY, TEST_PRIVKEY))?,
+                verify(Sig(sig.0), Message(message.to_vec()), PubKey(TEST_PUBKEY))?
+            );
+        }
+        assert!(verify_with_keypair(Sig(sig.0), Message(Vec::from(TEST_MESSAGE)), Keypair(TEST_PUBKEY, TEST_PRIVKEY))?);
+        assert!(!verify_with_keypair(sig, Message(Vec::from(TEST_MESSAGE)), Keypair(CHILD_PUBKEY, CHILD_PRIVKEY))?);
+        Ok(())
+    }
 
-    let R = R_point.as_compressed();
-    t.commit_point(b"sign:R",&R);
+    #[test]
+    fn test_sign_wrapped() -> PyResult<()> {
+        let message = || Message(Vec::from(TEST_MESSAGE));
+        let sig = sign_wrapped(Keypair(TEST_PUBKEY, TEST_PRIVKEY), message())?;
+        assert!(verify_wrapped(Sig(sig.0), message(), PubKey(TEST_PUBKEY))?);
+        assert!(!verify(Sig(sig.0), message(), PubKey(TEST_PUBKEY))?);
 
-    let e = t.challenge_scalar(b"sign:c");  // context, message, A/public_key, R=rG
+        // The wrapped message is what is actually signed, and is not wrapped twice
+        let wrapped = || Message([&b"<Bytes>"[..], TEST_MESSAGE, b"</Bytes>"].concat());
+        assert!(verify(Sig(sig.0), wrapped(), PubKey(TEST_PUBKEY))?);
+        assert!(verify_wrapped(Sig(sig.0), wrapped(), PubKey(TEST_PUBKEY))?);
 
-    let mut num1 = [0u8; 32];
-    let mut num2 = [0u8; 32];
-    let mut num3 = [0u8; 32];
-    num1.clone_from_slice(&e.to_bytes()[0..32]);
-    num2.clone_from_slice(&secret.to_bytes()[0..32]);
-    num3.clone_from_slice(&k.to_bytes()[0..32]);
-    let n1 = Scalar::from_bytes_mod_order(num1);
+        let raw = sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), message())?;
+        assert!(!verify_wrapped(raw, message(), PubKey(TEST_PUBKEY))?);
+        Ok(())
+    }
 
-    let n2 = Scalar::from_bytes_mod_order(num2);
+    #[test]
+    fn test_sign_encoded() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let signed = |encoding: &str| sign_encoded(py, Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)), encoding);
+            let verifies = |sig: Vec<u8>| -> PyResult<bool> {
+                let mut bytes = [0u8; SIGNATURE_LENGTH];
+                bytes.copy_from_slice(&sig);
+                verify(Sig(bytes), Message(Vec::from(TEST_MESSAGE)), PubKey(TEST_PUBKEY))
+            };
 
-    let n3 = Scalar::from_bytes_mod_order(num3);
+            let raw: Vec<u8> = signed("bytes")?.extract(py)?;
+            assert!(verifies(raw)?);
+            let hex_sig: String = signed("hex")?.extract(py)?;
+            assert!(verifies(hex::decode(hex_sig.strip_prefix("0x").unwrap()).unwrap())?);
+            let base58_sig: String = signed("base58")?.extract(py)?;
+            assert!(verifies(bs58::decode(base58_sig).into_vec().unwrap())?);
 
-    let s = &(&n1 * &n2) + &n3;
+            assert!(signed("base64").is_err());
+            Ok(())
+        })
+    }
 
-    let sbb = concat_u8(&R.as_bytes()[..], &s.as_bytes()[..]);
-    let sb = sbb.as_slice();
-    let sig_byte_arrays = [sb[0], sb[1], sb[2], sb[3], sb[4], sb[5], sb[6], sb[7], sb[8], sb[9],
-                           sb[10], sb[11], sb[12], sb[13], sb[14], sb[15], sb[16], sb[17], sb[18], sb[19],
-                           sb[20], sb[21], sb[22], sb[23], sb[24], sb[25], sb[26], sb[27], sb[28], sb[29],
-                           sb[30], sb[31], sb[32], sb[33], sb[34], sb[35], sb[36], sb[37], sb[38], sb[39],
-                           sb[40], sb[41], sb[42], sb[43], sb[44], sb[45], sb[46], sb[47], sb[48], sb[49],
-                           sb[50], sb[51], sb[52], sb[53], sb[54], sb[55], sb[56], sb[57], sb[58], sb[59],
-                           sb[60], sb[61], sb[62], sb[63]];
-    Ok(Sig(sig_byte_arrays))
-}
+    #[test]
+    fn test_sign_checked() -> PyResult<()> {
+        let signature = sign_checked(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)))?;
+        assert!(verify(signature, Message(Vec::from(TEST_MESSAGE)), PubKey(TEST_PUBKEY))?);
 
-// Convert Keypair object to a Python Keypair tuple
-impl IntoPy<PyObject> for Keypair {
-    fn into_py(self, py: Python) -> PyObject {
-        let secret = PyBytes::new(py, &self.0);
-        let public = PyBytes::new(py, &self.1);
-        PyTuple::new(py, vec![secret, public]).into_py(py)
+        // A public key from another keypair fails the self-check
+        Python::with_gil(|py| {
+            let err = sign_checked(Keypair(CHILD_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE))).err().unwrap();
+            let code: String = err.into_py(py).getattr(py, "code").unwrap().extract(py).unwrap();
+            assert_eq!(code, "SELF_CHECK_FAILED");
+        });
+        Ok(())
     }
-}
 
-// Convert Python Keypair into Rust
-impl<'a> FromPyObject<'a> for Keypair {
-    fn extract(obj: &'a PyAny) -> PyResult<Self> {
-        let keypair = obj
-            .downcast::<PyTuple>()
-            .map_err(|_| exceptions::PyTypeError::new_err("Invalid Keypair: expected a tuple"))?;
-        if keypair.len() < 2 {
-            return Err(exceptions::PyIndexError::new_err(format!("Expected tuple of size 2, got {}", keypair.len())));
+    #[test]
+    fn test_sign_and_verify_prehashed() -> PyResult<()> {
+        let message = vec![0x5a; 1 << 20];
+        let digest = _blake2b_256(&message);
+        let sig = sign_prehashed(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(digest.to_vec()))?;
+        assert!(verify_prehashed(Sig(sig.0), Message(digest.to_vec()), PubKey(TEST_PUBKEY))?);
+
+        // The digest goes into the transcript exactly as with schnorrkel's `hash256`, which
+        // takes any 32-byte digest
+        let mut sha256 = <sha2::Sha256 as sha2::Digest>::new();
+        sha2::Digest::update(&mut sha256, &message);
+        let sha_digest = sha2::Digest::finalize(sha256.clone());
+        let sha_sig = sign_prehashed(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(sha_digest.to_vec()))?;
+        let pk = PublicKey::from_bytes(&TEST_PUBKEY).unwrap();
+        let t = signing_context(SIGNING_CTX).hash256(sha256);
+        assert!(pk.verify(t, &Signature::from_bytes(&sha_sig.0).unwrap()).is_ok());
+
+        // It is not a signature over the digest bytes themselves
+        assert!(!verify(Sig(sig.0), Message(digest.to_vec()), PubKey(TEST_PUBKEY))?);
+        assert!(!verify_prehashed(Sig(sig.0), Message(vec![0u8; 32]), PubKey(TEST_PUBKEY))?);
+        assert!(sign_prehashed(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(vec![0u8; 31])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_rejects_non_canonical_signatures() -> PyResult<()> {
+        let message = || Message(Vec::from(TEST_MESSAGE));
+        let signature = sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), message())?;
+
+        // Without the marker bit
+        let mut unmarked = signature.0;
+        unmarked[63] &= 127;
+        assert!(verify(Sig(unmarked), message(), PubKey(TEST_PUBKEY)).is_err());
+
+        // With s replaced by s + l, which is the same scalar but not reduced
+        let l = hex!("edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010");
+        let mut malleated = signature.0;
+        malleated[63] &= 127;
+        let mut carry = 0u16;
+        for i in 0..SCALAR_LENGTH {
+            let digit = malleated[32 + i] as u16 + l[i] as u16 + carry;
+            malleated[32 + i] = digit as u8;
+            carry = digit >> 8;
         }
+        malleated[63] |= 128;
+        assert!(verify(Sig(malleated), message(), PubKey(TEST_PUBKEY)).is_err());
 
-        // Convert bytes to fixed width arrays
-        let mut public: [u8; PUBLIC_KEY_LENGTH] = [0u8; PUBLIC_KEY_LENGTH];
-        let mut private: [u8; SECRET_KEY_LENGTH] = [0u8; SECRET_KEY_LENGTH];
-        public.clone_from_slice(
-            &keypair.get_item(0)
-                    .downcast::<PyBytes>()
-                    .map_err(|_| exceptions::PyTypeError::new_err("Invalid PubKey: expected a python Bytes object"))
-                    .and_then(|b| _check_pybytes_len(b, PUBLIC_KEY_LENGTH))?
-                    .as_bytes()[0..PUBLIC_KEY_LENGTH]);
-        private.clone_from_slice(
-            &keypair.get_item(1)
-                    .downcast::<PyBytes>()
-                    .map_err(|_| exceptions::PyTypeError::new_err("Invalid SecretKey: Expected a python Bytes object"))
-                    .and_then(|b| _check_pybytes_len(b, SECRET_KEY_LENGTH))?
-                    .as_bytes()[0..SECRET_KEY_LENGTH]);
-        let keypair = Keypair(public, private);
-        Ok(keypair)
+        // With the unused top bit of R set
+        let mut bad_r = signature.0;
+        bad_r[31] |= 128;
+        assert!(!verify(Sig(bad_r), message(), PubKey(TEST_PUBKEY))?);
+
+        assert!(verify(signature, message(), PubKey(TEST_PUBKEY))?);
+        Ok(())
     }
-}
 
-// Convert Sig struct to a PyObject
-impl IntoPy<PyObject> for Sig {
-    fn into_py(self, py: Python) -> PyObject {
-        let sig = PyBytes::new(py, &self.0);
-        sig.into_py(py)
+    #[test]
+    fn test_quick_reject() -> PyResult<()> {
+        let message = || Message(Vec::from(TEST_MESSAGE));
+        let sig = sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), message())?;
+
+        // A real signature over another message passes the filter, and only verify rejects it
+        assert!(!quick_reject(Sig(sig.0), Message(b"other".to_vec()), PubKey(TEST_PUBKEY)));
+        assert!(!verify(Sig(sig.0), Message(b"other".to_vec()), PubKey(TEST_PUBKEY))?);
+
+        assert!(quick_reject(Sig([0xff; SIGNATURE_LENGTH]), message(), PubKey(TEST_PUBKEY)));
+        assert!(quick_reject(Sig(sig.0), message(), PubKey([0xff; PUBLIC_KEY_LENGTH])));
+        let mut bad_r = sig.0;
+        bad_r[31] |= 128;
+        assert!(quick_reject(Sig(bad_r), message(), PubKey(TEST_PUBKEY)));
+        Ok(())
     }
-}
 
-// Convert a PyBytes object of size 64 to a Sig object
-impl<'a> FromPyObject<'a> for Sig {
-    fn extract(obj: &'a PyAny) -> PyResult<Self> {
-        let signature = obj
-            .downcast::<PyBytes>()
-            .map_err(|_| exceptions::PyTypeError::new_err(format!("Expected {} byte signature", SIGNATURE_LENGTH)))
-            .and_then(|b| _check_pybytes_len(b, SIGNATURE_LENGTH))?;
+    #[test]
+    fn test_verify_strict() -> PyResult<()> {
+        let sig = sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)))?;
+        assert!(verify_strict(Sig(sig.0), Message(Vec::from(TEST_MESSAGE)), PubKey(TEST_PUBKEY))?);
+        assert!(!verify_strict(Sig(sig.0), Message(b"other".to_vec()), PubKey(TEST_PUBKEY))?);
 
-        // Convert bytes to fixed width array
-        let mut fixed: [u8; SIGNATURE_LENGTH] = [0u8; SIGNATURE_LENGTH];
-        fixed.clone_from_slice(signature.as_bytes());
-        Ok(Sig(fixed))
+        // `verify` just returns false for the identity, where the strict variant raises
+        let identity = [0u8; PUBLIC_KEY_LENGTH];
+        assert!(!verify(Sig(sig.0), Message(Vec::from(TEST_MESSAGE)), PubKey(identity))?);
+        assert!(verify_strict(Sig(sig.0), Message(Vec::from(TEST_MESSAGE)), PubKey(identity)).is_err());
+        let err = verify_strict(Sig(sig.0), Message(Vec::from(TEST_MESSAGE)), PubKey(identity)).err().unwrap();
+        assert!(err.to_string().contains("small order point"));
+
+        // The other Edwards small-order points are not Ristretto encodings at all
+        for point in small_order_points().into_iter().filter(|point| point.0 != identity) {
+            let err = verify_strict(Sig(sig.0), Message(Vec::from(TEST_MESSAGE)), point).err().unwrap();
+            assert!(!err.to_string().contains("small order point"));
+        }
+        Ok(())
     }
-}
 
-// Convert a PyBytes object into a Seed
-impl<'a> FromPyObject<'a> for Seed {
-    fn extract(obj: &'a PyAny) -> PyResult<Self> {
-        let seed = obj
-            .downcast::<PyBytes>()
-            .map_err(|_| PyErr::new::<exceptions::PyTypeError, _>("Expected a bytestring"))?;
+    #[test]
+    fn test_verify_compat() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let message = || Message(Vec::from(TEST_MESSAGE));
+            let failure = |result: PyResult<bool>| -> (String, String) {
+                let err = result.err().unwrap().into_py(py);
+                (err.getattr(py, "code").unwrap().extract(py).unwrap(), err.as_ref(py).str().unwrap().to_string())
+            };
+            let sig = sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), message())?;
 
-        if seed.as_bytes().len() != MINI_SECRET_KEY_LENGTH {
-            return Err(PyErr::new::<exceptions::PyIndexError, _>(
-                format!("Expected seed with length: {}", MINI_SECRET_KEY_LENGTH),
-            ));
+            assert!(verify_compat(Sig(sig.0), message(), PubKey(TEST_PUBKEY), Message(SIGNING_CTX.to_vec()))?);
+
+            let (code, reason) = failure(verify_compat(Sig(sig.0), message(), PubKey(TEST_PUBKEY), Message(b"other".to_vec())));
+            assert_eq!(code, "CONTEXT_MISMATCH");
+            assert!(reason.contains("substrate context"));
+
+            let (code, reason) = failure(verify_compat(Sig(sig.0), Message(b"other".to_vec()), PubKey(TEST_PUBKEY), Message(SIGNING_CTX.to_vec())));
+            assert_eq!(code, "INVALID_SIGNATURE");
+            assert!(reason.contains("public key decompressed"));
+
+            let (code, _) = failure(verify_compat(Sig(sig.0), message(), PubKey([0xff; PUBLIC_KEY_LENGTH]), Message(SIGNING_CTX.to_vec())));
+            assert_eq!(code, "INVALID_KEY");
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_sign_and_verify_hex() -> PyResult<()> {
+        let public_hex = format!("0x{}", hex::encode(TEST_PUBKEY));
+        let secret_hex = hex::encode(TEST_PRIVKEY);
+        let message_hex = hex::encode(TEST_MESSAGE);
+
+        let signature_hex = sign_hex((&public_hex, &secret_hex), &message_hex)?;
+        assert!(signature_hex.starts_with("0x"));
+        assert!(verify_hex(&signature_hex, &format!("0x{}", message_hex), &public_hex)?);
+
+        // The hex API works on the same bytes as the raw one
+        let signature = hex::decode(&signature_hex[2..]).unwrap();
+        let mut sig = [0u8; SIGNATURE_LENGTH];
+        sig.copy_from_slice(&signature);
+        assert!(verify(Sig(sig), Message(Vec::from(TEST_MESSAGE)), PubKey(TEST_PUBKEY))?);
+        let raw = sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)))?;
+        assert!(verify_hex(&hex::encode(raw.0), &message_hex, &public_hex)?);
+        assert!(!verify_hex(&signature_hex, "00", &public_hex)?);
+
+        assert!(verify_hex(&signature_hex, "0x123", &public_hex).is_err());
+        assert!(verify_hex(&signature_hex, "zz", &public_hex).is_err());
+        assert!(sign_hex((&public_hex[..10], &secret_hex), &message_hex).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_key_from_hex() {
+        Python::with_gil(|py| {
+            for prefix in ["", "0x"] {
+                let public = public_key_from_hex(py, &format!("{}{}", prefix, hex::encode(TEST_PUBKEY))).unwrap();
+                assert_eq!(public.as_bytes(), &TEST_PUBKEY[..]);
+                let secret = secret_key_from_hex(py, &format!("{}{}", prefix, hex::encode(TEST_PRIVKEY))).unwrap();
+                assert_eq!(secret.as_bytes(), &TEST_PRIVKEY[..]);
+            }
+
+            // Each checks its own key length
+            assert!(public_key_from_hex(py, &hex::encode(TEST_PRIVKEY)).is_err());
+            assert!(secret_key_from_hex(py, &hex::encode(TEST_PUBKEY)).is_err());
+            assert!(public_key_from_hex(py, "0xzz").is_err());
+            assert!(secret_key_from_hex(py, "0x123").is_err());
+        });
+    }
+
+    #[test]
+    fn test_sign_and_verify_transcript() -> PyResult<()> {
+        let items = || vec![
+            (Message(b"recipient".to_vec()), Message(Vec::from(CHILD_PUBKEY))),
+            (Message(b"amount".to_vec()), Message(1000u64.to_le_bytes().to_vec())),
+        ];
+        let sig = sign_transcript(Keypair(TEST_PUBKEY, TEST_PRIVKEY), items())?;
+        assert!(verify_transcript(Sig(sig.0), items(), PubKey(TEST_PUBKEY))?);
+
+        // Changing, reordering or relabelling an item invalidates the signature
+        let mut changed = items();
+        changed[1].1 = Message(1001u64.to_le_bytes().to_vec());
+        assert!(!verify_transcript(Sig(sig.0), changed, PubKey(TEST_PUBKEY))?);
+        let mut reordered = items();
+        reordered.reverse();
+        assert!(!verify_transcript(Sig(sig.0), reordered, PubKey(TEST_PUBKEY))?);
+        let mut relabelled = items();
+        relabelled[0].0 = Message(b"sender".to_vec());
+        assert!(!verify_transcript(Sig(sig.0), relabelled, PubKey(TEST_PUBKEY))?);
+        assert!(!verify_transcript(sig, items(), PubKey(CHILD_PUBKEY))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_assemble_signature() -> PyResult<()> {
+        let signature = sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)))?;
+        let mut r = [0u8; PUBLIC_KEY_LENGTH];
+        let mut s = [0u8; SCALAR_LENGTH];
+        r.copy_from_slice(&signature.0[..32]);
+        s.copy_from_slice(&signature.0[32..]);
+        s[31] &= 127;
+
+        let assembled = assemble_signature(PubKey(r), ScalarBytes(s), PubKey(TEST_PUBKEY), Message(Vec::from(TEST_MESSAGE)), Message(Vec::from(SIGNING_CTX)))?;
+        assert_eq!(&assembled.0[..], &signature.0[..]);
+
+        // Mismatched message, public key and context are all rejected
+        assert!(assemble_signature(PubKey(r), ScalarBytes(s), PubKey(TEST_PUBKEY), Message(b"other".to_vec()), Message(Vec::from(SIGNING_CTX))).is_err());
+        assert!(assemble_signature(PubKey(r), ScalarBytes(s), PubKey(CHILD_PUBKEY), Message(Vec::from(TEST_MESSAGE)), Message(Vec::from(SIGNING_CTX))).is_err());
+        assert!(assemble_signature(PubKey(r), ScalarBytes(s), PubKey(TEST_PUBKEY), Message(Vec::from(TEST_MESSAGE)), Message(b"other".to_vec())).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_signature_components() -> PyResult<()> {
+        let signature = sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)))?;
+        let (r, s) = signature_components(Sig(signature.0))?;
+        assert_eq!(&r.0[..], &signature.0[..32]);
+        assert!(Scalar::from_canonical_bytes(s.0).is_some());
+
+        let reassembled = signature_from_components(r, s)?;
+        assert_eq!(&reassembled.0[..], &signature.0[..]);
+
+        // A signature without the marker bit, and a non-canonical s, are rejected
+        let mut unmarked = signature.0;
+        unmarked[63] &= 127;
+        assert!(signature_components(Sig(unmarked)).is_err());
+        assert!(signature_from_components(PubKey(TEST_PUBKEY), ScalarBytes([0xff; SCALAR_LENGTH])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sign_deterministic() -> PyResult<()> {
+        let signature = sign_deterministic(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)))?;
+        let signature_again = sign_deterministic(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)))?;
+
+        assert_eq!(&signature.0[..], &signature_again.0[..]);
+        assert!(verify(signature, Message(Vec::from(TEST_MESSAGE)), PubKey(TEST_PUBKEY))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sign_with_rng_seed() -> PyResult<()> {
+        let sign_seeded = |seed: [u8; 32]| sign_with_rng_seed(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)), Message(seed.to_vec()));
+        let signature = sign_seeded([1u8; 32])?;
+
+        assert_eq!(&signature.0[..], &sign_seeded([1u8; 32])?.0[..]);
+        assert_ne!(&signature.0[..], &sign_seeded([2u8; 32])?.0[..]);
+        assert!(verify(signature, Message(Vec::from(TEST_MESSAGE)), PubKey(TEST_PUBKEY))?);
+
+        // The all-zero seed is what sign_deterministic uses
+        let deterministic = sign_deterministic(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)))?;
+        assert_eq!(&sign_seeded([0u8; 32])?.0[..], &deterministic.0[..]);
+
+        assert!(sign_with_rng_seed(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)), Message(vec![0u8; 31])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sign_rfc6979() -> PyResult<()> {
+        let signature = sign_rfc6979(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)), Message(Vec::from(SIGNING_CTX)))?;
+        let signature_again = sign_rfc6979(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)), Message(Vec::from(SIGNING_CTX)))?;
+        let other_context = sign_rfc6979(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)), Message(b"other".to_vec()))?;
+
+        assert_eq!(&signature.0[..], &signature_again.0[..]);
+        assert_ne!(&signature.0[..32], &other_context.0[..32]);
+        assert!(verify(signature, Message(Vec::from(TEST_MESSAGE)), PubKey(TEST_PUBKEY))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sign_multi_context() -> PyResult<()> {
+        let contexts = vec![Message(b"substrate".to_vec()), Message(b"alpha".to_vec()), Message(b"beta".to_vec())];
+        let signatures = sign_multi_context(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)), contexts)?;
+        assert_eq!(signatures.len(), 3);
+
+        // Each signature verifies only under its own context
+        let pk = PublicKey::from_bytes(&TEST_PUBKEY).unwrap();
+        let contexts: [&[u8]; 3] = [b"substrate", b"alpha", b"beta"];
+        for (index, sig) in signatures.iter().enumerate() {
+            let sig = Signature::from_bytes(&sig.0).unwrap();
+            for (other, context) in contexts.iter().enumerate() {
+                assert_eq!(pk.verify_simple(context, TEST_MESSAGE, &sig).is_ok(), index == other);
+            }
         }
 
-        // Convert bytes to fixed width array
-        let mut fixed: [u8; MINI_SECRET_KEY_LENGTH] = Default::default();
-        fixed.copy_from_slice(seed.as_bytes());
-        Ok(Seed(fixed))
+        assert!(sign_multi_context(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)), vec![]).is_err());
+        Ok(())
     }
-}
 
-// Convert PubKey struct to a PyObject
-impl IntoPy<PyObject> for PubKey {
-    fn into_py(self, py: Python) -> PyObject {
-        let key = PyBytes::new(py, &self.0);
-        key.into_py(py)
+    #[test]
+    fn test_resign_under_context() -> PyResult<()> {
+        let (old_sig, new_sig) = resign_under_context(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)), Message(b"old".to_vec()), Message(b"new".to_vec()))?;
+        let pk = PublicKey::from_bytes(&TEST_PUBKEY).unwrap();
+        let old_sig = Signature::from_bytes(&old_sig.0).unwrap();
+        let new_sig = Signature::from_bytes(&new_sig.0).unwrap();
+
+        assert!(pk.verify_simple(b"old", TEST_MESSAGE, &old_sig).is_ok());
+        assert!(pk.verify_simple(b"new", TEST_MESSAGE, &new_sig).is_ok());
+        assert!(pk.verify_simple(b"old", TEST_MESSAGE, &new_sig).is_err());
+
+        // A keypair whose halves don't belong together can't produce the old signature
+        assert!(resign_under_context(Keypair(CHILD_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)), Message(b"old".to_vec()), Message(b"new".to_vec())).is_err());
+        Ok(())
     }
-}
 
-// Convert a PyBytes object of size 32 to a PublicKey struct
-impl<'a> FromPyObject<'a> for PubKey {
-    fn extract(obj: &'a PyAny) -> PyResult<Self> {
-        let pubkey = obj
-            .downcast::<PyBytes>()
-            .map_err(|_| exceptions::PyTypeError::new_err("Invalid PubKey, expected bytes object"))
-            .and_then(|b| _check_pybytes_len(b, PUBLIC_KEY_LENGTH))?;
+    #[test]
+    fn test_sign_session() -> PyResult<()> {
+        let context = || Message(b"login".to_vec());
+        let signature = sign_session(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)), Message(b"session-1".to_vec()), context())?;
 
-        // Convert bytes to fixed width array
-        let mut fixed: [u8; PUBLIC_KEY_LENGTH] = Default::default();
-        fixed.clone_from_slice(pubkey.as_bytes());
-        Ok(PubKey(fixed))
+        assert!(verify_session(Sig(signature.0), Message(Vec::from(TEST_MESSAGE)), Message(b"session-1".to_vec()), PubKey(TEST_PUBKEY), context())?);
+        assert!(!verify_session(Sig(signature.0), Message(Vec::from(TEST_MESSAGE)), Message(b"session-2".to_vec()), PubKey(TEST_PUBKEY), context())?);
+        Ok(())
     }
-}
 
-// Convert PrivKey struct to a PyObject
-impl IntoPy<PyObject> for PrivKey {
-    fn into_py(self, py: Python) -> PyObject {
-        let key = PyBytes::new(py, &self.0);
-        key.into_py(py)
+    #[test]
+    fn test_sign_with_expiry_block() -> PyResult<()> {
+        let context = || Message(b"voucher".to_vec());
+        let signature = sign_with_expiry_block(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)), 1000, context())?;
+        let check = |expiry_block, current_block| verify_with_expiry_block(Sig(signature.0), Message(Vec::from(TEST_MESSAGE)), expiry_block, current_block, PubKey(TEST_PUBKEY), context());
+
+        assert_eq!(check(1000, 999)?, (true, false));
+        assert_eq!(check(1000, 1000)?, (true, false));
+        assert_eq!(check(1000, 1001)?, (true, true));
+        // Claiming a later expiry than was signed invalidates the signature
+        assert_eq!(check(2000, 1001)?, (false, false));
+        Ok(())
     }
-}
 
-// Convert a PyBytes object of size 64 to a PrivKey object
-impl<'a> FromPyObject<'a> for PrivKey {
-    fn extract(obj: &'a PyAny) -> PyResult<Self> {
-        let secret = obj
-            .downcast::<PyBytes>()
-            .map_err(|_| exceptions::PyTypeError::new_err(format!("Expected {} byte secret key", SECRET_KEY_LENGTH)))
-            .and_then(|b| _check_pybytes_len(b, SECRET_KEY_LENGTH))?;
+    #[test]
+    fn test_vrf_sign() -> PyResult<()> {
+        let (output, proof) = vrf_sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)))?;
+        let (output_again, _) = vrf_sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)))?;
+
+        // The output is a deterministic function of key and message, the proof is not
+        assert_eq!(output.0, output_again.0);
+        assert_eq!(proof.0.len(), VRF_PROOF_BATCHABLE_LENGTH);
+        assert!(vrf_sign(Keypair(TEST_PUBKEY, [0xffu8; SECRET_KEY_LENGTH]), Message(Vec::from(TEST_MESSAGE))).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_vrf_verify() -> PyResult<()> {
+        let (output, proof) = vrf_sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)))?;
+        assert!(vrf_verify(PubKey(TEST_PUBKEY), Message(Vec::from(TEST_MESSAGE)), VrfOutput(output.0), VrfProof(proof.0))?);
+        assert!(!vrf_verify(PubKey(TEST_PUBKEY), Message(b"tampered".to_vec()), VrfOutput(output.0), VrfProof(proof.0))?);
+        assert!(!vrf_verify(PubKey(CHILD_PUBKEY), Message(Vec::from(TEST_MESSAGE)), VrfOutput(output.0), VrfProof(proof.0))?);
+
+        // Malformed output bytes are an error rather than a failed proof
+        assert!(vrf_verify(PubKey(TEST_PUBKEY), Message(Vec::from(TEST_MESSAGE)), VrfOutput([0u8; VRF_PREOUT_LENGTH]), VrfProof(proof.0)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_vrf_sign_extra() -> PyResult<()> {
+        let message = || Message(Vec::from(TEST_MESSAGE));
+        let (output, proof) = vrf_sign_extra(Keypair(TEST_PUBKEY, TEST_PRIVKEY), message(), Message(b"slot 42".to_vec()))?;
+        assert!(vrf_verify_extra(PubKey(TEST_PUBKEY), message(), Message(b"slot 42".to_vec()), VrfOutput(output.0), VrfProof(proof.0))?);
+
+        // Only the extra data is changed
+        assert!(!vrf_verify_extra(PubKey(TEST_PUBKEY), message(), Message(b"slot 43".to_vec()), VrfOutput(output.0), VrfProof(proof.0))?);
+        assert!(!vrf_verify(PubKey(TEST_PUBKEY), message(), VrfOutput(output.0), VrfProof(proof.0))?);
+
+        // The output does not depend on the extra data
+        let (plain_output, _) = vrf_sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), message())?;
+        assert_eq!(output.0, plain_output.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_vrf_output_bytes() -> PyResult<()> {
+        let (output, _) = vrf_sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)))?;
+        Python::with_gil(|py| {
+            let expand = |context: &[u8], length| vrf_output_bytes(py, VrfOutput(output.0), Message(context.to_vec()), length, PubKey(TEST_PUBKEY), Message(Vec::from(TEST_MESSAGE)));
+            let bytes = expand(b"lottery", 48)?;
+            let bytes_again = expand(b"lottery", 48)?;
+            let other_context = expand(b"shuffle", 48)?;
+
+            assert_eq!(bytes.as_bytes().len(), 48);
+            assert_eq!(bytes.as_bytes(), bytes_again.as_bytes());
+            assert_ne!(bytes.as_bytes(), other_context.as_bytes());
+
+            // Matches schnorrkel's own fixed-size expansion
+            let pk = PublicKey::from_bytes(&TEST_PUBKEY).unwrap();
+            let in_out = VRFPreOut::from_bytes(&output.0).unwrap()
+                .attach_input_hash(&pk, signing_context(SIGNING_CTX).bytes(TEST_MESSAGE)).unwrap();
+            let expected: [u8; 32] = in_out.make_bytes(b"lottery");
+            assert_eq!(expand(b"lottery", 32)?.as_bytes(), &expected[..]);
+
+            // Lengths are capped rather than allocated, or panicking inside merlin
+            assert_eq!(expand(b"lottery", VRF_OUTPUT_MAX_BYTES)?.as_bytes().len(), VRF_OUTPUT_MAX_BYTES);
+            let err = expand(b"lottery", VRF_OUTPUT_MAX_BYTES + 1).err().unwrap().into_py(py);
+            assert_eq!(err.getattr(py, "code")?.extract::<String>(py)?, "INVALID_ARGUMENT");
+            assert!(expand(b"lottery", 1 << 33).is_err());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_verify_fresh() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let context = || Message(b"login".to_vec());
+            let signature = sign_fresh(py, Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(Vec::from(TEST_MESSAGE)), context(), 1_600_000_000)?.as_bytes().to_vec();
+            let check = |signature: &[u8], now| verify_fresh(Message(signature.to_vec()), Message(Vec::from(TEST_MESSAGE)), PubKey(TEST_PUBKEY), context(), 300, now);
+
+            assert_eq!(check(&signature, 1_600_000_100)?, (true, 100));
+            assert_eq!(check(&signature, 1_600_000_301)?, (false, 301));
+            assert_eq!(check(&signature, 1_599_999_990)?, (false, -10));
+
+            // Rewriting the embedded timestamp breaks the signature
+            let mut backdated = signature.clone();
+            backdated[7] += 100;
+            assert_eq!(check(&backdated, 1_600_000_301)?, (false, 201));
+            assert!(check(&signature[1..], 1_600_000_100).is_err());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_sign_auto_prehash() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let context = || Message(b"test".to_vec());
+            for (length, expected_flag) in [(PREHASH_THRESHOLD, PREHASH_FLAG_RAW), (PREHASH_THRESHOLD + 1, PREHASH_FLAG_BLAKE2B)].iter() {
+                let message = vec![42u8; *length];
+                let signature = sign_auto_prehash(py, Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(message.clone()), context())?.as_bytes().to_vec();
+                assert_eq!(signature[0], *expected_flag);
+                assert!(verify_auto_prehash(Message(signature.clone()), Message(message.clone()), PubKey(TEST_PUBKEY), context())?);
+
+                // Flipping the flag selects the wrong path
+                let mut flipped = signature.clone();
+                flipped[0] ^= 1;
+                assert!(!verify_auto_prehash(Message(flipped), Message(message), PubKey(TEST_PUBKEY), context())?);
+            }
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_verify_delegation_chain() -> PyResult<()> {
+        let root = SchnorrkelKeypair::from_bytes(&[&TEST_PRIVKEY[..], &TEST_PUBKEY[..]].concat()).unwrap();
+        let middle = SchnorrkelKeypair::from_bytes(&[&CHILD_PRIVKEY[..], &CHILD_PUBKEY[..]].concat()).unwrap();
+        let delegate = |signer: &SchnorrkelKeypair, delegate: [u8; PUBLIC_KEY_LENGTH]|
+            (PubKey(delegate), Sig(signer.sign_simple(b"delegation", &delegate).to_bytes()));
+
+        let links = vec![delegate(&root, CHILD_PUBKEY), delegate(&middle, CHILD_PUBKEY_HARD)];
+        let last = verify_delegation_chain(PubKey(TEST_PUBKEY), links, Message(b"delegation".to_vec()))?;
+        assert_eq!(last.0, CHILD_PUBKEY_HARD);
+
+        // The second link is signed by the root rather than the middle delegate
+        let broken = vec![delegate(&root, CHILD_PUBKEY), delegate(&root, CHILD_PUBKEY_HARD)];
+        assert!(verify_delegation_chain(PubKey(TEST_PUBKEY), broken, Message(b"delegation".to_vec())).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_extrinsic_signature() -> PyResult<()> {
+        let alice = dev_keypair("Alice")?;
+        let signer = SchnorrkelKeypair::from_bytes(&[&alice.1[..], &alice.0[..]].concat()).unwrap();
+        // Spec version, transaction version, genesis hash and block hash
+        let additional = [&9430u32.to_le_bytes()[..], &26u32.to_le_bytes()[..], &[0x91; 32][..], &[0x91; 32][..]].concat();
+        // Immortal era, nonce 5 and no tip
+        let extra = vec![0x00, 5 << 2, 0x00];
+
+        let extrinsic = |call: &[u8], additional: &[u8]| {
+            let sig = signer.sign_simple(SIGNING_CTX, &_auto_prehash_payload(&[call, &extra, additional].concat()).1);
+            let body = [&[0x84, 0x00][..], &alice.0[..], &[0x01][..], &sig.to_bytes()[..], &extra, call].concat();
+            Message([_scale_compact_len(body.len()), body].concat())
+        };
+        let ctx = || Message(SIGNING_CTX.to_vec());
+
+        // A short balances transfer-like call and one long enough to be prehashed
+        let call = [&[0x05, 0x00, 0x00][..], &ALICE_PUBKEY[..], &[0x02, 0x09, 0x3d, 0x00][..]].concat();
+        assert!(verify_extrinsic_signature(extrinsic(&call, &additional), ctx(), Message(additional.clone()))?);
+        assert!(verify_extrinsic_signature(extrinsic(&[0x42; 300], &additional), ctx(), Message(additional.clone()))?);
+
+        // Signed against another block hash, or with a tampered call
+        let mut other = additional.clone();
+        other[40] ^= 1;
+        assert!(!verify_extrinsic_signature(extrinsic(&call, &other), ctx(), Message(additional.clone()))?);
+        let mut tampered = extrinsic(&call, &additional);
+        let last = tampered.0.len() - 1;
+        tampered.0[last] ^= 1;
+        assert!(!verify_extrinsic_signature(tampered, ctx(), Message(additional.clone()))?);
+
+        // An unsigned extrinsic
+        assert!(verify_extrinsic_signature(Message(vec![0x0c, 0x04, 0x05, 0x00]), ctx(), Message(additional)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_batch() -> PyResult<()> {
+        let count = 500;
+        let keypairs: Vec<([u8; PUBLIC_KEY_LENGTH], [u8; SECRET_KEY_LENGTH])> = (0..count)
+            .map(|i| {
+                let keypair = derive_counter_keypair(PrivKey(TEST_PRIVKEY), i as u64).unwrap();
+                (keypair.0, keypair.1)
+            })
+            .collect();
+        let messages: Vec<Vec<u8>> = (0..count).map(|i| format!("message {}", i).into_bytes()).collect();
+        let mut signatures: Vec<[u8; SIGNATURE_LENGTH]> = keypairs.iter().zip(messages.iter())
+            .map(|(keypair, message)| sign(Keypair(keypair.0, keypair.1), Message(message.clone())).unwrap().0)
+            .collect();
+
+        let batch = |signatures: &Vec<[u8; SIGNATURE_LENGTH]>| Python::with_gil(|py| verify_batch(
+            py,
+            signatures.iter().map(|sig| Sig(*sig)).collect(),
+            messages.iter().map(|message| Message(message.clone())).collect(),
+            keypairs.iter().map(|keypair| PubKey(keypair.0)).collect()));
+        let looped = |signatures: &Vec<[u8; SIGNATURE_LENGTH]>| signatures.iter().zip(messages.iter()).zip(keypairs.iter())
+            .all(|((sig, message), keypair)| verify(Sig(*sig), Message(message.clone()), PubKey(keypair.0)).unwrap());
+
+        assert!(batch(&signatures)?);
+        assert!(looped(&signatures));
+
+        // One signature swapped for another one's makes both approaches fail
+        signatures[count - 1] = signatures[0];
+        assert!(!batch(&signatures)?);
+        assert!(!looped(&signatures));
+
+        assert!(Python::with_gil(|py| verify_batch(py, vec![], vec![], vec![PubKey(TEST_PUBKEY)])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_batch_releases_gil() -> PyResult<()> {
+        let count = 200;
+        let messages: Vec<Vec<u8>> = (0..count).map(|i| format!("message {}", i).into_bytes()).collect();
+        let signatures: Vec<[u8; SIGNATURE_LENGTH]> = messages.iter()
+            .map(|message| sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(message.clone())).unwrap().0)
+            .collect();
+
+        Python::with_gil(|py| {
+            // A Python thread counts while it holds the GIL, which it can only take while
+            // verify_batch has released it
+            let globals = PyDict::new(py);
+            py.run("import threading\n\
+                state = {'count': 0, 'stop': False}\n\
+                def spin():\n    while not state['stop']:\n        state['count'] += 1\n\
+                thread = threading.Thread(target=spin)\n\
+                thread.start()", Some(globals), None)?;
+            // Read with the C API, as running Python code could hand the GIL over itself
+            let state: &PyDict = globals.get_item("state").unwrap().downcast()?;
+            let ticks = || -> PyResult<u64> { state.get_item("count").unwrap().extract() };
+            while ticks()? == 0 {
+                py.allow_threads(|| std::thread::sleep(Duration::from_millis(1)));
+            }
+
+            let before = ticks()?;
+            let result = verify_batch(
+                py,
+                signatures.iter().map(|sig| Sig(*sig)).collect(),
+                messages.iter().map(|message| Message(message.clone())).collect(),
+                messages.iter().map(|_| PubKey(TEST_PUBKEY)).collect());
+            let after = ticks()?;
+            py.run("state['stop'] = True\nthread.join()", Some(globals), None)?;
+
+            assert!(result?);
+            assert!(after > before);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_verify_batch_contexts() -> PyResult<()> {
+        let keypair = SchnorrkelKeypair::from_bytes(&[&TEST_PRIVKEY[..], &TEST_PUBKEY[..]].concat()).unwrap();
+        let contexts = vec![b"substrate".to_vec(), b"other".to_vec(), b"other".to_vec()];
+        let mut signatures: Vec<Sig> = contexts.iter()
+            .map(|context| Sig(keypair.sign_simple(context, TEST_MESSAGE).to_bytes()))
+            .collect();
+
+        let batch = |signatures: &Vec<Sig>, contexts: &Vec<Vec<u8>>| Python::with_gil(|py| verify_batch_contexts(
+            py,
+            signatures.iter().map(|sig| Sig(sig.0)).collect(),
+            contexts.iter().map(|_| Message(TEST_MESSAGE.to_vec())).collect(),
+            contexts.iter().map(|_| PubKey(TEST_PUBKEY)).collect(),
+            contexts.iter().map(|context| Message(context.clone())).collect()));
+        assert!(batch(&signatures, &contexts)?);
+
+        // A signature checked under another entry's context fails the batch
+        signatures.swap(0, 1);
+        assert!(!batch(&signatures, &contexts)?);
 
-        // Convert bytes to fixed width array
-        let mut fixed: [u8; 64] = [0u8; SECRET_KEY_LENGTH];
-        fixed.clone_from_slice(secret.as_bytes());
-        Ok(PrivKey(fixed))
+        assert!(Python::with_gil(|py| verify_batch_contexts(py, vec![], vec![], vec![], vec![Message(b"substrate".to_vec())])).is_err());
+        Ok(())
     }
-}
 
-// Convert an arbitrary sized PyBytes object to a Message struct
-impl<'a> FromPyObject<'a> for Message {
-    fn extract(obj: &PyAny) -> PyResult<Self> {
-        let messsge = obj
-            .downcast::<PyBytes>()
-            .map_err(|_| exceptions::PyTypeError::new_err("Expected bytes object"))?;
-        Ok(Message(messsge.as_bytes().to_owned()))
-    }
-}
+    #[test]
+    fn test_verify_batch_single_key() -> PyResult<()> {
+        let messages: Vec<Vec<u8>> = (0..50).map(|i| format!("log line {}", i).into_bytes()).collect();
+        let mut signatures: Vec<[u8; SIGNATURE_LENGTH]> = messages.iter()
+            .map(|message| sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(message.clone())).unwrap().0)
+            .collect();
 
-// Convert ExtendedPubKey into Python ExtendedPubKey tuple
-impl IntoPy<PyObject> for ExtendedPubKey {
-    fn into_py(self, py: Python) -> PyObject {
-        let chain_code = PyBytes::new(py, &self.0);
-        let public = PyBytes::new(py, &self.1);
+        let batch = |signatures: &Vec<[u8; SIGNATURE_LENGTH]>| Python::with_gil(|py| verify_batch_single_key(
+            py,
+            signatures.iter().map(|sig| Sig(*sig)).collect(),
+            messages.iter().map(|message| Message(message.clone())).collect(),
+            PubKey(TEST_PUBKEY)));
+        let looped = |signatures: &Vec<[u8; SIGNATURE_LENGTH]>| signatures.iter().zip(messages.iter())
+            .all(|(sig, message)| verify(Sig(*sig), Message(message.clone()), PubKey(TEST_PUBKEY)).unwrap());
+        assert!(batch(&signatures)?);
+        assert!(looped(&signatures));
 
-        PyTuple::new(py, vec![chain_code, public]).into_py(py)
+        // Signatures over the wrong messages fail both approaches
+        signatures.swap(0, 1);
+        assert!(!batch(&signatures)?);
+        assert!(!looped(&signatures));
+
+        Python::with_gil(|py| {
+            assert!(verify_batch_single_key(py, vec![], vec![Message(b"message".to_vec())], PubKey(TEST_PUBKEY)).is_err());
+            assert!(verify_batch_single_key(py, vec![], vec![], PubKey([0xff; PUBLIC_KEY_LENGTH])).is_err());
+        });
+        Ok(())
     }
-}
 
-// Convert Python ExtendedPubKey into Rust
-impl<'a> FromPyObject<'a> for ExtendedPubKey {
-    fn extract(obj: &'a PyAny) -> PyResult<Self> {
-        let extended = _to_pytuple(obj)?;
-        // Don't check that the length matches exactly here so that an extended
-        // private key can be passed in as well.
-        if extended.len() < 2 {
-            return Err(exceptions::PyIndexError::new_err(format!("Expected tuple of size 2, got {}", extended.len())));
-        }
+    #[test]
+    fn test_verify_each() -> PyResult<()> {
+        let messages: Vec<Vec<u8>> = (0..6).map(|i| format!("extrinsic {}", i).into_bytes()).collect();
+        let mut signatures: Vec<Sig> = messages.iter()
+            .map(|message| sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), Message(message.clone())).unwrap())
+            .collect();
+        let mut pubkeys = vec![TEST_PUBKEY; messages.len()];
 
-        // Convert bytes to fixed width arrays
-        let mut chain_code: [u8; CHAIN_CODE_LENGTH] = [0u8; CHAIN_CODE_LENGTH];
-        let mut public: [u8; PUBLIC_KEY_LENGTH] = [0u8; PUBLIC_KEY_LENGTH];
-        chain_code.clone_from_slice(
-            &extended.get_item(0)
-                    .downcast::<PyBytes>()
-                    .map_err(|_| exceptions::PyTypeError::new_err("Expected bytes object at index 0"))
-                    .and_then(|b| _check_pybytes_len(b, CHAIN_CODE_LENGTH))?
-                    .as_bytes()[0..CHAIN_CODE_LENGTH]);
-        public.clone_from_slice(
-            &extended.get_item(1)
-                    .downcast::<PyBytes>()
-                    .map_err(|_| exceptions::PyTypeError::new_err("Expected bytes object at index 1"))
-                    .and_then(|b| _check_pybytes_len(b, PUBLIC_KEY_LENGTH))?
-                    .as_bytes()[0..PUBLIC_KEY_LENGTH]);
-        let extended_pubkey = ExtendedPubKey(chain_code, public);
-        Ok(extended_pubkey)
+        // A signature over another message, one by another key, and a malformed signature
+        signatures[1] = Sig(signatures[0].0);
+        pubkeys[3] = CHILD_PUBKEY;
+        signatures[4] = Sig([0u8; SIGNATURE_LENGTH]);
+
+        let results = Python::with_gil(|py| verify_each(
+            py,
+            signatures,
+            messages.iter().map(|message| Message(message.clone())).collect(),
+            pubkeys.iter().map(|pubkey| PubKey(*pubkey)).collect()))?;
+        assert_eq!(results, vec![true, false, true, false, false, true]);
+
+        assert!(Python::with_gil(|py| verify_each(py, vec![], vec![], vec![PubKey(TEST_PUBKEY)])).is_err());
+        Ok(())
     }
-}
 
-// Convert ExtendedKeypair into Python ExtendedKeypair tuple
-impl IntoPy<PyObject> for ExtendedKeypair {
-    fn into_py(self, py: Python) -> PyObject {
-        let chain_code = PyBytes::new(py, &self.0);
-        let public = PyBytes::new(py, &self.1);
-        let private = PyBytes::new(py, &self.2);
+    #[test]
+    fn test_aggregate_signatures_compact() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let keys = [(TEST_PUBKEY, TEST_PRIVKEY), (CHILD_PUBKEY, CHILD_PRIVKEY), (TEST_PUBKEY, TEST_PRIVKEY)];
+            let messages: Vec<Vec<u8>> = (0..3).map(|i| format!("message {}", i).into_bytes()).collect();
+            let signatures: Vec<Sig> = keys.iter().zip(messages.iter())
+                .map(|((public, secret), message)| sign(Keypair(*public, *secret), Message(message.clone())).unwrap())
+                .collect();
+            let msgs = |messages: &Vec<Vec<u8>>| messages.iter().map(|message| Message(message.clone())).collect::<Vec<Message>>();
+            let pks = || keys.iter().map(|(public, _)| PubKey(*public)).collect::<Vec<PubKey>>();
 
-        PyTuple::new(py, vec![chain_code, public, private]).into_py(py)
+            let aggregate = aggregate_signatures_compact(py, signatures, msgs(&messages), pks())?.as_bytes().to_vec();
+            assert_eq!(aggregate.len(), 32 * 4);
+            assert!(verify_aggregate_compact(py, Message(aggregate.clone()), msgs(&messages), pks())?);
+
+            // Another message, a changed aggregate scalar, or reordered entries all fail
+            let mut other_messages = messages.clone();
+            other_messages[1] = b"other".to_vec();
+            assert!(!verify_aggregate_compact(py, Message(aggregate.clone()), msgs(&other_messages), pks())?);
+            let mut tampered = aggregate.clone();
+            tampered[0] ^= 1;
+            assert!(!verify_aggregate_compact(py, Message(tampered), msgs(&messages), pks())?);
+            let mut reordered = messages.clone();
+            reordered.swap(0, 2);
+            assert!(!verify_aggregate_compact(py, Message(aggregate.clone()), msgs(&reordered), pks())?);
+
+            assert!(verify_aggregate_compact(py, Message(aggregate[..96].to_vec()), msgs(&messages), pks()).is_err());
+            assert!(aggregate_signatures_compact(py, vec![], vec![], vec![]).is_err());
+
+            // An empty aggregate is rejected rather than vacuously valid
+            let err = verify_aggregate_compact(py, Message(vec![0u8; 32]), vec![], vec![]).err().unwrap();
+            assert_eq!(err.into_py(py).getattr(py, "code")?.extract::<String>(py)?, "INVALID_ARGUMENT");
+            Ok(())
+        })
     }
-}
 
-// Convert Python ExtendedKeypair into Rust
-impl<'a> FromPyObject<'a> for ExtendedKeypair {
-    fn extract(obj: &'a PyAny) -> PyResult<Self> {
-        let extended = _to_pytuple(obj)?;
-        if extended.len() < 3 {
-            return Err(exceptions::PyIndexError::new_err(format!("Expected tuple of size 3, got {}", extended.len())));
-        }
+    #[test]
+    fn test_timing_self_test() -> PyResult<()> {
+        // Any two timings are within a relative tolerance of 1.0 of each other
+        assert!(timing_self_test(5, 1.0)?);
+        let _: bool = timing_self_test(5, 0.1)?;
+        assert!(timing_self_test(0, 0.1).is_err());
+        Ok(())
+    }
 
-        // Convert bytes to fixed width arrays
-        let mut chain_code: [u8; CHAIN_CODE_LENGTH] = [0u8; CHAIN_CODE_LENGTH];
-        let mut public: [u8; PUBLIC_KEY_LENGTH] = [0u8; PUBLIC_KEY_LENGTH];
-        let mut private: [u8; SECRET_KEY_LENGTH] = [0u8; SECRET_KEY_LENGTH];
+    #[test]
+    fn test_public_from_secret_key() -> PyResult<()> {
+        let secret = PrivKey(TEST_PRIVKEY);
 
-        chain_code.clone_from_slice(
-            &extended.get_item(0)
-                    .downcast::<PyBytes>()
-                    .map_err(|_| exceptions::PyTypeError::new_err("Expected bytes object at index 0"))
-                    .and_then(|b| _check_pybytes_len(b, CHAIN_CODE_LENGTH))?
-                    .as_bytes()[0..CHAIN_CODE_LENGTH]);
-        public.clone_from_slice(
-            &extended.get_item(1)
-                    .downcast::<PyBytes>()
-                    .map_err(|_| exceptions::PyTypeError::new_err("Expected bytes object at index 1"))
-                    .and_then(|b| _check_pybytes_len(b, PUBLIC_KEY_LENGTH))?
-                    .as_bytes()[0..PUBLIC_KEY_LENGTH]);
-        private.clone_from_slice(
-           &extended.get_item(2)
-                    .downcast::<PyBytes>()
-                    .map_err(|_| exceptions::PyTypeError::new_err("Expected bytes object at index 2"))
-                    .and_then(|b| _check_pybytes_len(b, SECRET_KEY_LENGTH))?
-                    .as_bytes()[0..SECRET_KEY_LENGTH]);
-        let extended_keypair = ExtendedKeypair(chain_code, public, private);
-        Ok(extended_keypair)
+        let pubkey = public_from_secret_key(secret)?;
+        assert_eq!(pubkey.0, TEST_PUBKEY);
+        Ok(())
     }
-}
 
-/// This module is a python module implemented in Rust.
-#[pymodule]
-fn sr25519(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
-    m.add_wrapped(wrap_pyfunction!(pair_from_seed))?;
-    m.add_wrapped(wrap_pyfunction!(sign))?;
-    m.add_wrapped(wrap_pyfunction!(verify))?;
-    m.add_wrapped(wrap_pyfunction!(public_from_secret_key))?;
-    m.add_wrapped(wrap_pyfunction!(derive_pubkey))?;
-    m.add_wrapped(wrap_pyfunction!(derive_keypair))?;
-    m.add_wrapped(wrap_pyfunction!(hard_derive_keypair))?;
-    m.add_wrapped(wrap_pyfunction!(sum_public_points))?;
-    m.add_wrapped(wrap_pyfunction!(multi_sign))?;
+    #[test]
+    fn test_pair_from_secret_key() -> PyResult<()> {
+        let keypair = pair_from_secret_key(PrivKey(TEST_PRIVKEY))?;
+        assert_eq!(keypair.0, public_from_secret_key(PrivKey(TEST_PRIVKEY))?.0);
+        assert_eq!(&keypair.1[..], &TEST_PRIVKEY[..]);
 
-    Ok(())
-}
+        let sig = sign(keypair, Message(Vec::from(TEST_MESSAGE)))?;
+        assert!(verify(sig, Message(Vec::from(TEST_MESSAGE)), PubKey(TEST_PUBKEY))?);
 
+        assert!(pair_from_secret_key(PrivKey([0xff; SECRET_KEY_LENGTH])).is_err());
+        Ok(())
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use hex_literal::hex;
+    #[test]
+    fn test_keypair_bytes() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let blob = keypair_to_bytes(py, Keypair(TEST_PUBKEY, TEST_PRIVKEY))?.as_bytes().to_vec();
+            assert_eq!(blob.len(), KEYPAIR_LENGTH);
+            assert_eq!(&blob[..SECRET_KEY_LENGTH], &TEST_PRIVKEY[..]);
+            assert_eq!(&blob[SECRET_KEY_LENGTH..], &TEST_PUBKEY[..]);
 
-    static TEST_SEED: [u8; MINI_SECRET_KEY_LENGTH] = hex!("f30eb58ad9bde4a702da3c723709cbfaf7030b22d5e4d16bcbf733c9c09bf6bd");
-    static TEST_CHAIN_CODE: [u8; CHAIN_CODE_LENGTH] = hex!("79f708602879cb5cecfff56f57a8551ff170025d77a42d053a9caf7ac4c543b5");
+            let keypair = keypair_from_bytes(Message(blob.clone()))?;
+            assert_eq!(keypair.0, TEST_PUBKEY);
+            assert_eq!(&keypair.1[..], &TEST_PRIVKEY[..]);
+            let sig = sign(keypair, Message(Vec::from(TEST_MESSAGE)))?;
+            assert!(verify(sig, Message(Vec::from(TEST_MESSAGE)), PubKey(TEST_PUBKEY))?);
 
-    static TEST_PUBKEY: [u8; PUBLIC_KEY_LENGTH] = hex!("0e563c7dcb4446c0ed7e7a9d9f0a3a3d41c8767a87f205bd48fb8ef5db066b6b");
-    static TEST_PRIVKEY: [u8; SECRET_KEY_LENGTH] = hex!("1a470f5b685a943fc90d8c0ec0cddb4ace28e26fd3e0091eb39a4333277def0bc5cb44ce61338968b0d5f2022346684a90ba8e526dd9d1c0616f1e76be5edcff");
+            assert!(keypair_from_bytes(Message(blob[..KEYPAIR_LENGTH - 1].to_vec())).is_err());
+            Ok(())
+        })
+    }
 
-    static CHILD_CHAIN_CODE: [u8; CHAIN_CODE_LENGTH] = hex!("6c623b771ab68008e4d3c739abf5ae322a2be44e2bd477e3dec237a0fe5ede1e");
-    static CHILD_PUBKEY: [u8; PUBLIC_KEY_LENGTH] = hex!("5e578b800520128de3056e59e2e11aad250dd72a28dddf5886ab7f7808f7642f");
-    static CHILD_PRIVKEY: [u8; SECRET_KEY_LENGTH] = hex!("4f0db52b5d4105303aa0ef052b33040727c5fd5beeb0a7ca12cd8244ed1c6505f17d23ab6e22685f03aec29eb7722b53b7c79434ec6f380956bb904eccc6dd14");
+    #[test]
+    fn test_is_valid_key() {
+        assert!(is_valid_public_key(Message(Vec::from(TEST_PUBKEY))));
+        assert!(!is_valid_public_key(Message(Vec::from(&TEST_PUBKEY[..31]))));
+        assert!(!is_valid_public_key(Message(vec![0xff; PUBLIC_KEY_LENGTH])));
 
-    static CHILD_CHAIN_CODE_HARD: [u8; CHAIN_CODE_LENGTH] = hex!("0e89c4b7b29d92138c0a093b9f9cf9a0132960f52664a188e6d0e3ef324316cc");
-    static CHILD_PUBKEY_HARD: [u8; PUBLIC_KEY_LENGTH] = hex!("8c0ff65769fdb4bfdf12e628261f39bfb29fba94353b6faddbcddcd455d4ea29");
-    static CHILD_PRIVKEY_HARD: [u8; SECRET_KEY_LENGTH] = hex!("1c76b89abb8cce5310fa40ef7f197b6c0fd482f2b59e1a524ec30736fbcf580881f2e63b515c6be0a484e3ceaefce0372f1826d3dd230502f8cea22a323bfbb1");
+        assert!(is_valid_secret_key(Message(Vec::from(TEST_PRIVKEY))));
+        assert!(!is_valid_secret_key(Message(Vec::from(TEST_PUBKEY))));
+        assert!(!is_valid_secret_key(Message(vec![0xff; SECRET_KEY_LENGTH])));
+    }
 
-    static TEST_MESSAGE: &[u8] =
-        b"All of the world's a stage \
-        And all the men and women merely players; \
-        They have their exits and their entrances, \
-        And one man in his time plays many parts, \
-        His acts being seven ages.";
+    #[test]
+    fn test_public_from_keypair() -> PyResult<()> {
+        assert_eq!(public_from_keypair(Keypair(TEST_PUBKEY, TEST_PRIVKEY), true)?.0, TEST_PUBKEY);
+
+        // A public key paired with another key's secret is caught unless checking is off
+        assert!(public_from_keypair(Keypair(CHILD_PUBKEY, TEST_PRIVKEY), true).is_err());
+        assert_eq!(public_from_keypair(Keypair(CHILD_PUBKEY, TEST_PRIVKEY), false)?.0, CHILD_PUBKEY);
+        Ok(())
+    }
 
     #[test]
-    fn test_pair_from_seed() -> PyResult<()> {
-        let seed = Seed(TEST_SEED);
-        let keypair = pair_from_seed(seed)?;
+    fn test_secret_key_to_mini_secret() -> PyResult<()> {
+        let scalar = secret_key_to_mini_secret(PrivKey(TEST_PRIVKEY))?;
+        assert_eq!(scalar.0[..], TEST_PRIVKEY[..32]);
+        let x = Scalar::from_canonical_bytes(scalar.0).unwrap();
+        assert_eq!((&x * &RISTRETTO_BASEPOINT_TABLE).compress().to_bytes(), TEST_PUBKEY);
 
-        assert_eq!(keypair.0, TEST_PUBKEY);
-        assert_eq!(&keypair.1[0..SECRET_KEY_LENGTH], &TEST_PRIVKEY[0..SECRET_KEY_LENGTH]);
+        // It is not the seed the key was expanded from
+        assert_ne!(scalar.0, TEST_SEED);
+        assert_ne!(pair_from_seed(Seed(scalar.0), "ed25519")?.0, TEST_PUBKEY);
+
+        let mut non_canonical = TEST_PRIVKEY;
+        non_canonical[31] = 0xff;
+        assert!(secret_key_to_mini_secret(PrivKey(non_canonical)).is_err());
         Ok(())
     }
 
     #[test]
-    fn test_sign_and_verify() -> PyResult<()> {
-        let signer_keypair = Keypair(TEST_PUBKEY, TEST_PRIVKEY);
-        let signer_pubkey = PubKey(TEST_PUBKEY);
+    fn test_secret_key_to_ed25519_bytes() -> PyResult<()> {
+        let ed25519 = secret_key_to_ed25519_bytes(PrivKey(TEST_PRIVKEY))?;
+        assert_eq!(ed25519.0[32..], TEST_PRIVKEY[32..]);
+        // The scalar times 8 as an integer, not reduced modulo the group order
+        let mut times_eight = [0u8; 32];
+        let mut carry = 0u8;
+        for (out, byte) in times_eight.iter_mut().zip(TEST_PRIVKEY[..32].iter()) {
+            *out = (byte << 3) | carry;
+            carry = byte >> 5;
+        }
+        assert_eq!(ed25519.0[..32], times_eight);
 
-        let test_message = Message(Vec::from(TEST_MESSAGE));
-        let test_message_copy = Message(Vec::from(TEST_MESSAGE));
+        let round_trip = SecretKey::from_ed25519_bytes(&ed25519.0).unwrap();
+        assert_eq!(&round_trip.to_bytes()[..], &TEST_PRIVKEY[..]);
 
-        let signature = sign(signer_keypair, test_message)?;
-        let is_good = verify(signature, test_message_copy, signer_pubkey)?;
-        assert!(is_good);
+        assert!(secret_key_to_ed25519_bytes(PrivKey([0xff; SECRET_KEY_LENGTH])).is_err());
         Ok(())
     }
 
     #[test]
-    fn test_public_from_secret_key() -> PyResult<()> {
-        let secret = PrivKey(TEST_PRIVKEY);
+    fn test_secret_from_ed25519_bytes() -> PyResult<()> {
+        let ed25519 = secret_key_to_ed25519_bytes(PrivKey(TEST_PRIVKEY))?;
+        assert_eq!(secret_from_ed25519_bytes(ed25519)?.0, TEST_PRIVKEY);
 
-        let pubkey = public_from_secret_key(secret)?;
-        assert_eq!(pubkey.0, TEST_PUBKEY);
+        // schnorrkel's own vector
+        let secret = hex!("28b0ae221c6bb06856b287f60d7ea0d98552ea5a16db16956849aa371db3eb51fd190cce74df356432b410bd64682309d6dedb27c76845daf388557cbac3ca34");
+        let converted = secret_from_ed25519_bytes(PrivKey(secret))?;
+        let public = SecretKey::from_bytes(&converted.0).unwrap().to_public();
+        assert_eq!(public.to_bytes(), hex!("46ebddef8cd9bb167dc30878d7113b7e168e6f0646beffd77d69d39bad76b47a"));
+
+        let mut unclamped = secret;
+        unclamped[0] |= 1;
+        assert!(secret_from_ed25519_bytes(PrivKey(unclamped)).is_err());
+        assert!(secret_from_ed25519_bytes(PrivKey([0xf8; SECRET_KEY_LENGTH])).is_err());
         Ok(())
     }
 
+    #[test]
+    fn test_keys_equal() {
+        assert!(secret_keys_equal(PrivKey(TEST_PRIVKEY), PrivKey(TEST_PRIVKEY)));
+        assert!(!secret_keys_equal(PrivKey(TEST_PRIVKEY), PrivKey(CHILD_PRIVKEY)));
+        let mut last_byte_differs = TEST_PRIVKEY;
+        last_byte_differs[SECRET_KEY_LENGTH - 1] ^= 1;
+        assert!(!secret_keys_equal(PrivKey(TEST_PRIVKEY), PrivKey(last_byte_differs)));
+
+        assert!(public_keys_equal(PubKey(TEST_PUBKEY), PubKey(TEST_PUBKEY)));
+        assert!(!public_keys_equal(PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY)));
+    }
+
+    #[test]
+    fn test_chain_code_from_bytes() {
+        Python::with_gil(|py| {
+            let chain_code = chain_code_from_bytes(py, Message(b"stash".to_vec())).as_bytes().to_vec();
+            assert_eq!(chain_code.len(), CHAIN_CODE_LENGTH);
+            assert_eq!(chain_code, chain_code_from_bytes(py, Message(b"stash".to_vec())).as_bytes());
+            assert_ne!(chain_code, chain_code_from_bytes(py, Message(b"controller".to_vec())).as_bytes());
+        })
+    }
+
     #[test]
     fn test_derive_pubkey() -> PyResult<()> {
         let extended_pubkey = ExtendedPubKey(TEST_CHAIN_CODE, TEST_PUBKEY);
@@ -689,6 +6742,42 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_derive_pubkey_index() -> PyResult<()> {
+        let extended_pubkey = || ExtendedPubKey(TEST_CHAIN_CODE, TEST_PUBKEY);
+
+        let child = derive_pubkey_index(extended_pubkey(), 5)?;
+        let expected = derive_path_pubkey(extended_pubkey(), "/5")?;
+        assert_eq!(child.0, expected.0);
+        assert_eq!(child.1, expected.1);
+
+        // The public half of the keypair's child
+        let keypair_child = &derive_keypairs_range(ExtendedKeypair(TEST_CHAIN_CODE, TEST_PUBKEY, TEST_PRIVKEY), 5, 1)?[0];
+        assert_eq!(child.1, keypair_child.1);
+
+        // Not the same as passing the index's bytes as the id
+        let naive = derive_pubkey(extended_pubkey(), Message(5u64.to_le_bytes().to_vec()))?;
+        assert_ne!(child.1, naive.1);
+
+        assert!(derive_pubkey_index(extended_pubkey(), u64::MAX as i128).is_ok());
+        assert!(derive_pubkey_index(extended_pubkey(), -1).is_err());
+        assert!(derive_pubkey_index(extended_pubkey(), u64::MAX as i128 + 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hard_derive_pubkey() {
+        let err = hard_derive_pubkey(ExtendedPubKey(TEST_CHAIN_CODE, TEST_PUBKEY), Message(b"Alice".to_vec())).err().unwrap();
+        Python::with_gil(|py| {
+            let err = err.into_py(py);
+            assert_eq!(err.getattr(py, "code").unwrap().extract::<String>(py).unwrap(), "INVALID_ARGUMENT");
+            assert_eq!(
+                err.as_ref(py).str().unwrap().to_string(),
+                "Hard derivation requires the secret key: use hard_derive_keypair with the extended keypair, or derive_pubkey for a soft child"
+            );
+        });
+    }
+
     #[test]
     fn test_derive_keypair() -> PyResult<()> {
         let extended_keypair = ExtendedKeypair(TEST_CHAIN_CODE, TEST_PUBKEY, TEST_PRIVKEY);
@@ -702,6 +6791,52 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_derive_keypair_deterministic() -> PyResult<()> {
+        let derive = || derive_keypair_deterministic(
+            ExtendedKeypair(TEST_CHAIN_CODE, TEST_PUBKEY, TEST_PRIVKEY), Message(vec![1u8, 2u8, 3u8, 4u8]));
+        let child = derive()?;
+        let again = derive()?;
+        assert_eq!(&child.2[..], &again.2[..]);
+
+        // Everything but the nonce matches derive_keypair
+        assert_eq!(child.0, CHILD_CHAIN_CODE);
+        assert_eq!(child.1, CHILD_PUBKEY);
+        assert_eq!(&child.2[..32], &CHILD_PRIVKEY[..32]);
+        assert!(SecretKey::from_bytes(&child.2).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_keypairs_range() -> PyResult<()> {
+        let extended_keypair = || ExtendedKeypair(TEST_CHAIN_CODE, TEST_PUBKEY, TEST_PRIVKEY);
+        let children = derive_keypairs_range(extended_keypair(), 5, 3)?;
+        assert_eq!(children.len(), 3);
+
+        // The first element is a single `derive_keypair` call with the index's junction
+        let single = derive_keypair(ExtendedKeypair(_index_junction(5).0, TEST_PUBKEY, TEST_PRIVKEY), Message(vec![]))?;
+        assert_eq!(children[0].0, single.0);
+        assert_eq!(children[0].1, single.1);
+        assert_eq!(&children[0].2[..32], &single.2[..32]);
+
+        // Each child is the soft junction `/index`, whatever the parent chain code
+        let other_parent = derive_keypairs_range(ExtendedKeypair(CHILD_CHAIN_CODE, TEST_PUBKEY, TEST_PRIVKEY), 5, 1)?;
+        assert_eq!(other_parent[0].1, children[0].1);
+        for (index, child) in (5..).zip(children.iter()) {
+            let single = derive_path(extended_keypair(), &format!("/{}", index))?;
+            assert_eq!(child.0, single.0);
+            assert_eq!(child.1, single.1);
+            assert_eq!(&child.2[..32], &single.2[..32]);
+        }
+
+        assert_eq!(derive_keypairs_range(extended_keypair(), u64::MAX, 1)?.len(), 1);
+        let err = derive_keypairs_range(extended_keypair(), u64::MAX, 2).err().unwrap();
+        Python::with_gil(|py| {
+            assert_eq!(err.into_py(py).getattr(py, "code")?.extract::<String>(py)?, "INVALID_ARGUMENT");
+            Ok(())
+        })
+    }
+
     #[test]
     fn test_hard_derive_keypair() -> PyResult<()> {
         let extended_keypair = ExtendedKeypair(TEST_CHAIN_CODE, TEST_PUBKEY, TEST_PRIVKEY);
@@ -714,4 +6849,263 @@ mod tests {
         assert_eq!(&child_ext_keypair.2[0..PUBLIC_KEY_LENGTH], &CHILD_PRIVKEY_HARD[0..PUBLIC_KEY_LENGTH]);
         Ok(())
     }
+
+    #[test]
+    fn test_hard_derive_mini_secret() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let extended_keypair = || ExtendedKeypair(TEST_CHAIN_CODE, TEST_PUBKEY, TEST_PRIVKEY);
+            let test_index = || Message(vec![1u8, 2u8, 3u8, 4u8]);
+
+            let (chain_code, mini_secret) = hard_derive_mini_secret(py, extended_keypair(), test_index())?;
+            assert_eq!(chain_code.as_bytes(), CHILD_CHAIN_CODE_HARD);
+
+            let expanded = pair_from_seed(Seed(mini_secret.0), "ed25519")?;
+            let derived = hard_derive_keypair(extended_keypair(), test_index())?;
+            assert_eq!(expanded.0, derived.1);
+            assert_eq!(&expanded.1[..], &derived.2[..]);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_encrypt_to_public() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let (ephemeral, ciphertext) = encrypt_to_public(py, PubKey(TEST_PUBKEY), Message(Vec::from(TEST_MESSAGE)))?;
+            let ciphertext = ciphertext.as_bytes().to_vec();
+            let plaintext = decrypt_with_secret(py, PrivKey(TEST_PRIVKEY), PubKey(ephemeral.0), Message(ciphertext.clone()))?;
+            assert_eq!(plaintext.as_bytes(), TEST_MESSAGE);
+
+            // Tampered ciphertext and the wrong recipient are both rejected
+            let mut tampered = ciphertext.clone();
+            tampered[0] ^= 1;
+            let code = |err: PyErr| -> PyResult<String> { err.into_py(py).getattr(py, "code")?.extract(py) };
+            let err = decrypt_with_secret(py, PrivKey(TEST_PRIVKEY), PubKey(ephemeral.0), Message(tampered)).err().unwrap();
+            assert_eq!(code(err)?, "DECRYPTION_FAILED");
+            assert!(decrypt_with_secret(py, PrivKey(CHILD_PRIVKEY_HARD), PubKey(ephemeral.0), Message(ciphertext)).is_err());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_keystore_round_trip() -> PyResult<()> {
+        let keystore = encrypt_keystore(Keypair(TEST_PUBKEY, TEST_PRIVKEY), "correct horse")?;
+        let document: serde_json::Value = serde_json::from_str(&keystore).unwrap();
+        assert_eq!(document["address"], ss58_encode(PubKey(TEST_PUBKEY), 42)?);
+
+        let keypair = decrypt_keystore(&keystore, "correct horse")?;
+        assert_eq!(keypair.0, TEST_PUBKEY);
+        assert_eq!(&keypair.1[..], &TEST_PRIVKEY[..]);
+
+        assert!(decrypt_keystore(&keystore, "wrong horse").is_err());
+
+        // Flipping a byte of the ciphertext fails authentication
+        let mut encoded = base64::decode(document["encoded"].as_str().unwrap()).unwrap();
+        let last = encoded.len() - 1;
+        encoded[last] ^= 1;
+        let mut corrupted = document.clone();
+        corrupted["encoded"] = json!(base64::encode(&encoded));
+        assert!(decrypt_keystore(&corrupted.to_string(), "correct horse").is_err());
+
+        let mut unsupported = document;
+        unsupported["encoding"]["version"] = json!("2");
+        assert!(decrypt_keystore(&unsupported.to_string(), "correct horse").is_err());
+
+        // Inflated scrypt parameters are rejected before any key derivation is attempted
+        let mut inflated: serde_json::Value = serde_json::from_str(&keystore).unwrap();
+        let mut encoded = base64::decode(inflated["encoded"].as_str().unwrap()).unwrap();
+        encoded[KEYSTORE_SALT_LENGTH..KEYSTORE_SALT_LENGTH + 4].copy_from_slice(&(1u32 << 30).to_le_bytes());
+        encoded[KEYSTORE_SALT_LENGTH + 8..KEYSTORE_SALT_LENGTH + 12].copy_from_slice(&(1u32 << 16).to_le_bytes());
+        inflated["encoded"] = json!(base64::encode(&encoded));
+        let err = decrypt_keystore(&inflated.to_string(), "correct horse").err().unwrap();
+        assert!(err.to_string().contains("unsupported scrypt parameters"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_keystore_vector() -> PyResult<()> {
+        // //Alice under the password "polkadot", with salt 0..32 and nonce 100..124. Encoded
+        // apart from this crate, following polkadot-js keyring's `encodePair` layout: scrypt
+        // N, p, r in that order, the PKCS8 header and divider, and the Ed25519-form secret
+        let keystore = r#"{"encoded": "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8AgAAAAQAAAAgAAABkZWZnaGlqa2xtbm9wcXJzdHV2d3h5ensPnYY1qikb/sJrZekPyDGW19dEI3BsCM5kMP+UndDGl4MUuOXZ9a6d+NSCnK0caCqlcu3KZFTmjc+SPvVa36VpbCBWIGBkbYtjZKteO37tQH2VELayhMyraUKvGgHr+8aeLK/FRIQvugo9fImP2Sx92EYaa5G/3Vj63G18SS2xm3rvKRJK", "encoding": {"content": ["pkcs8", "sr25519"], "type": ["scrypt", "xsalsa20-poly1305"], "version": "3"}, "address": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", "meta": {"name": "Alice"}}"#;
+        let keypair = decrypt_keystore(keystore, "polkadot")?;
+        assert_eq!(keypair.0, ALICE_PUBKEY);
+        assert_eq!(&keypair.1[..], &dev_keypair("Alice")?.1[..]);
+        assert!(decrypt_keystore(keystore, "kusama").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_participant_set() -> PyResult<()> {
+        let aggregate = validate_participant_set(vec![PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY), PubKey(ALICE_PUBKEY)])?;
+        let expected = _sum_public_keys(&[PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY), PubKey(ALICE_PUBKEY)])?;
+        assert_eq!(aggregate.0, expected.compress().to_bytes());
+
+        let message = |keys: Vec<PubKey>| validate_participant_set(keys).err().unwrap().to_string();
+        assert!(message(vec![PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY), PubKey(TEST_PUBKEY)]).contains("indices 0 and 2"));
+        assert!(message(vec![PubKey(TEST_PUBKEY), PubKey([0xff; PUBLIC_KEY_LENGTH])]).contains("index 1"));
+        assert!(message(vec![PubKey([0u8; PUBLIC_KEY_LENGTH])]).contains("identity"));
+        assert!(validate_participant_set(vec![]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_public_from_seeds() -> PyResult<()> {
+        let seeds = || vec![Seed([1u8; 32]), Seed([2u8; 32]), Seed([3u8; 32])];
+        let group = group_public_from_seeds(seeds())?;
+        assert_eq!(hex::encode(group.0), "a6e4ac204627a48964776790ca6373fda71c6eca09973f5c189104aa7f55c047");
+
+        let members: Vec<PubKey> = seeds().into_iter().map(|seed| PubKey(pair_from_seed(seed, "ed25519").unwrap().0)).collect();
+        let pair_sum = sum_public_points(PubKey(members[0].0), PubKey(members[1].0))?;
+        assert_eq!(group.0, sum_public_points(pair_sum, PubKey(members[2].0))?.0);
+
+        assert!(group_public_from_seeds(vec![]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_musig2_nonces() -> PyResult<()> {
+        let signers = [(TEST_PUBKEY, TEST_PRIVKEY), (CHILD_PUBKEY, CHILD_PRIVKEY)];
+        let pubkeys = || signers.iter().map(|signer| PubKey(signer.0)).collect::<Vec<PubKey>>();
+        let message = || Message(Vec::from(TEST_MESSAGE));
+
+        let nonces: Vec<_> = signers.iter()
+            .map(|signer| musig2_nonce_gen(PrivKey(signer.1), message(), pubkeys()))
+            .collect::<PyResult<_>>()?;
+        let nonce_lists = || nonces.iter().map(|n| (PubKey(n.0.0), PubKey(n.1.0))).collect::<Vec<_>>();
+        let (r, _) = musig2_nonce_agg(nonce_lists(), pubkeys(), message())?;
+        let partials = signers.iter().zip(nonces.iter())
+            .map(|(signer, n)| musig2_partial_sign(
+                Keypair(signer.0, signer.1), (ScalarBytes(n.2.0), ScalarBytes(n.3.0)), nonce_lists(), pubkeys(), message()))
+            .collect::<PyResult<Vec<_>>>()?;
+        let (aggregate, coefficients) = musig2_key_agg(pubkeys())?;
+        assert_eq!(coefficients.len(), signers.len());
+
+        let sig = musig2_partial_sig_agg(PubKey(r.0), partials)?;
+        assert!(verify(Sig(sig.0), message(), PubKey(aggregate.0))?);
+        assert!(!verify(Sig(sig.0), message(), sum_public_points(PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY))?)?);
+
+        // A signer outside the set, or nonces not in the session, can't sign
+        assert!(musig2_partial_sign(Keypair(CHILD_PUBKEY_HARD, CHILD_PRIVKEY_HARD), (ScalarBytes(nonces[0].2.0), ScalarBytes(nonces[0].3.0)),
+            nonce_lists(), pubkeys(), message()).is_err());
+        assert!(musig2_partial_sign(Keypair(TEST_PUBKEY, TEST_PRIVKEY), (ScalarBytes(nonces[1].2.0), ScalarBytes(nonces[0].3.0)),
+            nonce_lists(), pubkeys(), message()).is_err());
+        assert!(musig2_partial_sig_agg(PubKey(r.0), vec![]).is_err());
+
+        // Fresh nonces are generated every time
+        let again = musig2_nonce_gen(PrivKey(TEST_PRIVKEY), message(), pubkeys())?;
+        assert_ne!(again.0.0, nonces[0].0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_aggregation_proof() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let keys = || vec![PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY)];
+            let (aggregate, proof) = aggregation_proof(py, keys())?;
+            let proof = || Message(proof.as_bytes().to_vec());
+            assert_eq!(proof().0.len(), 32);
+            assert!(verify_aggregation_proof(PubKey(aggregate.0), keys(), proof())?);
+
+            // Order doesn't matter
+            assert!(verify_aggregation_proof(PubKey(aggregate.0), vec![PubKey(CHILD_PUBKEY), PubKey(TEST_PUBKEY)], proof())?);
+
+            // Removing or adding a key invalidates the proof
+            assert!(!verify_aggregation_proof(PubKey(aggregate.0), vec![PubKey(TEST_PUBKEY)], proof())?);
+            assert!(!verify_aggregation_proof(PubKey(aggregate.0), vec![PubKey(TEST_PUBKEY), PubKey(CHILD_PUBKEY), PubKey(CHILD_PUBKEY_HARD)], proof())?);
+            assert!(!verify_aggregation_proof(PubKey(aggregate.0), keys(), Message(proof().0[..31].to_vec()))?);
+            assert!(aggregation_proof(py, vec![]).is_err());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_small_order_points() -> PyResult<()> {
+        let points = small_order_points();
+        assert_eq!(points.len(), 8);
+        for point in points {
+            assert!(CompressedEdwardsY(point.0).decompress().unwrap().is_small_order());
+            // As a Ristretto encoding, only the all-zero one decodes, to the identity
+            assert_eq!(PublicKey::from_bytes(&point.0).is_ok(), point.0 == [0u8; PUBLIC_KEY_LENGTH]);
+        }
+        reject_small_order_public_key(PubKey([0u8; PUBLIC_KEY_LENGTH])).unwrap_err();
+        assert!(_is_small_order(&CompressedRistretto::identity().to_bytes()));
+        reject_small_order_public_key(PubKey(TEST_PUBKEY))
+    }
+
+    #[test]
+    fn test_ss58_encode() -> PyResult<()> {
+        assert_eq!(ss58_encode(PubKey(ALICE_PUBKEY), 0)?, "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5");
+        assert_eq!(ss58_encode(PubKey(ALICE_PUBKEY), 2)?, "HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F");
+        assert_eq!(ss58_encode(PubKey(ALICE_PUBKEY), 42)?, "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY");
+        assert!(ss58_encode(PubKey(ALICE_PUBKEY), 16384).is_err());
+        assert!(ss58_encode(PubKey(ALICE_PUBKEY), -1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ss58_decode() -> PyResult<()> {
+        // 16383 is the largest ID that fits the two-byte prefix
+        for network_id in [0, 2, 42, 64, 255, 16383] {
+            let address = ss58_encode(PubKey(ALICE_PUBKEY), network_id)?;
+            let (pubkey, decoded_id) = ss58_decode(&address)?;
+            assert_eq!(pubkey.0, ALICE_PUBKEY);
+            assert_eq!(decoded_id as i64, network_id);
+        }
+
+        let mut data = bs58::decode("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY").into_vec().unwrap();
+        data[5] ^= 1;
+        assert!(ss58_decode(&bs58::encode(&data).into_string()).is_err());
+        data[0] = 0b1000_0000;
+        assert!(ss58_decode(&bs58::encode(&data).into_string()).is_err());
+        assert!(ss58_decode(&bs58::encode(&data[..20]).into_string()).is_err());
+        assert!(ss58_decode("0OIl").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_public_key_to_account_id() {
+        Python::with_gil(|py| {
+            let account_id = public_key_to_account_id(py, PubKey(ALICE_PUBKEY));
+            assert_eq!(account_id.as_bytes(), hex!("2e3fb4c297a84c5cebc0e78257d213d0927ccc7596044c6ba013dd05522aacba"));
+        })
+    }
+
+    #[test]
+    fn test_keypair_to_subkey_json() -> PyResult<()> {
+        let document: serde_json::Value = serde_json::from_str(&keypair_to_subkey_json(dev_keypair("Alice")?, 42, None)?).unwrap();
+        assert_eq!(document["publicKey"], "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d");
+        assert_eq!(document["accountId"], document["publicKey"]);
+        assert_eq!(document["ss58Address"], "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY");
+        assert!(document.get("secretPhrase").is_none());
+
+        let document: serde_json::Value = serde_json::from_str(&keypair_to_subkey_json(pair_from_mnemonic(DEV_PHRASE, "")?, 0, Some(DEV_PHRASE.to_string()))?).unwrap();
+        assert_eq!(document["secretPhrase"], DEV_PHRASE);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extended_key_fingerprint() {
+        Python::with_gil(|py| {
+            let fingerprint = extended_key_fingerprint(py, ExtendedPubKey(TEST_CHAIN_CODE, TEST_PUBKEY));
+            let fingerprint_again = extended_key_fingerprint(py, ExtendedPubKey(TEST_CHAIN_CODE, TEST_PUBKEY));
+            let child_fingerprint = extended_key_fingerprint(py, ExtendedPubKey(CHILD_CHAIN_CODE, CHILD_PUBKEY));
+
+            assert_eq!(fingerprint.as_bytes().len(), FINGERPRINT_LENGTH);
+            assert_eq!(fingerprint.as_bytes(), fingerprint_again.as_bytes());
+            assert_ne!(fingerprint.as_bytes(), child_fingerprint.as_bytes());
+        })
+    }
+
+    #[test]
+    fn test_derive_counter_keypair() -> PyResult<()> {
+        let first = derive_counter_keypair(PrivKey(TEST_PRIVKEY), 0)?;
+        let first_again = derive_counter_keypair(PrivKey(TEST_PRIVKEY), 0)?;
+        let second = derive_counter_keypair(PrivKey(TEST_PRIVKEY), 1)?;
+
+        assert_eq!(first.0, first_again.0);
+        assert_eq!(&first.1[..], &first_again.1[..]);
+        assert_ne!(first.0, second.0);
+        assert_ne!(first.0, TEST_PUBKEY);
+        Ok(())
+    }
 }
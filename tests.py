@@ -26,6 +26,14 @@ class MyTestCase(unittest.TestCase):
     chain_code = bytes.fromhex('7eadeb0f985ffcab9e50f25a19c1e4c8c2f4cd742049fc35e07c684040057e9a')
     child_index = b"\x01\x02\x03\x04"
 
+    def test_constants(self):
+        self.assertEqual(sr25519.PUBLIC_KEY_LENGTH, 32)
+        self.assertEqual(sr25519.SECRET_KEY_LENGTH, 64)
+        self.assertEqual(sr25519.MINI_SECRET_KEY_LENGTH, 32)
+        self.assertEqual(sr25519.SIGNATURE_LENGTH, 64)
+        self.assertEqual(sr25519.CHAIN_CODE_LENGTH, 32)
+        self.assertEqual(sr25519.DEFAULT_CONTEXT, b'substrate')
+
     def test_sign_and_verify_message(self):
         # Get private and public key from seed
         public_key, private_key = sr25519.pair_from_seed(bytes(self.seed))
@@ -39,6 +47,18 @@ class MyTestCase(unittest.TestCase):
         # Verify message with signature
         self.assertTrue(sr25519.verify(signature, self.message, public_key))
 
+    def test_sign_encoding(self):
+        public_key, private_key = sr25519.pair_from_seed(bytes(self.seed))
+
+        signature = sr25519.sign((public_key, private_key), self.message, encoding='hex')
+        self.assertIsInstance(signature, str)
+        self.assertTrue(sr25519.verify(bytes.fromhex(signature[2:]), self.message, public_key))
+
+        self.assertIsInstance(sr25519.sign((public_key, private_key), self.message, 'base58'), str)
+        self.assertIsInstance(sr25519.sign((public_key, private_key), self.message), bytes)
+        with self.assertRaises(ValueError):
+            sr25519.sign((public_key, private_key), self.message, encoding='base64')
+
     def test_derive_soft(self):
         # Get private and public key from seed
         public_key, private_key = sr25519.pair_from_seed(bytes(self.seed))
@@ -86,6 +106,135 @@ class MyTestCase(unittest.TestCase):
 
         self.assertTrue(sr25519.verify(signature, self.message, child_pubkey))
 
+    def test_bytes_like_arguments(self):
+        public_key, private_key = sr25519.pair_from_seed(bytearray(self.seed))
+
+        signature = sr25519.sign((public_key, private_key), bytearray(self.message))
+
+        self.assertTrue(sr25519.verify(memoryview(signature), memoryview(self.message), memoryview(public_key)))
+
+        # Tuple items are bytes-like too
+        signature = sr25519.sign((bytearray(public_key), bytearray(private_key)), self.message)
+        self.assertTrue(sr25519.verify(signature, self.message, public_key))
+
+    def test_str_message(self):
+        public_key, private_key = sr25519.pair_from_seed(bytes(self.seed))
+
+        signature = sr25519.sign((public_key, private_key), 'tést')
+        self.assertTrue(sr25519.verify(signature, 'tést'.encode('utf-8'), public_key))
+
+        signature = sr25519.sign((public_key, private_key), self.message)
+        self.assertTrue(sr25519.verify(signature, self.message.decode(), public_key))
+
+    def test_error_codes(self):
+        public_key, private_key = sr25519.pair_from_seed(bytes(self.seed))
+
+        with self.assertRaises(sr25519.Sr25519Error) as cm:
+            sr25519.verify(bytes(64), self.message, public_key)
+        self.assertEqual(cm.exception.code, 'INVALID_SIGNATURE')
+
+        with self.assertRaises(sr25519.Sr25519Error) as cm:
+            sr25519.sign((public_key, b'\xff' * 64), self.message)
+        self.assertEqual(cm.exception.code, 'INVALID_KEY')
+
+        # Still a ValueError, for handlers written before the codes existed
+        with self.assertRaises(ValueError) as cm:
+            sr25519.verify_batch([], [self.message], [])
+        self.assertEqual(cm.exception.code, 'INVALID_LENGTH')
+
+    def test_keypair_class(self):
+        public_key, private_key = sr25519.pair_from_seed(bytes(self.seed))
+        keypair = sr25519.Keypair(public_key, private_key)
+
+        self.assertEqual(keypair.public_key, public_key)
+        self.assertEqual(keypair.secret_key, private_key)
+        self.assertEqual(keypair.public(), public_key)
+
+        # Signatures from the class and from functions given the class both verify
+        self.assertTrue(sr25519.verify(keypair.sign(self.message), self.message, public_key))
+        self.assertTrue(sr25519.verify(sr25519.sign(keypair, self.message), self.message, public_key))
+
+    def test_signer_class(self):
+        public_key, private_key = sr25519.pair_from_seed(bytes(self.seed))
+        signer = sr25519.Signer((public_key, private_key))
+
+        for i in range(10):
+            message = b'message %d' % i
+            self.assertTrue(sr25519.verify(signer.sign(message), message, public_key))
+
+    def test_verifier_class(self):
+        public_key, private_key = sr25519.pair_from_seed(bytes(self.seed))
+        verifier = sr25519.Verifier(public_key)
+
+        for i in range(10):
+            message = b'message %d' % i
+            self.assertTrue(verifier.verify(sr25519.sign((public_key, private_key), message), message))
+
+        with self.assertRaises(ValueError):
+            sr25519.Verifier(b'\xff' * 32)
+
+    def test_signing_context_class(self):
+        public_key, private_key = sr25519.pair_from_seed(bytes(self.seed))
+        context = sr25519.SigningContext(b'x')
+
+        signature = context.sign((public_key, private_key), self.message)
+        self.assertTrue(context.verify(signature, self.message, public_key))
+        self.assertFalse(sr25519.SigningContext(b'y').verify(signature, self.message, public_key))
+
+    def test_load_secret(self):
+        public_key, private_key = sr25519.pair_from_seed(bytes(self.seed))
+
+        with sr25519.load_secret(private_key) as sk:
+            signature = sk.sign(self.message, b'substrate')
+
+        self.assertTrue(sr25519.verify(signature, self.message, public_key))
+
+        # The secret is wiped once the block exits
+        with self.assertRaises(ValueError):
+            sk.sign(self.message, b'substrate')
+
+    def test_musig(self):
+        keypairs = [
+            sr25519.pair_from_seed(bytes(self.seed)),
+            sr25519.pair_from_seed(bytes(32)),
+        ]
+        sessions = [sr25519.MuSig(keypair, self.message) for keypair in keypairs]
+
+        def exchange(values, add):
+            for session, (public_key, _) in zip(sessions, keypairs):
+                for other, value in zip(keypairs, values):
+                    if other[0] != public_key:
+                        add(session, other[0], value)
+
+        exchange([s.commitment() for s in sessions], sr25519.MuSig.add_their_commitment)
+        exchange([s.reveal() for s in sessions], sr25519.MuSig.add_their_reveal)
+        exchange([s.cosign() for s in sessions], sr25519.MuSig.add_their_cosignature)
+
+        signature = sessions[0].signature()
+        self.assertEqual(signature, sessions[1].signature())
+        self.assertTrue(sr25519.verify(signature, self.message, sessions[0].public_key()))
+
+    def test_pair_from_seed_expansion_mode(self):
+        keypair = sr25519.pair_from_seed(bytes(self.seed))
+
+        self.assertEqual(keypair, sr25519.pair_from_seed(bytes(self.seed), 'ed25519'))
+        self.assertNotEqual(keypair, sr25519.pair_from_seed(bytes(self.seed), 'uniform'))
+        with self.assertRaises(ValueError):
+            sr25519.pair_from_seed(bytes(self.seed), 'other')
+
+    def test_pair_from_mnemonic(self):
+        mnemonic = 'daughter song common combine misery cotton audit morning stuff weasel flee field'
+
+        # Matches deriving the mini secret with py-bip39-bindings first
+        self.assertEqual(
+            sr25519.pair_from_mnemonic(mnemonic),
+            sr25519.pair_from_seed(bytes(self.seed))
+        )
+        self.assertEqual(
+            sr25519.pair_from_mnemonic(mnemonic, 'password'),
+            sr25519.pair_from_seed(bytes(bip39.bip39_to_mini_secret(mnemonic, 'password')))
+        )
+
 
 if __name__ == '__main__':
     unittest.main()